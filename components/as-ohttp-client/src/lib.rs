@@ -301,3 +301,6 @@ mod tests {
 }
 
 uniffi::include_scaffolding!("as_ohttp_client");
+
+pub const UNIFFI_NAMESPACE: &str = "as_ohttp_client";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -14,6 +14,7 @@ CREATE TABLE IF NOT EXISTS addresses_data (
     country             TEXT NOT NULL,  -- ISO 3166
     tel                 TEXT NOT NULL,  -- Stored in E.164 format
     email               TEXT NOT NULL,
+    label               TEXT NOT NULL DEFAULT '',  -- eg, "Home" or "Work"
 
     time_created        INTEGER NOT NULL,
     time_last_used      INTEGER NOT NULL,
@@ -23,6 +24,12 @@ CREATE TABLE IF NOT EXISTS addresses_data (
     sync_change_counter INTEGER NOT NULL
 );
 
+-- Used by `search_addresses()` for case-insensitive prefix searches.
+CREATE INDEX IF NOT EXISTS addresses_name_idx ON addresses_data(name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS addresses_organization_idx ON addresses_data(organization COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS addresses_street_address_idx ON addresses_data(street_address COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS addresses_email_idx ON addresses_data(email COLLATE NOCASE);
+
 -- What's on the server as the JSON payload.
 CREATE TABLE IF NOT EXISTS addresses_mirror (
     guid                TEXT NOT NULL PRIMARY KEY CHECK(length(guid) != 0),
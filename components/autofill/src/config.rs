@@ -0,0 +1,61 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+// Knobs for the address reconciliation logic. Apps are expected to read these
+// from their Nimbus feature and pass them when creating the store, so changes
+// to how we normalize and dedupe addresses can be rolled out as experiments.
+
+use crate::normalize::{normalize_address_contact, normalize_address_location};
+
+/// How much we normalize address fields as they are saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizationLevel {
+    /// Save the fields as they were given to us.
+    None,
+    /// Normalize the country, and the fields whose format depends on it (the
+    /// region and postal code).
+    Location,
+    /// Also normalize the phone number and email.
+    #[default]
+    Full,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutofillFeatureConfig {
+    /// Whether imported addresses are considered duplicates if their fields
+    /// only differ in case, whitespace and, for phone numbers, punctuation.
+    /// If false, only addresses whose fields are identical are merged.
+    pub fuzzy_dedupe: bool,
+    pub normalization: NormalizationLevel,
+}
+
+impl Default for AutofillFeatureConfig {
+    fn default() -> Self {
+        Self {
+            fuzzy_dedupe: true,
+            normalization: NormalizationLevel::default(),
+        }
+    }
+}
+
+impl AutofillFeatureConfig {
+    /// Normalizes the fields of an address according to `self.normalization`.
+    pub(crate) fn normalize_address(
+        &self,
+        country: &mut String,
+        address_level1: &mut String,
+        postal_code: &mut String,
+        tel: &mut String,
+        email: &mut String,
+    ) {
+        if self.normalization == NormalizationLevel::None {
+            return;
+        }
+        normalize_address_location(country, address_level1, postal_code);
+        if self.normalization == NormalizationLevel::Full {
+            normalize_address_contact(tel, email, country);
+        }
+    }
+}
@@ -3,25 +3,40 @@
 * file, You can obtain one at http://mozilla.org/MPL/2.0/.
 */
 
+use crate::config::AutofillFeatureConfig;
 use crate::db::{
     models::{
-        address::{InternalAddress, UpdatableAddressFields},
+        address::{
+            Address, AddressSearchField, AddressValidationError, AddressValidationReason,
+            ImportMetrics, InternalAddress, UpdatableAddressFields,
+        },
         Metadata,
     },
     schema::{ADDRESS_COMMON_COLS, ADDRESS_COMMON_VALS},
 };
 use crate::error::*;
+use crate::normalize::{comparison_key, normalize_tel, normalize_whitespace};
 
 use rusqlite::{Connection, Transaction};
+use sql_support::ConnExt;
+use std::collections::HashSet;
 use sync_guid::Guid;
 use types::Timestamp;
 
 pub(crate) fn add_address(
     conn: &Connection,
-    new: UpdatableAddressFields,
+    config: &AutofillFeatureConfig,
+    mut new: UpdatableAddressFields,
 ) -> Result<InternalAddress> {
     let tx = conn.unchecked_transaction()?;
     let now = Timestamp::now();
+    config.normalize_address(
+        &mut new.country,
+        &mut new.address_level1,
+        &mut new.postal_code,
+        &mut new.tel,
+        &mut new.email,
+    );
 
     // We return an InternalAddress, so set it up first, including the missing
     // fields, before we insert it.
@@ -37,6 +52,7 @@ pub(crate) fn add_address(
         country: new.country,
         tel: new.tel,
         email: new.email,
+        label: new.label,
         metadata: Metadata {
             time_created: now,
             time_last_modified: now,
@@ -73,6 +89,7 @@ pub(crate) fn add_internal_address(tx: &Transaction<'_>, address: &InternalAddre
             ":country": address.country,
             ":tel": address.tel,
             ":email": address.email,
+            ":label": address.label,
             ":time_created": address.metadata.time_created,
             ":time_last_used": address.metadata.time_last_used,
             ":time_last_modified": address.metadata.time_last_modified,
@@ -115,15 +132,156 @@ pub(crate) fn get_all_addresses(conn: &Connection) -> Result<Vec<InternalAddress
     Ok(addresses)
 }
 
+/// Returns up to `limit` addresses, most relevant first, for use in form-fill
+/// dropdowns. Addresses used most recently come first, with ties broken by how
+/// often, and then how recently, they were used and created.
+pub(crate) fn get_addresses_ranked(conn: &Connection, limit: u32) -> Result<Vec<InternalAddress>> {
+    let sql = format!(
+        "SELECT
+            {common_cols},
+            sync_change_counter
+        FROM addresses_data
+        ORDER BY time_last_used DESC, times_used DESC, time_created DESC
+        LIMIT :limit",
+        common_cols = ADDRESS_COMMON_COLS
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let addresses = stmt
+        .query_map(
+            rusqlite::named_params! { ":limit": limit },
+            InternalAddress::from_row,
+        )?
+        .collect::<std::result::Result<Vec<InternalAddress>, _>>()?;
+    Ok(addresses)
+}
+
+/// Returns the addresses where `field` starts with `prefix`, ignoring case, in
+/// the same order as `get_addresses_ranked`. An empty prefix matches every
+/// address.
+pub(crate) fn search_addresses(
+    conn: &Connection,
+    field: AddressSearchField,
+    prefix: &str,
+) -> Result<Vec<InternalAddress>> {
+    // `LIKE` is case-insensitive for ASCII and can use the `NOCASE` index on
+    // the column, as long as the pattern doesn't start with a wildcard.
+    let sql = format!(
+        "SELECT
+            {common_cols},
+            sync_change_counter
+        FROM addresses_data
+        WHERE {column} LIKE :pattern ESCAPE '\\'
+        ORDER BY time_last_used DESC, times_used DESC, time_created DESC",
+        common_cols = ADDRESS_COMMON_COLS,
+        column = field.column(),
+    );
+    let pattern = format!("{}%", escape_like(prefix));
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let addresses = stmt
+        .query_map(
+            rusqlite::named_params! { ":pattern": pattern },
+            InternalAddress::from_row,
+        )?
+        .collect::<std::result::Result<Vec<InternalAddress>, _>>()?;
+    Ok(addresses)
+}
+
+// Escapes the `LIKE` wildcards in `s`, for use with `ESCAPE '\'`.
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 /// Updates just the "updatable" columns - suitable for exposure as a public
 /// API.
 pub(crate) fn update_address(
     conn: &Connection,
+    config: &AutofillFeatureConfig,
     guid: &Guid,
     address: &UpdatableAddressFields,
 ) -> Result<()> {
     let tx = conn.unchecked_transaction()?;
-    tx.execute(
+    update_address_fields(&tx, config, guid, address)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Updates the "updatable" columns of many addresses in a single transaction.
+/// Every address is checked first, and if any of them can't be updated,
+/// nothing is changed and the problems are returned. An empty result means all
+/// of the addresses were updated.
+pub(crate) fn update_addresses(
+    conn: &Connection,
+    config: &AutofillFeatureConfig,
+    addresses: Vec<Address>,
+) -> Result<Vec<AddressValidationError>> {
+    let tx = conn.unchecked_transaction()?;
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+    for address in addresses {
+        let guid = Guid::new(&address.guid);
+        let fields = UpdatableAddressFields {
+            name: address.name,
+            organization: address.organization,
+            street_address: address.street_address,
+            address_level3: address.address_level3,
+            address_level2: address.address_level2,
+            address_level1: address.address_level1,
+            postal_code: address.postal_code,
+            country: address.country,
+            tel: address.tel,
+            email: address.email,
+            label: address.label,
+        };
+        let reason = if !seen.insert(guid.clone()) {
+            Some(AddressValidationReason::DuplicateGuid)
+        } else if is_empty_address(&fields) {
+            Some(AddressValidationReason::EmptyAddress)
+        } else if !update_address_fields(&tx, config, &guid, &fields)? {
+            Some(AddressValidationReason::NoSuchRecord)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            errors.push(AddressValidationError {
+                guid: guid.to_string(),
+                reason,
+            });
+        }
+    }
+    // Dropping the transaction without committing rolls back any updates we
+    // already made.
+    if errors.is_empty() {
+        tx.commit()?;
+    }
+    Ok(errors)
+}
+
+// Normalizes and writes the "updatable" columns of an address. Returns false
+// if there's no address with the guid.
+fn update_address_fields(
+    tx: &Transaction<'_>,
+    config: &AutofillFeatureConfig,
+    guid: &Guid,
+    address: &UpdatableAddressFields,
+) -> Result<bool> {
+    let mut address = address.clone();
+    config.normalize_address(
+        &mut address.country,
+        &mut address.address_level1,
+        &mut address.postal_code,
+        &mut address.tel,
+        &mut address.email,
+    );
+    let rows_changed = tx.execute(
         "UPDATE addresses_data
         SET name                = :name,
             organization        = :organization,
@@ -135,6 +293,7 @@ pub(crate) fn update_address(
             country             = :country,
             tel                 = :tel,
             email               = :email,
+            label               = :label,
             sync_change_counter = sync_change_counter + 1
         WHERE guid              = :guid",
         rusqlite::named_params! {
@@ -148,12 +307,28 @@ pub(crate) fn update_address(
             ":country": address.country,
             ":tel": address.tel,
             ":email": address.email,
+            ":label": address.label,
             ":guid": guid,
         },
     )?;
+    Ok(rows_changed != 0)
+}
 
-    tx.commit()?;
-    Ok(())
+fn is_empty_address(address: &UpdatableAddressFields) -> bool {
+    [
+        &address.name,
+        &address.organization,
+        &address.street_address,
+        &address.address_level3,
+        &address.address_level2,
+        &address.address_level1,
+        &address.postal_code,
+        &address.country,
+        &address.tel,
+        &address.email,
+    ]
+    .iter()
+    .all(|value| value.trim().is_empty())
 }
 
 /// Updates all fields including metadata - although the change counter gets
@@ -177,6 +352,7 @@ pub(crate) fn update_internal_address(
             country             = :country,
             tel                 = :tel,
             email               = :email,
+            label               = :label,
             time_created        = :time_created,
             time_last_used      = :time_last_used,
             time_last_modified  = :time_last_modified,
@@ -194,6 +370,7 @@ pub(crate) fn update_internal_address(
             ":country": address.country,
             ":tel": address.tel,
             ":email": address.email,
+            ":label": address.label,
             ":time_created": address.metadata.time_created,
             ":time_last_used": address.metadata.time_last_used,
             ":time_last_modified": address.metadata.time_last_modified,
@@ -223,6 +400,206 @@ pub(crate) fn delete_address(conn: &Connection, guid: &Guid) -> Result<bool> {
     Ok(exists)
 }
 
+/// Imports addresses from another source, such as a desktop profile being
+/// migrated. Fields are normalized before being compared with the existing
+/// addresses, and an address which duplicates an existing one is merged into
+/// it rather than being added again. How loosely fields are compared depends
+/// on `config.fuzzy_dedupe`.
+pub(crate) fn import_addresses(
+    conn: &Connection,
+    config: &AutofillFeatureConfig,
+    addresses: Vec<Address>,
+) -> Result<ImportMetrics> {
+    let tx = conn.unchecked_transaction()?;
+    let now = Timestamp::now();
+    let mut existing = get_all_addresses(&tx)?;
+    let mut metrics = ImportMetrics::default();
+
+    for address in addresses {
+        let mut imported = normalized_import(address, config, now);
+        if fields(&imported).iter().all(|value| value.is_empty()) {
+            metrics.skipped += 1;
+            continue;
+        }
+        if let Some(local) = existing
+            .iter_mut()
+            .find(|local| is_duplicate(&imported, local, config.fuzzy_dedupe))
+        {
+            if merge_imported(local, &imported, now) {
+                update_internal_address(&tx, local, true)?;
+                metrics.merged += 1;
+            } else {
+                metrics.skipped += 1;
+            }
+            continue;
+        }
+        // Keep the guid if we can, so that syncing the imported address later
+        // reconciles with the server record instead of creating a duplicate.
+        if !imported.guid.is_valid_for_sync_server() || guid_exists(&tx, &imported.guid)? {
+            imported.guid = Guid::random();
+        }
+        add_internal_address(&tx, &imported)?;
+        existing.push(imported);
+        metrics.added += 1;
+    }
+
+    tx.commit()?;
+    Ok(metrics)
+}
+
+fn normalized_import(
+    address: Address,
+    config: &AutofillFeatureConfig,
+    now: Timestamp,
+) -> InternalAddress {
+    let time_created = match address.time_created {
+        t if t > 0 => Timestamp(t as u64),
+        _ => now,
+    };
+    let mut imported = InternalAddress {
+        guid: Guid::new(&address.guid),
+        name: normalize_whitespace(&address.name),
+        organization: normalize_whitespace(&address.organization),
+        street_address: normalize_whitespace(&address.street_address),
+        address_level3: normalize_whitespace(&address.address_level3),
+        address_level2: normalize_whitespace(&address.address_level2),
+        address_level1: address.address_level1,
+        postal_code: address.postal_code,
+        country: address.country,
+        tel: address.tel,
+        email: address.email,
+        label: normalize_whitespace(&address.label),
+        metadata: Metadata {
+            time_created,
+            time_last_used: Timestamp(address.time_last_used.unwrap_or_default().max(0) as u64),
+            time_last_modified: now,
+            times_used: address.times_used.max(0),
+            sync_change_counter: 0,
+        },
+    };
+    config.normalize_address(
+        &mut imported.country,
+        &mut imported.address_level1,
+        &mut imported.postal_code,
+        &mut imported.tel,
+        &mut imported.email,
+    );
+    imported
+}
+
+// The indexes of fields in the arrays returned by `fields()` and `fields_mut()`.
+const STREET_ADDRESS_FIELD: usize = 2;
+const POSTAL_CODE_FIELD: usize = 6;
+const TEL_FIELD: usize = 8;
+const EMAIL_FIELD: usize = 9;
+
+// The fields which identify an address, unlike e.g. a name, which many
+// addresses can share.
+const IDENTIFYING_FIELDS: [usize; 4] = [
+    STREET_ADDRESS_FIELD,
+    POSTAL_CODE_FIELD,
+    TEL_FIELD,
+    EMAIL_FIELD,
+];
+
+fn fields(address: &InternalAddress) -> [&String; 11] {
+    [
+        &address.name,
+        &address.organization,
+        &address.street_address,
+        &address.address_level3,
+        &address.address_level2,
+        &address.address_level1,
+        &address.postal_code,
+        &address.country,
+        &address.tel,
+        &address.email,
+        &address.label,
+    ]
+}
+
+fn fields_mut(address: &mut InternalAddress) -> [&mut String; 11] {
+    [
+        &mut address.name,
+        &mut address.organization,
+        &mut address.street_address,
+        &mut address.address_level3,
+        &mut address.address_level2,
+        &mut address.address_level1,
+        &mut address.postal_code,
+        &mut address.country,
+        &mut address.tel,
+        &mut address.email,
+        &mut address.label,
+    ]
+}
+
+// Whether two values of the field at `index` are the same. Fuzzy matching
+// ignores differences in case and whitespace, and `tel` is the only
+// field where punctuation isn't significant.
+fn field_matches(index: usize, a: &str, b: &str, fuzzy: bool) -> bool {
+    if !fuzzy {
+        a == b
+    } else if index == TEL_FIELD {
+        normalize_tel(a) == normalize_tel(b)
+    } else {
+        comparison_key(a) == comparison_key(b)
+    }
+}
+
+// Whether `a` and `b` are the same address: they must share the value of at
+// least one identifying field, and one must have a subset of the other's
+// fields. Without the former, a sparse address with only a name would be a
+// duplicate of every address with that name.
+fn is_duplicate(a: &InternalAddress, b: &InternalAddress, fuzzy: bool) -> bool {
+    let (a_fields, b_fields) = (fields(a), fields(b));
+    let shares_identifying_field = IDENTIFYING_FIELDS.iter().any(|&i| {
+        !a_fields[i].is_empty()
+            && !b_fields[i].is_empty()
+            && field_matches(i, a_fields[i], b_fields[i], fuzzy)
+    });
+    shares_identifying_field && (is_subset(a, b, fuzzy) || is_subset(b, a, fuzzy))
+}
+
+// Whether every non-empty field in `a` has the same value in `b`.
+fn is_subset(a: &InternalAddress, b: &InternalAddress, fuzzy: bool) -> bool {
+    fields(a)
+        .into_iter()
+        .zip(fields(b))
+        .enumerate()
+        .all(|(i, (a, b))| a.is_empty() || field_matches(i, a, b, fuzzy))
+}
+
+// Fills in the fields missing from `local` and merges the usage metadata.
+// Returns true if `local` changed.
+fn merge_imported(local: &mut InternalAddress, imported: &InternalAddress, now: Timestamp) -> bool {
+    let mut changed = false;
+    for (local_value, imported_value) in fields_mut(local).into_iter().zip(fields(imported)) {
+        if local_value.is_empty() && !imported_value.is_empty() {
+            *local_value = imported_value.clone();
+            changed = true;
+        }
+    }
+    let metadata = local.metadata;
+    local.metadata.merge(&imported.metadata, None);
+    // We don't want the import itself to count as a modification unless it
+    // actually changed the address.
+    local.metadata.time_last_modified = metadata.time_last_modified;
+    if changed {
+        local.metadata.time_last_modified = now;
+    }
+    changed || local.metadata != metadata
+}
+
+fn guid_exists(conn: &Connection, guid: &Guid) -> Result<bool> {
+    Ok(conn.exists(
+        "SELECT 1 FROM addresses_data WHERE guid = :guid
+         UNION ALL
+         SELECT 1 FROM addresses_tombstones WHERE guid = :guid",
+        rusqlite::named_params! { ":guid": guid },
+    )?)
+}
+
 pub fn touch(conn: &Connection, guid: &Guid) -> Result<()> {
     let tx = conn.unchecked_transaction()?;
     let now_ms = Timestamp::now();
@@ -294,6 +671,7 @@ mod tests {
 
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "jane doe".to_string(),
                 street_address: "123 Main Street".to_string(),
@@ -355,6 +733,7 @@ mod tests {
 
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "jane doe".to_string(),
                 street_address: "123 Second Avenue".to_string(),
@@ -368,6 +747,7 @@ mod tests {
 
         let saved_address2 = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "john deer".to_string(),
                 street_address: "123 First Avenue".to_string(),
@@ -382,6 +762,7 @@ mod tests {
         // creating a third address with a tombstone to ensure it's not returned
         let saved_address3 = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "abraham lincoln".to_string(),
                 street_address: "1600 Pennsylvania Ave NW".to_string(),
@@ -418,6 +799,7 @@ mod tests {
 
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "john doe".to_string(),
                 street_address: "1300 Broadway".to_string(),
@@ -434,6 +816,7 @@ mod tests {
         let expected_name = "john paul deer".to_string();
         let update_result = update_address(
             &db,
+            &AutofillFeatureConfig::default(),
             &saved_address.guid,
             &UpdatableAddressFields {
                 name: expected_name.clone(),
@@ -533,6 +916,7 @@ mod tests {
 
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "jane doe".to_string(),
                 street_address: "123 Second Avenue".to_string(),
@@ -550,6 +934,7 @@ mod tests {
         // do it again, but with it in the mirror.
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "jane doe".to_string(),
                 street_address: "123 Second Avenue".to_string(),
@@ -636,6 +1021,7 @@ mod tests {
         let db = new_mem_db();
         let saved_address = add_address(
             &db,
+            &AutofillFeatureConfig::default(),
             UpdatableAddressFields {
                 name: "jane doe".to_string(),
                 street_address: "123 Second Avenue".to_string(),
@@ -658,4 +1044,492 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_addresses_ranked() -> Result<()> {
+        let db = new_mem_db();
+        let mut guids = Vec::new();
+        // (name, time_last_used, times_used)
+        for (name, last_used, times_used) in [
+            ("never used", 0, 0),
+            ("used long ago", 1000, 10),
+            ("used recently", 5000, 1),
+            ("used recently and often", 5000, 3),
+        ] {
+            let address = add_address(
+                &db,
+                &AutofillFeatureConfig::default(),
+                UpdatableAddressFields {
+                    name: name.to_string(),
+                    ..UpdatableAddressFields::default()
+                },
+            )?;
+            db.execute(
+                "UPDATE addresses_data
+                 SET time_last_used = :time_last_used, times_used = :times_used
+                 WHERE guid = :guid",
+                rusqlite::named_params! {
+                    ":time_last_used": last_used,
+                    ":times_used": times_used,
+                    ":guid": address.guid,
+                },
+            )?;
+            guids.push(address.guid);
+        }
+
+        let ranked = get_addresses_ranked(&db, 10)?
+            .into_iter()
+            .map(|a| a.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![
+                "used recently and often",
+                "used recently",
+                "used long ago",
+                "never used",
+            ]
+        );
+
+        assert_eq!(get_addresses_ranked(&db, 2)?.len(), 2);
+        assert!(get_addresses_ranked(&db, 0)?.is_empty());
+
+        // Touching an address moves it to the top.
+        touch(&db, &guids[0])?;
+        assert_eq!(get_addresses_ranked(&db, 1)?[0].guid, guids[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_addresses() -> Result<()> {
+        let db = new_mem_db();
+        for (name, street_address, email) in [
+            ("Jane Doe", "123 Maple Lane", "jane@example.com"),
+            ("John Doe", "1 Main St", "john@example.com"),
+            ("Janet 50%_off", "12 Elm St", "janet@example.org"),
+        ] {
+            add_address(
+                &db,
+                &AutofillFeatureConfig::default(),
+                UpdatableAddressFields {
+                    name: name.to_string(),
+                    street_address: street_address.to_string(),
+                    email: email.to_string(),
+                    ..UpdatableAddressFields::default()
+                },
+            )?;
+        }
+
+        let search = |field, prefix| -> Result<Vec<String>> {
+            let mut names = search_addresses(&db, field, prefix)?
+                .into_iter()
+                .map(|a| a.name)
+                .collect::<Vec<_>>();
+            names.sort();
+            Ok(names)
+        };
+
+        assert_eq!(
+            search(AddressSearchField::Name, "jan")?,
+            vec!["Jane Doe", "Janet 50%_off"]
+        );
+        assert_eq!(search(AddressSearchField::Name, "JOHN")?, vec!["John Doe"]);
+        assert!(search(AddressSearchField::Name, "Doe")?.is_empty());
+        assert_eq!(
+            search(AddressSearchField::StreetAddress, "12")?,
+            vec!["Jane Doe", "Janet 50%_off"]
+        );
+        assert_eq!(
+            search(AddressSearchField::Email, "jane@")?,
+            vec!["Jane Doe"]
+        );
+        assert!(search(AddressSearchField::Organization, "Mozilla")?.is_empty());
+        assert_eq!(search(AddressSearchField::Name, "")?.len(), 3);
+
+        // Wildcards in the prefix are matched literally.
+        assert!(search(AddressSearchField::Name, "J%")?.is_empty());
+        assert!(search(AddressSearchField::Name, "Jan_")?.is_empty());
+        assert_eq!(
+            search(AddressSearchField::Name, "Janet 50%_")?,
+            vec!["Janet 50%_off"]
+        );
+
+        // The search uses the index rather than scanning the table.
+        let plan: String = db.query_row(
+            "EXPLAIN QUERY PLAN SELECT guid FROM addresses_data
+             WHERE name LIKE 'jan%' ESCAPE '\\'",
+            [],
+            |row| row.get(3),
+        )?;
+        assert!(plan.contains("addresses_name_idx"), "{}", plan);
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_location_normalized() -> Result<()> {
+        let db = new_mem_db();
+        let address = add_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            UpdatableAddressFields {
+                name: "jane doe".to_string(),
+                address_level1: "california".to_string(),
+                postal_code: "940431234".to_string(),
+                country: "United States".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+        let address = get_address(&db, &address.guid)?;
+        assert_eq!(address.country, "US");
+        assert_eq!(address.address_level1, "CA");
+        assert_eq!(address.postal_code, "94043-1234");
+
+        update_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            &address.guid,
+            &UpdatableAddressFields {
+                name: "jane doe".to_string(),
+                address_level1: "ontario".to_string(),
+                postal_code: "m5v3l9".to_string(),
+                country: "Canada".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+        let address = get_address(&db, &address.guid)?;
+        assert_eq!(address.country, "CA");
+        assert_eq!(address.address_level1, "ON");
+        assert_eq!(address.postal_code, "M5V 3L9");
+        Ok(())
+    }
+
+    #[test]
+    fn test_address_contact_normalized() -> Result<()> {
+        let db = new_mem_db();
+        let address = add_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            UpdatableAddressFields {
+                name: "jane doe".to_string(),
+                country: "US".to_string(),
+                tel: "(555) 123-4567".to_string(),
+                email: " jane@EXAMPLE.com ".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+        let address = get_address(&db, &address.guid)?;
+        assert_eq!(address.tel, "+15551234567");
+        assert_eq!(address.email, "jane@example.com");
+
+        update_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            &address.guid,
+            &UpdatableAddressFields {
+                name: "jane doe".to_string(),
+                country: "United Kingdom".to_string(),
+                tel: "020 7946 0018".to_string(),
+                email: "not an email".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+        let address = get_address(&db, &address.guid)?;
+        assert_eq!(address.tel, "+442079460018");
+        assert_eq!(address.email, "not an email");
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_addresses() -> Result<()> {
+        let db = new_mem_db();
+        let config = AutofillFeatureConfig::default();
+        let mut guids = Vec::new();
+        for name in ["jane doe", "john doe"] {
+            let address = add_address(
+                &db,
+                &config,
+                UpdatableAddressFields {
+                    name: name.to_string(),
+                    country: "USA".to_string(),
+                    ..UpdatableAddressFields::default()
+                },
+            )?;
+            guids.push(address.guid.to_string());
+        }
+        let fix_country = |guid: &str| Address {
+            guid: guid.to_string(),
+            name: "jane doe".to_string(),
+            country: "united states".to_string(),
+            ..Address::default()
+        };
+
+        // A batch with problems changes nothing.
+        let errors = update_addresses(
+            &db,
+            &config,
+            vec![
+                fix_country(&guids[0]),
+                fix_country(&guids[0]),
+                Address {
+                    guid: guids[1].clone(),
+                    name: "  ".to_string(),
+                    ..Address::default()
+                },
+                fix_country("unknownguid"),
+            ],
+        )?;
+        assert_eq!(
+            errors,
+            vec![
+                AddressValidationError {
+                    guid: guids[0].clone(),
+                    reason: AddressValidationReason::DuplicateGuid,
+                },
+                AddressValidationError {
+                    guid: guids[1].clone(),
+                    reason: AddressValidationReason::EmptyAddress,
+                },
+                AddressValidationError {
+                    guid: "unknownguid".to_string(),
+                    reason: AddressValidationReason::NoSuchRecord,
+                },
+            ]
+        );
+        let address = get_address(&db, &Guid::new(&guids[0]))?;
+        assert_eq!(address.country, "US");
+        assert_eq!(address.metadata.sync_change_counter, 0);
+
+        let errors =
+            update_addresses(&db, &config, guids.iter().map(|g| fix_country(g)).collect())?;
+        assert!(errors.is_empty());
+        for guid in &guids {
+            let address = get_address(&db, &Guid::new(guid))?;
+            assert_eq!(address.name, "jane doe");
+            assert_eq!(address.country, "US");
+            assert_eq!(address.metadata.sync_change_counter, 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_addresses() -> Result<()> {
+        let db = new_mem_db();
+        let existing = add_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            UpdatableAddressFields {
+                name: "Jane Doe".to_string(),
+                street_address: "123 Main St".to_string(),
+                country: "US".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+
+        let metrics = import_addresses(
+            &db,
+            &AutofillFeatureConfig::default(),
+            vec![
+                // Only differs in formatting, but adds a phone number, so is
+                // merged into the existing address.
+                Address {
+                    name: "  jane   DOE ".to_string(),
+                    street_address: "123 main st".to_string(),
+                    country: "us".to_string(),
+                    tel: "+1 (555) 123-4567".to_string(),
+                    times_used: 5,
+                    ..Address::default()
+                },
+                // A duplicate of the merged address which doesn't add anything.
+                Address {
+                    name: "Jane Doe".to_string(),
+                    tel: "+15551234567".to_string(),
+                    times_used: 2,
+                    ..Address::default()
+                },
+                // An empty address.
+                Address {
+                    name: "   ".to_string(),
+                    ..Address::default()
+                },
+                // A different address.
+                Address {
+                    guid: "importedguid".to_string(),
+                    name: "John Doe".to_string(),
+                    street_address: "1 Other St".to_string(),
+                    ..Address::default()
+                },
+                // A duplicate of an address added by the same import.
+                Address {
+                    name: "john doe".to_string(),
+                    street_address: "1 other st".to_string(),
+                    ..Address::default()
+                },
+            ],
+        )?;
+        assert_eq!(
+            metrics,
+            ImportMetrics {
+                added: 1,
+                merged: 1,
+                skipped: 3,
+            }
+        );
+
+        let merged = get_address(&db, &existing.guid)?;
+        assert_eq!(merged.name, "Jane Doe");
+        assert_eq!(merged.tel, "+15551234567");
+        assert_eq!(merged.metadata.times_used, 5);
+        assert_eq!(merged.metadata.sync_change_counter, 1);
+
+        let added = get_address(&db, &Guid::new("importedguid"))?;
+        assert_eq!(added.name, "John Doe");
+        assert_eq!(get_all_addresses(&db)?.len(), 2);
+
+        // Importing the same guid again must not collide with the existing record.
+        let metrics = import_addresses(
+            &db,
+            &AutofillFeatureConfig::default(),
+            vec![Address {
+                guid: "importedguid".to_string(),
+                name: "Someone Else".to_string(),
+                ..Address::default()
+            }],
+        )?;
+        assert_eq!(metrics.added, 1);
+        assert_eq!(get_all_addresses(&db)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_addresses_sparse_local() -> Result<()> {
+        let db = new_mem_db();
+        let sparse = add_address(
+            &db,
+            &AutofillFeatureConfig::default(),
+            UpdatableAddressFields {
+                name: "Jane Doe".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+
+        // A local address with only a name doesn't identify an address, so
+        // fuller addresses with the same name aren't merged into it.
+        let metrics = import_addresses(
+            &db,
+            &AutofillFeatureConfig::default(),
+            vec![
+                Address {
+                    name: "Jane Doe".to_string(),
+                    street_address: "123 Main St".to_string(),
+                    ..Address::default()
+                },
+                Address {
+                    name: "Jane Doe".to_string(),
+                    street_address: "1 Other St".to_string(),
+                    ..Address::default()
+                },
+                // Nor is an address with only the same name.
+                Address {
+                    name: "jane doe".to_string(),
+                    ..Address::default()
+                },
+            ],
+        )?;
+        assert_eq!(
+            metrics,
+            ImportMetrics {
+                added: 3,
+                merged: 0,
+                skipped: 0,
+            }
+        );
+        assert_eq!(get_address(&db, &sparse.guid)?.street_address, "");
+        assert_eq!(get_all_addresses(&db)?.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalization_level() -> Result<()> {
+        use crate::config::NormalizationLevel;
+        let db = new_mem_db();
+        let fields = UpdatableAddressFields {
+            name: "Jane Doe".to_string(),
+            country: "united states".to_string(),
+            address_level1: "california".to_string(),
+            tel: "(555) 123-4567".to_string(),
+            email: "Jane@Example.COM".to_string(),
+            ..UpdatableAddressFields::default()
+        };
+        let add_with = |normalization| {
+            let config = AutofillFeatureConfig {
+                normalization,
+                ..AutofillFeatureConfig::default()
+            };
+            add_address(&db, &config, fields.clone())
+        };
+
+        let address = add_with(NormalizationLevel::None)?;
+        assert_eq!(address.country, "united states");
+        assert_eq!(address.tel, "(555) 123-4567");
+
+        let address = add_with(NormalizationLevel::Location)?;
+        assert_eq!(address.country, "US");
+        assert_eq!(address.address_level1, "CA");
+        assert_eq!(address.tel, "(555) 123-4567");
+        assert_eq!(address.email, "Jane@Example.COM");
+
+        let address = add_with(NormalizationLevel::Full)?;
+        assert_eq!(address.country, "US");
+        assert_eq!(address.tel, "+15551234567");
+        assert_eq!(address.email, "jane@example.com");
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_addresses_without_fuzzy_dedupe() -> Result<()> {
+        let db = new_mem_db();
+        let config = AutofillFeatureConfig {
+            fuzzy_dedupe: false,
+            ..AutofillFeatureConfig::default()
+        };
+        add_address(
+            &db,
+            &config,
+            UpdatableAddressFields {
+                name: "Jane Doe".to_string(),
+                street_address: "123 Main St".to_string(),
+                ..UpdatableAddressFields::default()
+            },
+        )?;
+
+        let metrics = import_addresses(
+            &db,
+            &config,
+            vec![
+                // Only differs in case, so is a different address.
+                Address {
+                    name: "jane doe".to_string(),
+                    street_address: "123 Main St".to_string(),
+                    ..Address::default()
+                },
+                // Identical fields are still merged.
+                Address {
+                    name: "Jane Doe".to_string(),
+                    street_address: "123 Main St".to_string(),
+                    email: "jane@example.com".to_string(),
+                    ..Address::default()
+                },
+            ],
+        )?;
+        assert_eq!(
+            metrics,
+            ImportMetrics {
+                added: 1,
+                merged: 1,
+                skipped: 0,
+            }
+        );
+        assert_eq!(get_all_addresses(&db)?.len(), 2);
+        Ok(())
+    }
 }
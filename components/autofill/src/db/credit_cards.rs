@@ -11,6 +11,7 @@ use crate::db::{
     schema::{CREDIT_CARD_COMMON_COLS, CREDIT_CARD_COMMON_VALS},
 };
 use crate::error::*;
+use crate::normalize::normalize_cc_type;
 
 use rusqlite::{Connection, Transaction};
 use sync_guid::Guid;
@@ -33,7 +34,7 @@ pub(crate) fn add_credit_card(
         cc_exp_year: new_credit_card_fields.cc_exp_year,
         // Credit card types are a fixed set of strings as defined in the link below
         // (https://searchfox.org/mozilla-central/rev/7ef5cefd0468b8f509efe38e0212de2398f4c8b3/toolkit/modules/CreditCard.jsm#9-22)
-        cc_type: new_credit_card_fields.cc_type,
+        cc_type: normalize_cc_type(&new_credit_card_fields.cc_type),
         metadata: Metadata {
             time_created: now,
             time_last_modified: now,
@@ -137,7 +138,7 @@ pub fn update_credit_card(
             ":cc_number_last_4": credit_card.cc_number_last_4,
             ":cc_exp_month": credit_card.cc_exp_month,
             ":cc_exp_year": credit_card.cc_exp_year,
-            ":cc_type": credit_card.cc_type,
+            ":cc_type": normalize_cc_type(&credit_card.cc_type),
             ":time_last_modified": Timestamp::now(),
             ":guid": guid,
         },
@@ -707,4 +708,24 @@ pub(crate) mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_credit_card_type_normalized() -> Result<()> {
+        let db = new_mem_db();
+        let mut fields = UpdatableCreditCardFields {
+            cc_name: "jane doe".to_string(),
+            cc_number_enc: "XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX".to_string(),
+            cc_number_last_4: "1234".to_string(),
+            cc_exp_month: 3,
+            cc_exp_year: 2022,
+            cc_type: "Visa".to_string(),
+        };
+        let guid = add_credit_card(&db, fields.clone())?.guid;
+        assert_eq!(get_credit_card(&db, &guid)?.cc_type, "visa");
+
+        fields.cc_type = "American Express".to_string();
+        update_credit_card(&db, &guid, &fields)?;
+        assert_eq!(get_credit_card(&db, &guid)?.cc_type, "amex");
+        Ok(())
+    }
 }
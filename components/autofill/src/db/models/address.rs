@@ -25,6 +25,8 @@ pub struct UpdatableAddressFields {
     pub country: String,
     pub tel: String,
     pub email: String,
+    // A name the user gave the address, such as "Home" or "Work".
+    pub label: String,
 }
 
 // "Address" is what we return to consumers and has most of the metadata.
@@ -41,6 +43,7 @@ pub struct Address {
     pub country: String,
     pub tel: String,
     pub email: String,
+    pub label: String,
     // We expose some of the metadata
     pub time_created: i64,
     pub time_last_used: Option<i64>,
@@ -48,6 +51,59 @@ pub struct Address {
     pub times_used: i64,
 }
 
+// The result of importing addresses from another source, such as a desktop
+// profile being migrated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportMetrics {
+    // Addresses which didn't match an existing address and were added.
+    pub added: u32,
+    // Addresses which duplicated an existing address and were merged into it.
+    pub merged: u32,
+    // Addresses which were empty or which didn't add anything to an existing
+    // address.
+    pub skipped: u32,
+}
+
+// Why an address passed to `update_addresses()` couldn't be updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressValidationReason {
+    // There's no address with the guid.
+    NoSuchRecord,
+    // The guid appeared earlier in the same batch.
+    DuplicateGuid,
+    // Every field of the address was empty.
+    EmptyAddress,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressValidationError {
+    pub guid: String,
+    pub reason: AddressValidationReason,
+}
+
+// The address fields which can be searched by prefix, eg, to narrow down the
+// addresses shown in a form-fill dropdown as the user types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSearchField {
+    Name,
+    Organization,
+    StreetAddress,
+    Email,
+}
+
+impl AddressSearchField {
+    // The column for this field. Each of these columns has a case-insensitive
+    // index, so prefix searches don't need to scan the table.
+    pub(crate) fn column(&self) -> &'static str {
+        match self {
+            AddressSearchField::Name => "name",
+            AddressSearchField::Organization => "organization",
+            AddressSearchField::StreetAddress => "street_address",
+            AddressSearchField::Email => "email",
+        }
+    }
+}
+
 // This is used to "externalize" an address, suitable for handing back to
 // consumers.
 impl From<InternalAddress> for Address {
@@ -64,6 +120,7 @@ impl From<InternalAddress> for Address {
             country: ia.country,
             tel: ia.tel,
             email: ia.email,
+            label: ia.label,
             // note we can't use u64 in uniffi
             time_created: u64::from(ia.metadata.time_created) as i64,
             time_last_used: if ia.metadata.time_last_used.0 == 0 {
@@ -93,6 +150,7 @@ pub struct InternalAddress {
     pub country: String,
     pub tel: String,
     pub email: String,
+    pub label: String,
     pub metadata: Metadata,
 }
 
@@ -110,6 +168,7 @@ impl InternalAddress {
             country: row.get("country")?,
             tel: row.get("tel")?,
             email: row.get("email")?,
+            label: row.get("label")?,
             metadata: Metadata {
                 time_created: row.get("time_created")?,
                 time_last_used: row.get("time_last_used")?,
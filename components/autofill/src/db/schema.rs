@@ -19,6 +19,7 @@ pub const ADDRESS_COMMON_COLS: &str = "
     country,
     tel,
     email,
+    label,
     time_created,
     time_last_used,
     time_last_modified,
@@ -36,6 +37,7 @@ pub const ADDRESS_COMMON_VALS: &str = "
     :country,
     :tel,
     :email,
+    :label,
     :time_created,
     :time_last_used,
     :time_last_modified,
@@ -75,7 +77,7 @@ pub struct AutofillConnectionInitializer;
 
 impl ConnectionInitializer for AutofillConnectionInitializer {
     const NAME: &'static str = "autofill db";
-    const END_VERSION: u32 = 3;
+    const END_VERSION: u32 = 5;
 
     fn prepare(&self, conn: &Connection, _db_empty: bool) -> Result<()> {
         define_functions(conn)?;
@@ -105,6 +107,8 @@ impl ConnectionInitializer for AutofillConnectionInitializer {
             0 => upgrade_from_v0(db),
             1 => upgrade_from_v1(db),
             2 => upgrade_from_v2(db),
+            3 => upgrade_from_v3(db),
+            4 => upgrade_from_v4(db),
             _ => Err(Error::IncompatibleVersion(version)),
         }
     }
@@ -230,6 +234,39 @@ fn upgrade_from_v2(db: &Connection) -> Result<()> {
     Ok(())
 }
 
+fn upgrade_from_v3(db: &Connection) -> Result<()> {
+    db.execute_batch(
+        "
+        CREATE INDEX IF NOT EXISTS addresses_name_idx ON addresses_data(name COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS addresses_organization_idx ON addresses_data(organization COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS addresses_street_address_idx ON addresses_data(street_address COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS addresses_email_idx ON addresses_data(email COLLATE NOCASE);
+        ",
+    )?;
+    Ok(())
+}
+
+fn upgrade_from_v4(db: &Connection) -> Result<()> {
+    // Addresses which were already synced might have a label from a newer
+    // client, which until now we only kept in the mirror's payload.
+    db.execute_batch(
+        "
+        ALTER TABLE addresses_data ADD COLUMN label TEXT NOT NULL DEFAULT '';
+        UPDATE addresses_data
+        SET label = (
+            SELECT json_extract(m.payload, '$.entry.label')
+            FROM addresses_mirror m
+            WHERE m.guid = addresses_data.guid
+        )
+        WHERE guid IN (
+            SELECT guid FROM addresses_mirror
+            WHERE typeof(json_extract(payload, '$.entry.label')) = 'text'
+        );
+        ",
+    )?;
+    Ok(())
+}
+
 pub fn create_empty_sync_temp_tables(db: &Connection) -> Result<()> {
     log::debug!("Initializing sync temp tables");
     db.execute_batch(CREATE_SYNC_TEMP_TABLES_SQL)?;
@@ -371,4 +408,48 @@ mod tests {
         // Record B has no given_name, additional_name or family_name, so name should also be empty.
         assert_eq!(address.name, "");
     }
+
+    #[test]
+    fn test_upgrade_version_3() {
+        let db_file = MigratedDatabaseFile::new(AutofillConnectionInitializer, CREATE_V2_DB);
+        db_file.upgrade_to(3);
+        let db = db_file.open();
+        let index_count = || -> u32 {
+            db.query_row(
+                "SELECT COUNT(*) FROM sqlite_master
+                 WHERE type = 'index' AND tbl_name = 'addresses_data' AND name LIKE '%_idx'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(index_count(), 0);
+
+        db_file.upgrade_to(4);
+        assert_eq!(index_count(), 4);
+    }
+
+    #[test]
+    fn test_upgrade_version_4() {
+        let db_file = MigratedDatabaseFile::new(AutofillConnectionInitializer, CREATE_V2_DB);
+        db_file.upgrade_to(4);
+        let db = db_file.open();
+        db.execute_batch("SELECT label from addresses_data")
+            .expect_err("select should fail");
+        // Pretend "A" was synced from a client which knows about labels.
+        db.execute(
+            "INSERT INTO addresses_mirror (guid, payload) VALUES ('A', :payload)",
+            rusqlite::named_params! {
+                ":payload": r#"{"id": "A", "entry": {"name": "Jane John Doe", "label": "Home", "version": 1}}"#,
+            },
+        )
+        .unwrap();
+
+        db_file.upgrade_to(5);
+
+        let address = get_address(&db, &Guid::new("A")).unwrap();
+        assert_eq!(address.label, "Home");
+        let address = get_address(&db, &Guid::new("B")).unwrap();
+        assert_eq!(address.label, "");
+    }
 }
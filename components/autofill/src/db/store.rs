@@ -2,50 +2,66 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use crate::db::models::address::{Address, UpdatableAddressFields};
+use crate::config::AutofillFeatureConfig;
+use crate::db::models::address::{
+    Address, AddressSearchField, AddressValidationError, ImportMetrics, UpdatableAddressFields,
+};
 use crate::db::models::credit_card::{CreditCard, UpdatableCreditCardFields};
 use crate::db::{addresses, credit_cards, AutofillDb};
+use crate::encryption::EncryptorDecryptor;
 use crate::error::*;
 use error_support::handle_error;
 use rusqlite::{
     types::{FromSql, ToSql},
     Connection,
 };
+use serde_json::json;
 use sql_support::{self, ConnExt};
 use std::path::Path;
-use std::sync::{Arc, Mutex, Weak};
-use sync15::engine::{SyncEngine, SyncEngineId};
+use std::sync::{Arc, Mutex};
+use sync15::engine::{EngineRegistry, SyncEngine, SyncEngineId};
 use sync_guid::Guid;
 
-// Our "sync manager" will use whatever is stashed here.
+// Our "sync manager" will use whatever is registered here.
 lazy_static::lazy_static! {
-    // Mutex: just taken long enough to update the contents - needed to wrap
-    //        the Weak as it isn't `Sync`
-    // [Arc/Weak]<Store>: What the sync manager actually needs.
-    static ref STORE_FOR_MANAGER: Mutex<Weak<Store>> = Mutex::new(Weak::new());
+    static ref STORES_FOR_MANAGER: EngineRegistry<Store> = EngineRegistry::new();
 }
 
 /// Called by the sync manager to get a sync engine via the store previously
 /// registered with the sync manager.
 pub fn get_registered_sync_engine(engine_id: &SyncEngineId) -> Option<Box<dyn SyncEngine>> {
-    let weak = STORE_FOR_MANAGER.lock().unwrap();
-    match weak.upgrade() {
-        None => None,
-        Some(store) => match engine_id {
-            SyncEngineId::Addresses => Some(Box::new(crate::sync::address::create_engine(store))),
-            SyncEngineId::CreditCards => {
-                Some(Box::new(crate::sync::credit_card::create_engine(store)))
-            }
-            // panicking here seems reasonable - it's a static error if this
-            // it hit, not something that runtime conditions can influence.
-            _ => unreachable!("can't provide unknown engine: {}", engine_id),
-        },
+    let store = STORES_FOR_MANAGER.get()?;
+    Some(create_sync_engine(store, engine_id))
+}
+
+/// Called by the sync manager to get a sync engine via the store previously
+/// registered with the sync manager for the specified account.
+pub fn get_registered_sync_engine_for_account(
+    account_id: &str,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn SyncEngine>> {
+    let store = STORES_FOR_MANAGER.get_for_account(account_id)?;
+    Some(create_sync_engine(store, engine_id))
+}
+
+fn create_sync_engine(store: Arc<Store>, engine_id: &SyncEngineId) -> Box<dyn SyncEngine> {
+    match engine_id {
+        SyncEngineId::Addresses => Box::new(crate::sync::address::create_engine(store)),
+        SyncEngineId::CreditCards => Box::new(crate::sync::credit_card::create_engine(store)),
+        // panicking here seems reasonable - it's a static error if this
+        // it hit, not something that runtime conditions can influence.
+        _ => unreachable!("can't provide unknown engine: {}", engine_id),
     }
 }
 
+// The version of the document returned by `Store::export_all()`. Bump this if
+// the format changes incompatibly.
+const EXPORT_VERSION: u32 = 1;
+
 // This is the type that uniffi exposes.
 pub struct Store {
     pub(crate) db: Mutex<AutofillDb>,
+    pub(crate) config: AutofillFeatureConfig,
 }
 
 impl Store {
@@ -53,6 +69,20 @@ impl Store {
     pub fn new(db_path: impl AsRef<Path>) -> ApiResult<Self> {
         Ok(Self {
             db: Mutex::new(AutofillDb::new(db_path)?),
+            config: AutofillFeatureConfig::default(),
+        })
+    }
+
+    /// Like `new()`, but with the address reconciliation behavior controlled
+    /// by `config`, which apps would typically read from Nimbus.
+    #[handle_error(Error)]
+    pub fn new_with_config(
+        db_path: impl AsRef<Path>,
+        config: AutofillFeatureConfig,
+    ) -> ApiResult<Self> {
+        Ok(Self {
+            db: Mutex::new(AutofillDb::new(db_path)?),
+            config,
         })
     }
 
@@ -61,6 +91,7 @@ impl Store {
     pub fn new_memory() -> Self {
         Self {
             db: Mutex::new(crate::db::test::new_mem_db()),
+            config: AutofillFeatureConfig::default(),
         }
     }
 
@@ -69,6 +100,7 @@ impl Store {
     pub fn new_shared_memory(db_name: &str) -> ApiResult<Self> {
         Ok(Self {
             db: Mutex::new(AutofillDb::new_memory(db_name)?),
+            config: AutofillFeatureConfig::default(),
         })
     }
 
@@ -119,7 +151,8 @@ impl Store {
 
     #[handle_error(Error)]
     pub fn add_address(&self, new_address: UpdatableAddressFields) -> ApiResult<Address> {
-        Ok(addresses::add_address(&self.db.lock().unwrap().writer, new_address)?.into())
+        let db = self.db.lock().unwrap();
+        Ok(addresses::add_address(&db.writer, &self.config, new_address)?.into())
     }
 
     #[handle_error(Error)]
@@ -136,9 +169,50 @@ impl Store {
         Ok(addresses)
     }
 
+    #[handle_error(Error)]
+    pub fn get_addresses_ranked(&self, limit: u32) -> ApiResult<Vec<Address>> {
+        let addresses = addresses::get_addresses_ranked(&self.db.lock().unwrap().writer, limit)?
+            .into_iter()
+            .map(|x| x.into())
+            .collect();
+        Ok(addresses)
+    }
+
+    #[handle_error(Error)]
+    pub fn search_addresses(
+        &self,
+        field: AddressSearchField,
+        prefix: String,
+    ) -> ApiResult<Vec<Address>> {
+        let addresses =
+            addresses::search_addresses(&self.db.lock().unwrap().writer, field, &prefix)?
+                .into_iter()
+                .map(|x| x.into())
+                .collect();
+        Ok(addresses)
+    }
+
+    #[handle_error(Error)]
+    pub fn import_addresses(&self, addresses: Vec<Address>) -> ApiResult<ImportMetrics> {
+        addresses::import_addresses(&self.db.lock().unwrap().writer, &self.config, addresses)
+    }
+
     #[handle_error(Error)]
     pub fn update_address(&self, guid: String, address: UpdatableAddressFields) -> ApiResult<()> {
-        addresses::update_address(&self.db.lock().unwrap().writer, &Guid::new(&guid), &address)
+        addresses::update_address(
+            &self.db.lock().unwrap().writer,
+            &self.config,
+            &Guid::new(&guid),
+            &address,
+        )
+    }
+
+    #[handle_error(Error)]
+    pub fn update_addresses(
+        &self,
+        addresses: Vec<Address>,
+    ) -> ApiResult<Vec<AddressValidationError>> {
+        addresses::update_addresses(&self.db.lock().unwrap().writer, &self.config, addresses)
     }
 
     #[handle_error(Error)]
@@ -162,14 +236,109 @@ impl Store {
         Ok(())
     }
 
+    /// Deletes all local addresses and credit cards, along with their sync
+    /// metadata. No tombstones are created, so nothing is deleted from the
+    /// server - if the app keeps syncing, the server's records will be
+    /// downloaded again by the next sync.
+    #[handle_error(Error)]
+    pub fn wipe_all_local(&self) -> ApiResult<()> {
+        let db = self.db.lock().unwrap();
+        let tx = db.writer.unchecked_transaction()?;
+        tx.execute_batch(
+            "DELETE FROM addresses_data;
+            DELETE FROM addresses_mirror;
+            DELETE FROM addresses_tombstones;
+            DELETE FROM credit_cards_data;
+            DELETE FROM credit_cards_mirror;
+            DELETE FROM credit_cards_tombstones;
+            DELETE FROM moz_meta;",
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns all addresses and credit cards as a JSON document, so apps can
+    /// offer the user a copy of their data. Credit card numbers are decrypted
+    /// and included only if `encryption_key` is given; otherwise only the last
+    /// 4 digits are.
+    #[handle_error(Error)]
+    pub fn export_all(&self, encryption_key: Option<String>) -> ApiResult<String> {
+        let encdec = encryption_key
+            .map(|key| EncryptorDecryptor::new(&key))
+            .transpose()?;
+        let db = self.db.lock().unwrap();
+        let addresses = addresses::get_all_addresses(&db.writer)?
+            .into_iter()
+            .map(|address| {
+                let address = Address::from(address);
+                json!({
+                    "guid": address.guid,
+                    "name": address.name,
+                    "organization": address.organization,
+                    "street_address": address.street_address,
+                    "address_level3": address.address_level3,
+                    "address_level2": address.address_level2,
+                    "address_level1": address.address_level1,
+                    "postal_code": address.postal_code,
+                    "country": address.country,
+                    "tel": address.tel,
+                    "email": address.email,
+                    "label": address.label,
+                    "time_created": address.time_created,
+                    "time_last_used": address.time_last_used,
+                    "time_last_modified": address.time_last_modified,
+                    "times_used": address.times_used,
+                })
+            })
+            .collect::<Vec<_>>();
+        let credit_cards = credit_cards::get_all_credit_cards(&db.writer)?
+            .into_iter()
+            .map(|credit_card| {
+                // Scrubbed cards don't have a number to decrypt.
+                let cc_number = match &encdec {
+                    Some(encdec) if !credit_card.cc_number_enc.is_empty() => {
+                        Some(encdec.decrypt(&credit_card.cc_number_enc, "export")?)
+                    }
+                    _ => None,
+                };
+                let credit_card = CreditCard::from(credit_card);
+                Ok(json!({
+                    "guid": credit_card.guid,
+                    "cc_name": credit_card.cc_name,
+                    "cc_number": cc_number,
+                    "cc_number_last_4": credit_card.cc_number_last_4,
+                    "cc_exp_month": credit_card.cc_exp_month,
+                    "cc_exp_year": credit_card.cc_exp_year,
+                    "cc_type": credit_card.cc_type,
+                    "time_created": credit_card.time_created,
+                    "time_last_used": credit_card.time_last_used,
+                    "time_last_modified": credit_card.time_last_modified,
+                    "times_used": credit_card.times_used,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(json!({
+            "version": EXPORT_VERSION,
+            "addresses": addresses,
+            "credit_cards": credit_cards,
+        })
+        .to_string())
+    }
+
     // This allows the embedding app to say "make this instance available to
     // the sync manager". The implementation is more like "offer to sync mgr"
     // (thereby avoiding us needing to link with the sync manager) but
     // `register_with_sync_manager()` is logically what's happening so that's
     // the name it gets.
     pub fn register_with_sync_manager(self: Arc<Self>) {
-        let mut state = STORE_FOR_MANAGER.lock().unwrap();
-        *state = Arc::downgrade(&self);
+        STORES_FOR_MANAGER.register(&self);
+    }
+
+    // Like `register_with_sync_manager()`, but only makes this instance
+    // available to a sync manager created for the same account, so stores
+    // belonging to different accounts don't replace each other.
+    pub fn register_with_sync_manager_for_account(self: Arc<Self>, account_id: String) {
+        STORES_FOR_MANAGER.register_for_account(account_id, &self);
     }
 
     // These 2 are a little odd - they aren't exposed by uniffi - currently the
@@ -214,6 +383,53 @@ mod tests {
     use super::*;
     use crate::db::test::new_mem_db;
 
+    #[test]
+    fn test_export_and_wipe_all_local() -> Result<()> {
+        let store = Store::new_memory();
+        let key = crate::encryption::create_autofill_key().unwrap();
+        let encdec = EncryptorDecryptor::new(&key).unwrap();
+        store
+            .add_address(UpdatableAddressFields {
+                name: "jane doe".to_string(),
+                street_address: "123 Main St".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        store
+            .add_credit_card(UpdatableCreditCardFields {
+                cc_name: "jane doe".to_string(),
+                cc_number_enc: encdec.encrypt("4111111111111111", "test").unwrap(),
+                cc_number_last_4: "1111".to_string(),
+                cc_exp_month: 1,
+                cc_exp_year: 2030,
+                cc_type: "visa".to_string(),
+            })
+            .unwrap();
+
+        let export: serde_json::Value =
+            serde_json::from_str(&store.export_all(Some(key)).unwrap()).unwrap();
+        assert_eq!(export["version"], EXPORT_VERSION);
+        assert_eq!(export["addresses"][0]["street_address"], "123 Main St");
+        assert_eq!(export["credit_cards"][0]["cc_number"], "4111111111111111");
+
+        // Without the key, the number isn't included.
+        let export: serde_json::Value =
+            serde_json::from_str(&store.export_all(None).unwrap()).unwrap();
+        assert!(export["credit_cards"][0]["cc_number"].is_null());
+        assert_eq!(export["credit_cards"][0]["cc_number_last_4"], "1111");
+
+        store.wipe_all_local().unwrap();
+        assert!(store.get_all_addresses().unwrap().is_empty());
+        assert!(store.get_all_credit_cards().unwrap().is_empty());
+        let db = store.db.lock().unwrap();
+        let tombstones: u32 = db.writer.query_one(
+            "SELECT (SELECT COUNT(*) FROM addresses_tombstones)
+                  + (SELECT COUNT(*) FROM credit_cards_tombstones)",
+        )?;
+        assert_eq!(tombstones, 0);
+        Ok(())
+    }
+
     #[test]
     fn test_autofill_meta() -> Result<()> {
         let db = new_mem_db();
@@ -249,6 +465,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_scrub_encrypted_data() -> Result<()> {
+        let store = Arc::new(Store::new_memory());
+        let encdec = crate::encryption::EncryptorDecryptor::new_with_random_key()?;
+        let card = store
+            .add_credit_card(UpdatableCreditCardFields {
+                cc_name: "jane doe".to_string(),
+                cc_number_enc: encdec.encrypt("1234567812345678", "cc_number")?,
+                cc_number_last_4: "5678".to_string(),
+                cc_exp_month: 10,
+                cc_exp_year: 2025,
+                cc_type: "visa".to_string(),
+            })
+            .unwrap();
+        put_meta(
+            &store.db.lock().unwrap().writer,
+            "credit_cards.last_sync_time",
+            &1234,
+        )?;
+
+        Arc::clone(&store).scrub_encrypted_data().unwrap();
+
+        // The card number is gone, but the rest of the record remains.
+        let scrubbed = store.get_credit_card(card.guid).unwrap();
+        assert_eq!(scrubbed.cc_number_enc, "");
+        assert_eq!(scrubbed.cc_number_last_4, "5678");
+        // And the next sync will refetch everything from the server.
+        let last_sync: Option<i64> = get_meta(
+            &store.db.lock().unwrap().writer,
+            "credit_cards.last_sync_time",
+        )?;
+        assert_eq!(last_sync, Some(0));
+        Ok(())
+    }
+
     #[test]
     fn test_sync_manager_registration() {
         let store = Arc::new(Store::new_shared_memory("sync-mgr-test").unwrap());
@@ -257,11 +508,7 @@ mod tests {
         Arc::clone(&store).register_with_sync_manager();
         assert_eq!(Arc::strong_count(&store), 1);
         assert_eq!(Arc::weak_count(&store), 1);
-        let registered = STORE_FOR_MANAGER
-            .lock()
-            .unwrap()
-            .upgrade()
-            .expect("should upgrade");
+        let registered = STORES_FOR_MANAGER.get().expect("should upgrade");
         assert!(Arc::ptr_eq(&store, &registered));
         drop(registered);
         // should be no new references
@@ -269,6 +516,29 @@ mod tests {
         assert_eq!(Arc::weak_count(&store), 1);
         // dropping the registered object should drop the registration.
         drop(store);
-        assert!(STORE_FOR_MANAGER.lock().unwrap().upgrade().is_none());
+        assert!(STORES_FOR_MANAGER.get().is_none());
+    }
+
+    #[test]
+    fn test_sync_manager_registration_for_account() {
+        let store1 = Arc::new(Store::new_shared_memory("sync-mgr-account-1").unwrap());
+        let store2 = Arc::new(Store::new_shared_memory("sync-mgr-account-2").unwrap());
+        Arc::clone(&store1).register_with_sync_manager_for_account("account-1".to_string());
+        Arc::clone(&store2).register_with_sync_manager_for_account("account-2".to_string());
+        assert!(
+            get_registered_sync_engine_for_account("account-1", &SyncEngineId::Addresses).is_some()
+        );
+        assert!(
+            get_registered_sync_engine_for_account("account-3", &SyncEngineId::Addresses).is_none()
+        );
+        // dropping one account's store shouldn't impact the other.
+        drop(store1);
+        assert!(
+            get_registered_sync_engine_for_account("account-1", &SyncEngineId::Addresses).is_none()
+        );
+        assert!(
+            get_registered_sync_engine_for_account("account-2", &SyncEngineId::CreditCards)
+                .is_some()
+        );
     }
 }
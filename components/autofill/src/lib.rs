@@ -6,19 +6,26 @@
 #![allow(unknown_lints)]
 #![warn(rust_2018_idioms)]
 
+pub mod config;
 pub mod db;
 pub mod encryption;
 pub mod error;
+pub mod normalize;
 pub mod sync;
 
 // Re-export stuff the sync manager needs.
-pub use crate::db::store::get_registered_sync_engine;
+pub use crate::db::store::{get_registered_sync_engine, get_registered_sync_engine_for_account};
 
 // Expose stuff needed by the uniffi generated code.
+use crate::config::{AutofillFeatureConfig, NormalizationLevel};
 use crate::db::models::address::*;
 use crate::db::models::credit_card::*;
 use crate::db::store::Store;
 use crate::encryption::{create_autofill_key, decrypt_string, encrypt_string};
+use crate::normalize::{detect_card_type, is_valid_card_number, is_valid_email, normalize_phone};
 pub use error::{ApiResult, AutofillApiError, Error, Result};
 
 uniffi::include_scaffolding!("autofill");
+
+pub const UNIFFI_NAMESPACE: &str = "autofill";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,590 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+* License, v. 2.0. If a copy of the MPL was not distributed with this
+* file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*/
+
+// Helpers for normalizing the fields of autofill records, so records which
+// only differ in their formatting can be recognized as the same record.
+
+use std::ops::RangeInclusive;
+
+/// Trims the value and collapses runs of whitespace into a single space.
+/// Line breaks are kept, because they are significant in multi-line fields
+/// such as `street_address`, but blank lines are removed.
+pub fn normalize_whitespace(value: &str) -> String {
+    value
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Strips formatting (spaces, dashes, parentheses etc) from a phone number,
+/// leaving only the digits and a leading `+` if the number had one.
+pub fn normalize_tel(tel: &str) -> String {
+    let tel = tel.trim();
+    let digits = tel.chars().filter(char::is_ascii_digit);
+    if tel.starts_with('+') {
+        std::iter::once('+').chain(digits).collect()
+    } else {
+        digits.collect()
+    }
+}
+
+/// Returns the value used to decide whether two field values are the same,
+/// ignoring differences in case and whitespace.
+pub fn comparison_key(value: &str) -> String {
+    normalize_whitespace(value).to_lowercase()
+}
+
+// Names and common aliases for the countries we have specific rules for,
+// mapped to their ISO 3166-1 alpha-2 code. Keys are upper-case without
+// punctuation.
+const COUNTRY_ALIASES: &[(&str, &str)] = &[
+    ("AMERICA", "US"),
+    ("UNITED STATES", "US"),
+    ("UNITED STATES OF AMERICA", "US"),
+    ("USA", "US"),
+    ("CANADA", "CA"),
+    ("ENGLAND", "GB"),
+    ("GREAT BRITAIN", "GB"),
+    ("NORTHERN IRELAND", "GB"),
+    ("SCOTLAND", "GB"),
+    ("UK", "GB"),
+    ("UNITED KINGDOM", "GB"),
+    ("WALES", "GB"),
+    ("DEUTSCHLAND", "DE"),
+    ("GERMANY", "DE"),
+    ("FRANCE", "FR"),
+    ("ITALIA", "IT"),
+    ("ITALY", "IT"),
+    ("ESPAÑA", "ES"),
+    ("SPAIN", "ES"),
+    ("HOLLAND", "NL"),
+    ("NETHERLANDS", "NL"),
+    ("THE NETHERLANDS", "NL"),
+    ("AUSTRALIA", "AU"),
+];
+
+const US_STATES: &[(&str, &str)] = &[
+    ("ALABAMA", "AL"),
+    ("ALASKA", "AK"),
+    ("ARIZONA", "AZ"),
+    ("ARKANSAS", "AR"),
+    ("CALIFORNIA", "CA"),
+    ("COLORADO", "CO"),
+    ("CONNECTICUT", "CT"),
+    ("DELAWARE", "DE"),
+    ("DISTRICT OF COLUMBIA", "DC"),
+    ("FLORIDA", "FL"),
+    ("GEORGIA", "GA"),
+    ("HAWAII", "HI"),
+    ("IDAHO", "ID"),
+    ("ILLINOIS", "IL"),
+    ("INDIANA", "IN"),
+    ("IOWA", "IA"),
+    ("KANSAS", "KS"),
+    ("KENTUCKY", "KY"),
+    ("LOUISIANA", "LA"),
+    ("MAINE", "ME"),
+    ("MARYLAND", "MD"),
+    ("MASSACHUSETTS", "MA"),
+    ("MICHIGAN", "MI"),
+    ("MINNESOTA", "MN"),
+    ("MISSISSIPPI", "MS"),
+    ("MISSOURI", "MO"),
+    ("MONTANA", "MT"),
+    ("NEBRASKA", "NE"),
+    ("NEVADA", "NV"),
+    ("NEW HAMPSHIRE", "NH"),
+    ("NEW JERSEY", "NJ"),
+    ("NEW MEXICO", "NM"),
+    ("NEW YORK", "NY"),
+    ("NORTH CAROLINA", "NC"),
+    ("NORTH DAKOTA", "ND"),
+    ("OHIO", "OH"),
+    ("OKLAHOMA", "OK"),
+    ("OREGON", "OR"),
+    ("PENNSYLVANIA", "PA"),
+    ("RHODE ISLAND", "RI"),
+    ("SOUTH CAROLINA", "SC"),
+    ("SOUTH DAKOTA", "SD"),
+    ("TENNESSEE", "TN"),
+    ("TEXAS", "TX"),
+    ("UTAH", "UT"),
+    ("VERMONT", "VT"),
+    ("VIRGINIA", "VA"),
+    ("WASHINGTON", "WA"),
+    ("WEST VIRGINIA", "WV"),
+    ("WISCONSIN", "WI"),
+    ("WYOMING", "WY"),
+];
+
+const CA_PROVINCES: &[(&str, &str)] = &[
+    ("ALBERTA", "AB"),
+    ("BRITISH COLUMBIA", "BC"),
+    ("MANITOBA", "MB"),
+    ("NEW BRUNSWICK", "NB"),
+    ("NEWFOUNDLAND AND LABRADOR", "NL"),
+    ("NORTHWEST TERRITORIES", "NT"),
+    ("NOVA SCOTIA", "NS"),
+    ("NUNAVUT", "NU"),
+    ("ONTARIO", "ON"),
+    ("PRINCE EDWARD ISLAND", "PE"),
+    ("QUEBEC", "QC"),
+    ("QUÉBEC", "QC"),
+    ("SASKATCHEWAN", "SK"),
+    ("YUKON", "YT"),
+];
+
+// The key we use to look values up in the tables above.
+fn lookup_key(value: &str) -> String {
+    normalize_whitespace(&value.replace('.', "")).to_uppercase()
+}
+
+fn lookup_code(table: &[(&str, &'static str)], value: &str) -> Option<&'static str> {
+    let key = lookup_key(value);
+    table
+        .iter()
+        .find(|(name, code)| *name == key || *code == key)
+        .map(|(_, code)| *code)
+}
+
+/// Returns the ISO 3166-1 alpha-2 code for a country name or alias we know
+/// about (eg, "USA" or "United Kingdom"). Other two letter values are assumed
+/// to already be a code and are upper-cased. Anything else is returned with
+/// its whitespace normalized.
+pub fn normalize_country(country: &str) -> String {
+    if let Some(code) = lookup_code(COUNTRY_ALIASES, country) {
+        return code.to_string();
+    }
+    let country = normalize_whitespace(country);
+    if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+        country.to_ascii_uppercase()
+    } else {
+        country
+    }
+}
+
+/// Normalizes `address_level1` (eg, the state or province) for the given
+/// country code. For countries where it's conventional, names are replaced
+/// with their code (eg, "California" becomes "CA").
+pub fn normalize_address_level1(address_level1: &str, country: &str) -> String {
+    let table = match country {
+        "US" => US_STATES,
+        "CA" => CA_PROVINCES,
+        _ => return normalize_whitespace(address_level1),
+    };
+    match lookup_code(table, address_level1) {
+        Some(code) => code.to_string(),
+        None => normalize_whitespace(address_level1),
+    }
+}
+
+/// Formats a postal code the way the given country code does. Postal codes
+/// which don't look valid for the country are just upper-cased with their
+/// whitespace normalized, so we never lose data.
+pub fn normalize_postal_code(postal_code: &str, country: &str) -> String {
+    let compact: String = postal_code
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    match country {
+        "US" => {
+            let digits = compact.replace('-', "");
+            if is_digits(&digits) && digits.len() == 5 {
+                return digits;
+            }
+            if is_digits(&digits) && digits.len() == 9 {
+                return format!("{}-{}", &digits[..5], &digits[5..]);
+            }
+        }
+        // "A1A 1A1"
+        "CA" if compact.len() == 6 && compact.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            return format!("{} {}", &compact[..3], &compact[3..]);
+        }
+        // The "inward" code is always the last 3 characters, eg "SW1A 1AA".
+        "GB" if (5..=7).contains(&compact.len())
+            && compact.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            let split = compact.len() - 3;
+            return format!("{} {}", &compact[..split], &compact[split..]);
+        }
+        _ => (),
+    }
+    normalize_whitespace(postal_code).to_uppercase()
+}
+
+// The calling code for the countries we know about, the trunk prefix which is
+// dialled before national numbers, if any, and the number of digits a national
+// number can have. Numbers of any other length, such as short codes or US
+// numbers without an area code, aren't given a calling code.
+const CALLING_CODES: &[(&str, &str, Option<&str>, RangeInclusive<usize>)] = &[
+    ("US", "1", Some("1"), 10..=10),
+    ("CA", "1", Some("1"), 10..=10),
+    ("GB", "44", Some("0"), 9..=10),
+    ("DE", "49", Some("0"), 6..=13),
+    ("FR", "33", Some("0"), 9..=9),
+    ("IT", "39", None, 6..=11),
+    ("ES", "34", None, 9..=9),
+    ("NL", "31", Some("0"), 9..=9),
+    ("AU", "61", Some("0"), 9..=9),
+];
+
+/// Normalizes a phone number to an E.164-like format (eg, "+15551234567").
+/// Numbers which already have a `+` or "00" international prefix just have
+/// their formatting stripped. Other numbers are assumed to be national
+/// numbers for `country`, and are given its calling code if we know it and
+/// the number has the right number of digits for that country.
+pub fn normalize_phone(tel: &str, country: &str) -> String {
+    let tel = normalize_tel(tel);
+    if tel.starts_with('+') {
+        return tel;
+    }
+    if let Some(international) = tel.strip_prefix("00") {
+        return format!("+{}", international);
+    }
+    let country = normalize_country(country);
+    let Some((_, calling_code, trunk_prefix, national_len)) =
+        CALLING_CODES.iter().find(|(code, ..)| *code == country)
+    else {
+        return tel;
+    };
+    let national = trunk_prefix
+        .and_then(|prefix| tel.strip_prefix(prefix))
+        .filter(|national| national_len.contains(&national.len()))
+        .unwrap_or(&tel);
+    if !national_len.contains(&national.len()) {
+        return tel;
+    }
+    format!("+{}{}", calling_code, national)
+}
+
+/// A light check of whether a value looks like an email address. It only
+/// rejects values which obviously aren't one - the only way to really know is
+/// to send an email to it.
+pub fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.trim().split_once('@') else {
+        return false;
+    };
+    let has_whitespace = |s: &str| s.chars().any(char::is_whitespace);
+    !local.is_empty()
+        && !has_whitespace(local)
+        && !domain.contains('@')
+        && !has_whitespace(domain)
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+/// Trims an email address and, if it looks valid, lower-cases the domain,
+/// which unlike the local part is never case-sensitive.
+pub fn normalize_email(email: &str) -> String {
+    let email = email.trim();
+    if !is_valid_email(email) {
+        return email.to_string();
+    }
+    match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local, domain.to_lowercase()),
+        None => email.to_string(),
+    }
+}
+
+/// Normalizes the fields of an address used to contact someone. This should
+/// be called after the country has been normalized.
+pub fn normalize_address_contact(tel: &mut String, email: &mut String, country: &str) {
+    *tel = normalize_phone(tel, country);
+    *email = normalize_email(email);
+}
+
+/// Normalizes the country, and then the fields of an address whose format
+/// depends on the country.
+pub fn normalize_address_location(
+    country: &mut String,
+    address_level1: &mut String,
+    postal_code: &mut String,
+) {
+    *country = normalize_country(country);
+    *address_level1 = normalize_address_level1(address_level1, country);
+    *postal_code = normalize_postal_code(postal_code, country);
+}
+
+// The card networks we recognize, using the same identifiers as desktop
+// (https://searchfox.org/mozilla-central/source/toolkit/modules/CreditCard.sys.mjs),
+// and the IIN ranges they issue card numbers from. Each entry is
+// `(network, first prefix, last prefix, min length, max length)`, where the
+// prefixes of a range have the same number of digits. More specific ranges
+// come first, because some networks issue numbers inside another network's
+// range (eg, Carte Bancaire inside Visa's).
+const CARD_IIN_RANGES: &[(&str, u32, u32, usize, usize)] = &[
+    ("amex", 34, 34, 15, 15),
+    ("amex", 37, 37, 15, 15),
+    ("cartebancaire", 4035, 4035, 16, 16),
+    ("cartebancaire", 4360, 4360, 16, 16),
+    ("diners", 300, 305, 14, 19),
+    ("diners", 3095, 3095, 14, 19),
+    ("diners", 36, 36, 14, 19),
+    ("diners", 38, 39, 14, 19),
+    ("discover", 6011, 6011, 16, 19),
+    ("discover", 622126, 622925, 16, 19),
+    ("discover", 624000, 626999, 16, 19),
+    ("discover", 628200, 628899, 16, 19),
+    ("discover", 64, 65, 16, 19),
+    ("jcb", 3528, 3589, 16, 19),
+    ("mastercard", 2221, 2720, 16, 16),
+    ("mastercard", 51, 55, 16, 16),
+    ("mir", 2200, 2204, 16, 16),
+    ("unionpay", 62, 62, 16, 19),
+    ("unionpay", 81, 81, 16, 19),
+    ("visa", 4, 4, 13, 19),
+];
+
+// Common ways apps and older records spell the card networks, upper-cased and
+// without spaces or dashes.
+const CARD_TYPE_ALIASES: &[(&str, &str)] = &[
+    ("AMERICANEXPRESS", "amex"),
+    ("CB", "cartebancaire"),
+    ("DINERSCLUB", "diners"),
+    ("MASTER", "mastercard"),
+    ("MC", "mastercard"),
+    ("CUP", "unionpay"),
+    ("CHINAUNIONPAY", "unionpay"),
+];
+
+// Strips the spaces and dashes people use to group the digits of a card
+// number. Returns `None` if there's anything else which isn't a digit.
+fn card_number_digits(number: &str) -> Option<String> {
+    let digits = number
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(digits)
+}
+
+fn passes_luhn_check(digits: &str) -> bool {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let digit = u32::from(b - b'0');
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Returns the network which issued a card number, such as "visa" or "amex",
+/// based on the number's prefix and length, or `None` if we don't recognize
+/// it. This doesn't check whether the number is valid.
+pub fn detect_card_type(number: &str) -> Option<String> {
+    let digits = card_number_digits(number)?;
+    CARD_IIN_RANGES
+        .iter()
+        .find(|(_, start, end, min_len, max_len)| {
+            let prefix_len = start.to_string().len();
+            (*min_len..=*max_len).contains(&digits.len())
+                && digits[..prefix_len]
+                    .parse::<u32>()
+                    .is_ok_and(|prefix| (*start..=*end).contains(&prefix))
+        })
+        .map(|(network, ..)| network.to_string())
+}
+
+/// Returns whether a card number is well-formed: it has between 12 and 19
+/// digits, optionally grouped with spaces or dashes, and passes the Luhn
+/// check.
+pub fn is_valid_card_number(number: &str) -> bool {
+    card_number_digits(number)
+        .is_some_and(|digits| (12..=19).contains(&digits.len()) && passes_luhn_check(&digits))
+}
+
+/// Normalizes a card type to the identifiers used by desktop, eg, "Visa" to
+/// "visa" and "American Express" to "amex". Types we don't recognize are kept
+/// as they are, so we don't lose data written by newer versions.
+pub fn normalize_cc_type(cc_type: &str) -> String {
+    let cc_type = cc_type.trim();
+    let key = cc_type
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .collect::<String>()
+        .to_uppercase();
+    if let Some((network, ..)) = CARD_IIN_RANGES
+        .iter()
+        .find(|(network, ..)| network.eq_ignore_ascii_case(&key))
+    {
+        return network.to_string();
+    }
+    match lookup_code(CARD_TYPE_ALIASES, &key) {
+        Some(network) => network.to_string(),
+        None => cc_type.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_whitespace() {
+        assert_eq!(normalize_whitespace("  Jane   Doe "), "Jane Doe");
+        assert_eq!(
+            normalize_whitespace(" 123 Main St\n\n  Apt  4 \n"),
+            "123 Main St\nApt 4"
+        );
+        assert_eq!(normalize_whitespace("   "), "");
+    }
+
+    #[test]
+    fn test_normalize_tel() {
+        assert_eq!(normalize_tel("+1 (555) 123-4567"), "+15551234567");
+        assert_eq!(normalize_tel("555.123.4567"), "5551234567");
+        assert_eq!(normalize_tel(""), "");
+    }
+
+    #[test]
+    fn test_comparison_key() {
+        assert_eq!(comparison_key(" JANE  doe"), comparison_key("Jane Doe"));
+        assert_ne!(comparison_key("Jane Doe"), comparison_key("Jane Roe"));
+    }
+
+    #[test]
+    fn test_normalize_country() {
+        assert_eq!(normalize_country("USA"), "US");
+        assert_eq!(normalize_country("U.S.A."), "US");
+        assert_eq!(normalize_country(" united  kingdom "), "GB");
+        assert_eq!(normalize_country("de"), "DE");
+        assert_eq!(normalize_country(" Narnia "), "Narnia");
+        assert_eq!(normalize_country(""), "");
+    }
+
+    #[test]
+    fn test_normalize_address_level1() {
+        assert_eq!(normalize_address_level1("california", "US"), "CA");
+        assert_eq!(normalize_address_level1("ny", "US"), "NY");
+        assert_eq!(normalize_address_level1("Québec", "CA"), "QC");
+        // Only applies to the country it belongs to.
+        assert_eq!(normalize_address_level1("California", "DE"), "California");
+        assert_eq!(normalize_address_level1(" Bavaria ", "DE"), "Bavaria");
+    }
+
+    #[test]
+    fn test_normalize_postal_code() {
+        assert_eq!(normalize_postal_code(" 12345 ", "US"), "12345");
+        assert_eq!(normalize_postal_code("123456789", "US"), "12345-6789");
+        assert_eq!(normalize_postal_code("12345-6789", "US"), "12345-6789");
+        assert_eq!(normalize_postal_code("k1a0b1", "CA"), "K1A 0B1");
+        assert_eq!(normalize_postal_code("sw1a1aa", "GB"), "SW1A 1AA");
+        assert_eq!(normalize_postal_code("ec1a  1bb", "GB"), "EC1A 1BB");
+        // Values which don't look right are left alone, other than the case.
+        assert_eq!(normalize_postal_code("1234", "US"), "1234");
+        assert_eq!(normalize_postal_code("75008", "FR"), "75008");
+    }
+
+    #[test]
+    fn test_normalize_phone() {
+        assert_eq!(normalize_phone("(555) 123-4567", "US"), "+15551234567");
+        assert_eq!(normalize_phone("1-555-123-4567", "USA"), "+15551234567");
+        assert_eq!(normalize_phone("+1 555 123 4567", "US"), "+15551234567");
+        assert_eq!(normalize_phone("020 7946 0018", "GB"), "+442079460018");
+        assert_eq!(normalize_phone("0044 20 7946 0018", "US"), "+442079460018");
+        // Italian numbers keep their leading zero.
+        assert_eq!(normalize_phone("06 1234 5678", "IT"), "+390612345678");
+        // We don't know the calling code, or the number is the wrong length.
+        assert_eq!(normalize_phone("012 345 6789", "Narnia"), "0123456789");
+        assert_eq!(normalize_phone("911", "US"), "911");
+        assert_eq!(normalize_phone("555-1234", "US"), "5551234");
+        assert_eq!(normalize_phone("1-555-1234", "US"), "15551234");
+        assert_eq!(normalize_phone("(555) 123-45678", "US"), "55512345678");
+        assert_eq!(normalize_phone("", "US"), "");
+    }
+
+    #[test]
+    fn test_is_valid_email() {
+        assert!(is_valid_email("jane@example.com"));
+        assert!(is_valid_email(" jane.doe+autofill@mail.example.co.uk "));
+        assert!(!is_valid_email(""));
+        assert!(!is_valid_email("jane"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("jane@localhost"));
+        assert!(!is_valid_email("jane@example..com"));
+        assert!(!is_valid_email("jane@doe@example.com"));
+        assert!(!is_valid_email("jane doe@example.com"));
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email(" Jane@Example.COM "), "Jane@example.com");
+        // Invalid values are only trimmed.
+        assert_eq!(normalize_email(" Not An Email "), "Not An Email");
+    }
+
+    #[test]
+    fn test_detect_card_type() {
+        let card_type = |number| detect_card_type(number);
+        assert_eq!(card_type("4111 1111 1111 1111").as_deref(), Some("visa"));
+        assert_eq!(card_type("4111111111111").as_deref(), Some("visa"));
+        assert_eq!(
+            card_type("4035501000000008").as_deref(),
+            Some("cartebancaire")
+        );
+        assert_eq!(card_type("378282246310005").as_deref(), Some("amex"));
+        assert_eq!(
+            card_type("5555-5555-5555-4444").as_deref(),
+            Some("mastercard")
+        );
+        assert_eq!(card_type("2221000000000009").as_deref(), Some("mastercard"));
+        assert_eq!(card_type("2200000000000004").as_deref(), Some("mir"));
+        assert_eq!(card_type("6011111111111117").as_deref(), Some("discover"));
+        assert_eq!(card_type("6221260000000000").as_deref(), Some("discover"));
+        assert_eq!(card_type("6200000000000005").as_deref(), Some("unionpay"));
+        assert_eq!(card_type("3530111333300000").as_deref(), Some("jcb"));
+        assert_eq!(card_type("30569309025904").as_deref(), Some("diners"));
+        // Right prefix, wrong length.
+        assert_eq!(card_type("37828224631000"), None);
+        assert_eq!(card_type("555555555555444"), None);
+        assert_eq!(card_type("1234567890123456"), None);
+        assert_eq!(card_type("4111 1111 1111 111x"), None);
+        assert_eq!(card_type(""), None);
+    }
+
+    #[test]
+    fn test_is_valid_card_number() {
+        assert!(is_valid_card_number("4111 1111 1111 1111"));
+        assert!(is_valid_card_number("378282246310005"));
+        assert!(is_valid_card_number("5555-5555-5555-4444"));
+        assert!(!is_valid_card_number("4111 1111 1111 1112"));
+        assert!(!is_valid_card_number("4111.1111.1111.1111"));
+        // Passes the Luhn check, but is too short or too long.
+        assert!(!is_valid_card_number("0000"));
+        assert!(!is_valid_card_number("00000000000000000000"));
+        assert!(!is_valid_card_number(""));
+    }
+
+    #[test]
+    fn test_normalize_cc_type() {
+        assert_eq!(normalize_cc_type("Visa"), "visa");
+        assert_eq!(normalize_cc_type(" MasterCard "), "mastercard");
+        assert_eq!(normalize_cc_type("Master Card"), "mastercard");
+        assert_eq!(normalize_cc_type("American Express"), "amex");
+        assert_eq!(normalize_cc_type("Diners Club"), "diners");
+        assert_eq!(normalize_cc_type("carte-bancaire"), "cartebancaire");
+        assert_eq!(normalize_cc_type("China UnionPay"), "unionpay");
+        assert_eq!(normalize_cc_type("Some New Card"), "Some New Card");
+        assert_eq!(normalize_cc_type(""), "");
+    }
+}
@@ -4,6 +4,7 @@
 */
 
 use super::AddressPayload;
+use crate::config::AutofillFeatureConfig;
 use crate::db::addresses::{add_internal_address, update_internal_address};
 use crate::db::models::address::InternalAddress;
 use crate::db::schema::ADDRESS_COMMON_COLS;
@@ -93,11 +94,12 @@ fn create_incoming_bso(id: SyncGuid, raw: String) -> IncomingContent<AddressPayl
 
 fn bso_to_incoming(
     payload_content: IncomingContent<AddressPayload>,
+    config: &AutofillFeatureConfig,
 ) -> Result<IncomingContent<InternalAddress>> {
     Ok(match payload_content.kind {
         IncomingKind::Content(content) => IncomingContent {
             envelope: payload_content.envelope,
-            kind: IncomingKind::Content(InternalAddress::from_payload(content)?),
+            kind: IncomingKind::Content(InternalAddress::from_payload(content, config)?),
         },
         IncomingKind::Tombstone => IncomingContent {
             envelope: payload_content.envelope,
@@ -112,26 +114,18 @@ fn bso_to_incoming(
 
 // Takes a raw payload, as stored in our database, and returns an InternalAddress
 // or a tombstone. Addresses store the raw payload as cleartext json.
-fn raw_payload_to_incoming(id: SyncGuid, raw: String) -> Result<IncomingContent<InternalAddress>> {
-    let payload_content = create_incoming_bso(id, raw);
-
-    Ok(match payload_content.kind {
-        IncomingKind::Content(content) => IncomingContent {
-            envelope: payload_content.envelope,
-            kind: IncomingKind::Content(InternalAddress::from_payload(content)?),
-        },
-        IncomingKind::Tombstone => IncomingContent {
-            envelope: payload_content.envelope,
-            kind: IncomingKind::Tombstone,
-        },
-        IncomingKind::Malformed => IncomingContent {
-            envelope: payload_content.envelope,
-            kind: IncomingKind::Malformed,
-        },
-    })
+fn raw_payload_to_incoming(
+    id: SyncGuid,
+    raw: String,
+    config: &AutofillFeatureConfig,
+) -> Result<IncomingContent<InternalAddress>> {
+    bso_to_incoming(create_incoming_bso(id, raw), config)
 }
 
-pub(super) struct IncomingAddressesImpl {}
+#[derive(Default)]
+pub(super) struct IncomingAddressesImpl {
+    pub(super) config: AutofillFeatureConfig,
+}
 
 impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
     type Record = InternalAddress;
@@ -179,6 +173,7 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
             l.country,
             l.tel,
             l.email,
+            l.label,
             l.time_created,
             l.time_last_used,
             l.time_last_modified,
@@ -203,7 +198,7 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
                 &mut payload_content,
                 row.get("name").unwrap_or("".to_string()),
             );
-            let incoming = bso_to_incoming(payload_content)?;
+            let incoming = bso_to_incoming(payload_content, &self.config)?;
 
             Ok(IncomingState {
                 incoming,
@@ -234,7 +229,7 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
                     match row.get::<_, Option<String>>("m_payload")? {
                         Some(m_payload) => {
                             // a tombstone in the mirror can be treated as though it's missing.
-                            raw_payload_to_incoming(guid, m_payload)?.content()
+                            raw_payload_to_incoming(guid, m_payload, &self.config)?.content()
                         }
                         None => None,
                     }
@@ -274,7 +269,8 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
                 AND postal_code == :postal_code
                 AND country == :country
                 AND tel == :tel
-                AND email == :email", common_cols = ADDRESS_COMMON_COLS);
+                AND email == :email
+                AND label == :label", common_cols = ADDRESS_COMMON_COLS);
 
         let params = named_params! {
             ":guid": incoming.guid,
@@ -288,6 +284,7 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
             ":country": incoming.country,
             ":tel": incoming.tel,
             ":email": incoming.email,
+            ":label": incoming.label,
         };
 
         let result = tx.query_row(&sql, params, |row| {
@@ -343,6 +340,7 @@ impl ProcessIncomingRecordImpl for IncomingAddressesImpl {
 mod tests {
     use super::super::super::test::new_syncable_mem_db;
     use super::*;
+    use crate::config::NormalizationLevel;
     use crate::db::addresses::get_address;
     use crate::sync::common::tests::*;
 
@@ -420,7 +418,8 @@ mod tests {
     fn test_record(guid_prefix: char) -> InternalAddress {
         let json = test_json_record(guid_prefix);
         let address_payload = serde_json::from_value(json).unwrap();
-        InternalAddress::from_payload(address_payload).expect("should be valid")
+        InternalAddress::from_payload(address_payload, &AutofillFeatureConfig::default())
+            .expect("should be valid")
     }
 
     #[test]
@@ -484,7 +483,7 @@ mod tests {
                 .expect("should insert mirror record");
             }
 
-            let ri = IncomingAddressesImpl {};
+            let ri = IncomingAddressesImpl::default();
             ri.stage_incoming(
                 &tx,
                 array_to_incoming(tc.incoming_records),
@@ -497,7 +496,7 @@ mod tests {
                 |row| -> Result<IncomingContent<InternalAddress>> {
                     let guid: SyncGuid = row.get_unwrap("guid");
                     let payload: String = row.get_unwrap("payload");
-                    raw_payload_to_incoming(guid, payload)
+                    raw_payload_to_incoming(guid, payload, &ri.config)
                 },
             )?;
 
@@ -521,7 +520,7 @@ mod tests {
     fn test_change_record_guid() -> Result<()> {
         let mut db = new_syncable_mem_db();
         let tx = db.transaction()?;
-        let ri = IncomingAddressesImpl {};
+        let ri = IncomingAddressesImpl::default();
 
         ri.insert_local_record(&tx, test_record('C'))?;
 
@@ -540,7 +539,7 @@ mod tests {
     fn test_get_incoming() {
         let mut db = new_syncable_mem_db();
         let tx = db.transaction().expect("should get tx");
-        let ai = IncomingAddressesImpl {};
+        let ai = IncomingAddressesImpl::default();
         let record = test_record('C');
         let bso = record.clone().into_test_incoming_bso();
         do_test_incoming_same(&ai, &tx, record, bso);
@@ -564,19 +563,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_incoming_label() {
+        let mut json = test_json_record('C');
+        json["entry"]["label"] = json!("Work");
+        let address_payload = serde_json::from_value::<AddressPayload>(json).unwrap();
+        // Unlike fields we don't know about, the label is part of the record.
+        assert!(address_payload.entry.unknown_fields.is_empty());
+        let record =
+            InternalAddress::from_payload(address_payload, &AutofillFeatureConfig::default())
+                .unwrap();
+        assert_eq!(record.label, "Work");
+
+        let payload = serde_json::to_value(record.into_payload().unwrap()).unwrap();
+        assert_eq!(payload["entry"]["label"], "Work");
+
+        // An empty label isn't uploaded at all.
+        let record = InternalAddress::from_payload(
+            serde_json::from_value::<AddressPayload>(test_json_record('C')).unwrap(),
+            &AutofillFeatureConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(record.label, "");
+        let payload = serde_json::to_value(record.into_payload().unwrap()).unwrap();
+        assert!(payload["entry"].get("label").is_none());
+    }
+
+    #[test]
+    fn test_incoming_normalization() {
+        let mut json = test_json_record('C');
+        json["entry"]["tel"] = json!("(555) 123-4567");
+        let payload = || serde_json::from_value::<AddressPayload>(json.clone()).unwrap();
+
+        let record =
+            InternalAddress::from_payload(payload(), &AutofillFeatureConfig::default()).unwrap();
+        assert_eq!(record.country, "US");
+        assert_eq!(record.tel, "+15551234567");
+
+        // Synced records follow the same setting as local writes.
+        let config = AutofillFeatureConfig {
+            normalization: NormalizationLevel::None,
+            ..AutofillFeatureConfig::default()
+        };
+        let record = InternalAddress::from_payload(payload(), &config).unwrap();
+        assert_eq!(record.country, "United States");
+        assert_eq!(record.tel, "(555) 123-4567");
+    }
+
     #[test]
     fn test_incoming_tombstone() {
         let mut db = new_syncable_mem_db();
         let tx = db.transaction().expect("should get tx");
-        let ai = IncomingAddressesImpl {};
+        let ai = IncomingAddressesImpl::default();
         do_test_incoming_tombstone(&ai, &tx, test_record('C'));
     }
 
+    // An incoming record for an address we deleted locally (but haven't yet
+    // uploaded the tombstone for) should be resurrected.
+    #[test]
+    fn test_incoming_resurrects_local_tombstone() {
+        let mut db = new_syncable_mem_db();
+        let tx = db.transaction().expect("should get tx");
+        let ai = IncomingAddressesImpl::default();
+        let record = test_record('C');
+        let guid = record.guid.clone();
+        tx.execute(
+            "INSERT INTO addresses_tombstones (guid, time_deleted) VALUES (:guid, :time_deleted)",
+            named_params! {
+                ":guid": guid,
+                ":time_deleted": types::Timestamp::now(),
+            },
+        )
+        .expect("should insert tombstone");
+
+        ai.stage_incoming(&tx, vec![record.into_test_incoming_bso()], &NeverInterrupts)
+            .expect("stage should work");
+        let mut states = ai.fetch_incoming_states(&tx).expect("fetch should work");
+        assert_eq!(states.len(), 1);
+        assert!(matches!(states[0].local, LocalRecordInfo::Tombstone { .. }));
+        let action =
+            crate::sync::plan_incoming(&ai, &tx, states.pop().unwrap()).expect("plan should work");
+        assert!(matches!(
+            action,
+            crate::sync::IncomingAction::ResurrectLocalTombstone { .. }
+        ));
+        crate::sync::apply_incoming_action(&ai, &tx, action).expect("apply should work");
+
+        assert_eq!(get_address(&tx, &guid).expect("should exist").guid, guid);
+        let tombstones: u32 = tx
+            .query_one("SELECT COUNT(*) FROM addresses_tombstones")
+            .unwrap();
+        assert_eq!(tombstones, 0);
+    }
+
     #[test]
     fn test_staged_to_mirror() {
         let mut db = new_syncable_mem_db();
         let tx = db.transaction().expect("should get tx");
-        let ai = IncomingAddressesImpl {};
+        let ai = IncomingAddressesImpl::default();
         let record = test_record('C');
         let bso = record.clone().into_test_incoming_bso();
         do_test_staged_to_mirror(&ai, &tx, record, bso, "addresses_mirror");
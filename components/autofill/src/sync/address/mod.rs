@@ -12,6 +12,7 @@ use super::{
     MergeResult, Metadata, ProcessIncomingRecordImpl, ProcessOutgoingRecordImpl, SyncRecord,
     UnknownFields,
 };
+use crate::config::AutofillFeatureConfig;
 use crate::db::models::address::InternalAddress;
 use crate::error::*;
 use crate::sync_merge_field_check;
@@ -31,12 +32,17 @@ pub(crate) fn create_engine(store: Arc<crate::Store>) -> ConfigSyncEngine<Intern
             namespace: "addresses".to_string(),
             collection: "addresses".into(),
         },
-        store,
-        Box::new(AddressesEngineStorageImpl {}),
+        store.clone(),
+        Box::new(AddressesEngineStorageImpl {
+            config: store.config.clone(),
+        }),
     )
 }
 
-pub(super) struct AddressesEngineStorageImpl {}
+pub(super) struct AddressesEngineStorageImpl {
+    // Incoming records are normalized the same way as local writes.
+    config: AutofillFeatureConfig,
+}
 
 impl SyncEngineStorageImpl<InternalAddress> for AddressesEngineStorageImpl {
     fn get_incoming_impl(
@@ -44,7 +50,9 @@ impl SyncEngineStorageImpl<InternalAddress> for AddressesEngineStorageImpl {
         enc_key: &Option<String>,
     ) -> Result<Box<dyn ProcessIncomingRecordImpl<Record = InternalAddress>>> {
         assert!(enc_key.is_none());
-        Ok(Box::new(IncomingAddressesImpl {}))
+        Ok(Box::new(IncomingAddressesImpl {
+            config: self.config.clone(),
+        }))
     }
 
     fn reset_storage(&self, tx: &Transaction<'_>) -> Result<()> {
@@ -93,6 +101,9 @@ struct PayloadEntry {
     pub country: String,
     pub tel: String,
     pub email: String,
+    // Older clients don't know about the label, so only upload it when it's set.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
     // metadata (which isn't kebab-case for some historical reason...)
     #[serde(rename = "timeCreated")]
     pub time_created: Timestamp,
@@ -110,7 +121,7 @@ struct PayloadEntry {
 }
 
 impl InternalAddress {
-    fn from_payload(p: AddressPayload) -> Result<Self> {
+    fn from_payload(p: AddressPayload, config: &AutofillFeatureConfig) -> Result<Self> {
         if p.entry.version != 1 {
             // Always been version 1
             return Err(Error::InvalidSyncPayload(format!(
@@ -119,7 +130,7 @@ impl InternalAddress {
             )));
         }
 
-        Ok(InternalAddress {
+        let mut address = InternalAddress {
             guid: p.id,
             name: p.entry.name,
             organization: p.entry.organization,
@@ -131,6 +142,7 @@ impl InternalAddress {
             country: p.entry.country,
             tel: p.entry.tel,
             email: p.entry.email,
+            label: p.entry.label,
             metadata: Metadata {
                 time_created: p.entry.time_created,
                 time_last_used: p.entry.time_last_used,
@@ -138,7 +150,17 @@ impl InternalAddress {
                 times_used: p.entry.times_used,
                 sync_change_counter: 0,
             },
-        })
+        };
+        // Other clients may not normalize these fields the way we do, and we
+        // want records which only differ in formatting to dedupe.
+        config.normalize_address(
+            &mut address.country,
+            &mut address.address_level1,
+            &mut address.postal_code,
+            &mut address.tel,
+            &mut address.email,
+        );
+        Ok(address)
     }
 
     fn into_payload(self) -> Result<AddressPayload> {
@@ -163,6 +185,7 @@ impl InternalAddress {
                 country: self.country,
                 tel: self.tel,
                 email: self.email,
+                label: self.label,
                 time_created: self.metadata.time_created,
                 time_last_used: self.metadata.time_last_used,
                 time_last_modified: self.metadata.time_last_modified,
@@ -220,6 +243,7 @@ impl SyncRecord for InternalAddress {
         sync_merge_field_check!(country, incoming, local, mirror, merged_record);
         sync_merge_field_check!(tel, incoming, local, mirror, merged_record);
         sync_merge_field_check!(email, incoming, local, mirror, merged_record);
+        sync_merge_field_check!(label, incoming, local, mirror, merged_record);
 
         merged_record.metadata = incoming.metadata;
         merged_record
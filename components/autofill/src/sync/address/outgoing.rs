@@ -100,6 +100,7 @@ impl ProcessOutgoingRecordImpl for OutgoingAddressesImpl {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::AutofillFeatureConfig;
     use crate::db::{addresses::add_internal_address, models::address::InternalAddress};
     use crate::sync::{common::tests::*, test::new_syncable_mem_db, UnknownFields};
     use rusqlite::Connection;
@@ -178,7 +179,8 @@ mod tests {
     fn test_record(guid_prefix: char) -> InternalAddress {
         let json = test_json_record(guid_prefix);
         let payload = serde_json::from_value(json).unwrap();
-        InternalAddress::from_payload(payload).expect("should be valid")
+        InternalAddress::from_payload(payload, &AutofillFeatureConfig::default())
+            .expect("should be valid")
     }
 
     #[test]
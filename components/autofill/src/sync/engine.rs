@@ -5,6 +5,7 @@
 use super::{plan_incoming, ProcessIncomingRecordImpl, ProcessOutgoingRecordImpl, SyncRecord};
 use crate::error::*;
 use crate::Store;
+use interrupt_support::SqlInterruptHandle;
 use rusqlite::{
     types::{FromSql, ToSql},
     Connection, Transaction,
@@ -89,6 +90,10 @@ impl<T: SyncRecord + std::fmt::Debug> SyncEngine for ConfigSyncEngine<T> {
         self.config.collection.clone()
     }
 
+    fn interrupt_handle(&self) -> Option<Arc<SqlInterruptHandle>> {
+        Some(self.store.db.lock().unwrap().new_interrupt_handle())
+    }
+
     fn set_local_encryption_key(&mut self, key: &str) -> anyhow::Result<()> {
         self.local_enc_key = Some(key.to_string());
         Ok(())
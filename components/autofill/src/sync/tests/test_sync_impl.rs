@@ -368,3 +368,34 @@ fn test_plan_incoming_tombstone() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_metadata_merge() {
+    fn meta(created: u64, last_used: u64, times_used: i64) -> Metadata {
+        Metadata {
+            time_created: Timestamp(created),
+            time_last_used: Timestamp(last_used),
+            time_last_modified: Timestamp(last_used),
+            times_used,
+            sync_change_counter: 0,
+        }
+    }
+    // Both sides used the record since the last sync - usage from each side
+    // is added to the mirror's count.
+    let mirror = meta(100, 200, 5);
+    let mut incoming = meta(100, 400, 7);
+    let local = meta(90, 300, 6);
+    incoming.merge(&local, Some(&mirror));
+    assert_eq!(incoming.time_created, Timestamp(90));
+    assert_eq!(incoming.time_last_used, Timestamp(400));
+    assert_eq!(incoming.time_last_modified, Timestamp(400));
+    assert_eq!(incoming.times_used, 8);
+
+    // Without a mirror we can't tell what changed, so take the largest count
+    // rather than double-counting.
+    let mut incoming = meta(100, 400, 7);
+    incoming.merge(&local, None);
+    assert_eq!(incoming.time_created, Timestamp(90));
+    assert_eq!(incoming.time_last_used, Timestamp(400));
+    assert_eq!(incoming.times_used, 7);
+}
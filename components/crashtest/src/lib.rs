@@ -19,6 +19,9 @@ mod tests;
 
 uniffi::include_scaffolding!("crashtest");
 
+pub const UNIFFI_NAMESPACE: &str = "crashtest";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Trigger a hard abort inside the Rust code.
 ///
 /// This function simulates some kind of uncatchable illegal operation
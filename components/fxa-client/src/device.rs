@@ -119,10 +119,7 @@ impl FirefoxAccount {
     pub fn get_attached_clients(&self) -> ApiResult<Vec<AttachedClient>> {
         self.internal
             .lock()
-            .get_attached_clients()?
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<Result<_, _>>()
+            .get_attached_clients_with_capabilities()
     }
 
     /// Update the display name used for this application instance.
@@ -132,6 +129,8 @@ impl FirefoxAccount {
     /// This method modifies the name of the current application's device record, as seen by
     /// other applications and in the user's account management pages.
     ///
+    /// If the device record already has this name, this doesn't hit the server.
+    ///
     /// # Arguments
     ///
     ///    - `display_name` - the new name for the current device.
@@ -145,6 +144,25 @@ impl FirefoxAccount {
         self.internal.lock().set_device_name(display_name)
     }
 
+    /// Update the type of device this application instance is installed on.
+    ///
+    /// **💾 This method alters the persisted account state.**
+    ///
+    /// If the device record already has this type, this doesn't hit the server.
+    ///
+    /// # Arguments
+    ///
+    ///    - `device_type` - the new [type](DeviceType) of the current device.
+    ///
+    /// # Notes
+    ///
+    ///    - Device registration is only available to applications that have been
+    ///      granted the `https://identity.mozilla.com/apps/oldsync` scope.
+    #[handle_error(Error)]
+    pub fn set_device_type(&self, device_type: DeviceType) -> ApiResult<LocalDevice> {
+        self.internal.lock().set_device_type(device_type)
+    }
+
     /// Clear any custom display name used for this application instance.
     ///
     /// **💾 This method alters the persisted account state.**
@@ -265,4 +283,6 @@ pub struct AttachedClient {
     pub created_time: Option<i64>,
     pub last_access_time: Option<i64>,
     pub scope: Option<Vec<String>>,
+    /// The capabilities of the client, if it's registered as a device.
+    pub capabilities: Vec<DeviceCapability>,
 }
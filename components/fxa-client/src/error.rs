@@ -18,8 +18,8 @@ pub enum FxaError {
     /// or retry the operation with a freshly-generated token.
     #[error("authentication error")]
     Authentication,
-    /// Thrown if an operation fails due to network access problems.
-    /// The application may retry at a later time once connectivity is restored.
+    /// Thrown if an operation fails due to network access problems, or because the
+    /// server is having issues. The application may retry at a later time.
     #[error("network error")]
     Network,
     /// Thrown if the application attempts to complete an OAuth flow when no OAuth flow
@@ -118,6 +118,12 @@ pub enum Error {
     #[error("Client: {0} is not allowed to request scope: {1}")]
     ScopeNotAllowed(String, String),
 
+    #[error("Payload compression error: {0}")]
+    CompressionError(String),
+
+    #[error("Unsupported payload compression: {0}")]
+    UnsupportedCompression(String),
+
     #[error("Unsupported command: {0}")]
     UnsupportedCommand(&'static str),
 
@@ -195,6 +201,29 @@ pub enum Error {
     StateMachineLogicError(String),
 }
 
+impl Error {
+    /// Whether the error means our account credentials are missing or were
+    /// rejected, rather than a problem with this one request.
+    pub(crate) fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Error::RemoteError { code: 401, .. }
+                | Error::NoRefreshToken
+                | Error::NoScopedKey(_)
+                | Error::NoCachedToken(_)
+        )
+    }
+
+    /// Whether the FxA servers responded with a 5xx status.
+    pub(crate) fn is_server_error(&self) -> bool {
+        match self {
+            Error::RemoteError { code, .. } => (500..=599).contains(code),
+            Error::UnexpectedStatus(e) => e.status >= 500,
+            _ => false,
+        }
+    }
+}
+
 // Define how our internal errors are handled and converted to external errors
 // See `support/error/README.md` for how this works, especially the warning about PII.
 impl GetErrorHandling for Error {
@@ -202,13 +231,12 @@ impl GetErrorHandling for Error {
 
     fn get_error_handling(&self) -> ErrorHandling<Self::ExternalError> {
         match self {
-            Error::RemoteError { code: 401, .. }
-            | Error::NoRefreshToken
-            | Error::NoScopedKey(_)
-            | Error::NoCachedToken(_) => {
+            e if e.is_auth_error() => {
                 ErrorHandling::convert(FxaError::Authentication).log_warning()
             }
             Error::RequestError(_) => ErrorHandling::convert(FxaError::Network).log_warning(),
+            // Server errors aren't our bug, and are worth retrying later, like network errors.
+            e if e.is_server_error() => ErrorHandling::convert(FxaError::Network).log_warning(),
             Error::SyncScopedKeyMissingInServerResponse => {
                 ErrorHandling::convert(FxaError::SyncScopedKeyMissingInServerResponse)
                     .report_error("fxa-client-scoped-key-missing")
@@ -227,3 +255,59 @@ impl GetErrorHandling for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error_support::convert_log_report_error;
+
+    fn remote_error(code: u64) -> Error {
+        Error::RemoteError {
+            code,
+            errno: 999,
+            error: "error".to_string(),
+            message: "message".to_string(),
+            info: "info".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_server_errors_are_network_errors() {
+        assert!(matches!(
+            convert_log_report_error(remote_error(500)),
+            FxaError::Network
+        ));
+        assert!(matches!(
+            convert_log_report_error(remote_error(503)),
+            FxaError::Network
+        ));
+        assert!(matches!(
+            convert_log_report_error(Error::UnexpectedStatus(viaduct::UnexpectedStatus {
+                status: 502,
+                method: viaduct::Method::Get,
+                url: "https://accounts.firefox.com".parse().unwrap(),
+            })),
+            FxaError::Network
+        ));
+        assert!(matches!(
+            convert_log_report_error(Error::RequestError(viaduct::Error::NetworkError(
+                "offline".to_string()
+            ))),
+            FxaError::Network
+        ));
+    }
+
+    #[test]
+    fn test_client_errors_are_not_network_errors() {
+        assert!(matches!(
+            convert_log_report_error(remote_error(401)),
+            FxaError::Authentication
+        ));
+        assert!(matches!(
+            convert_log_report_error(remote_error(400)),
+            FxaError::Other
+        ));
+        assert!(!remote_error(400).is_server_error());
+        assert!(remote_error(599).is_server_error());
+    }
+}
@@ -23,15 +23,18 @@ impl FirefoxAccount {
         let (payload, sent_telemetry) =
             CloseTabsPayload::with_urls(urls.iter().map(|url| url.as_ref().to_owned()).collect());
         let oldsync_key = self.get_scoped_key(scopes::OLD_SYNC)?;
-        let command_payload = close_tabs::build_close_tabs_command(oldsync_key, target, &payload)?;
-        self.invoke_command(
-            close_tabs::COMMAND_NAME,
-            target,
-            &command_payload,
-            Some(close_tabs::COMMAND_TTL),
-        )?;
-        self.telemetry.record_command_sent(sent_telemetry);
-        Ok(())
+        let result = close_tabs::build_close_tabs_command(oldsync_key, target, &payload).and_then(
+            |command_payload| {
+                self.invoke_command(
+                    close_tabs::COMMAND_NAME,
+                    target,
+                    &command_payload,
+                    Some(close_tabs::COMMAND_TTL),
+                )
+            },
+        );
+        self.telemetry.record_send_result(sent_telemetry, &result);
+        result
     }
 
     pub(crate) fn handle_close_tabs_command(
@@ -56,6 +59,12 @@ impl FirefoxAccount {
                 Ok(IncomingDeviceCommand::TabsClosed { sender, payload })
             }
             Err(e) => {
+                self.telemetry
+                    .record_command_failed(telemetry::FailedCommand::received(
+                        telemetry::Command::CloseTabs,
+                        reason,
+                        &e,
+                    ));
                 log::warn!("Could not decrypt Close Remote Tabs payload. Diagnosing then resetting the Close Tabs keys.");
                 self.clear_close_tabs_keys();
                 self.reregister_current_capabilities()?;
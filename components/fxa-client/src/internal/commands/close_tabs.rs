@@ -10,6 +10,7 @@ use crate::{internal::telemetry, Error, Result, ScopedKey};
 
 use super::{
     super::device::Device,
+    compression,
     send_tab::{PrivateSendTabKeysV1, PublicSendTabKeys, SendTabKeysPayload},
 };
 
@@ -21,6 +22,9 @@ pub const COMMAND_TTL: u64 = 2 * 24 * 3600;
 pub struct EncryptedCloseTabsPayload {
     /// URL Safe Base 64 encrypted payload.
     encrypted: String,
+    /// How the payload was compressed before it was encrypted, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compression: Option<String>,
 }
 
 impl EncryptedCloseTabsPayload {
@@ -28,6 +32,7 @@ impl EncryptedCloseTabsPayload {
         rc_crypto::ensure_initialized();
         let encrypted = URL_SAFE_NO_PAD.decode(self.encrypted)?;
         let decrypted = ece::decrypt(keys.p256key(), keys.auth_secret(), &encrypted)?;
+        let decrypted = compression::decompress(self.compression.as_deref(), decrypted)?;
         Ok(serde_json::from_slice(&decrypted)?)
     }
 }
@@ -43,7 +48,11 @@ pub struct CloseTabsPayload {
 
 impl From<CloseTabsPayload> for crate::CloseTabsPayload {
     fn from(payload: CloseTabsPayload) -> Self {
-        crate::CloseTabsPayload { urls: payload.urls }
+        crate::CloseTabsPayload {
+            urls: payload.urls,
+            flow_id: payload.flow_id,
+            stream_id: payload.stream_id,
+        }
     }
 }
 
@@ -62,12 +71,19 @@ impl CloseTabsPayload {
 
     fn encrypt(&self, keys: PublicSendTabKeys) -> Result<EncryptedCloseTabsPayload> {
         rc_crypto::ensure_initialized();
-        let bytes = serde_json::to_vec(&self)?;
+        let mut bytes = serde_json::to_vec(&self)?;
+        let compression = keys.compression();
+        if let Some(method) = compression {
+            bytes = compression::compress(method, &bytes)?;
+        }
         let public_key = URL_SAFE_NO_PAD.decode(keys.public_key())?;
         let auth_secret = URL_SAFE_NO_PAD.decode(keys.auth_secret())?;
         let encrypted = ece::encrypt(&public_key, &auth_secret, &bytes)?;
         let encrypted = URL_SAFE_NO_PAD.encode(encrypted);
-        Ok(EncryptedCloseTabsPayload { encrypted })
+        Ok(EncryptedCloseTabsPayload {
+            encrypted,
+            compression: compression.map(Into::into),
+        })
     }
 }
 
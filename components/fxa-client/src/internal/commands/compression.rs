@@ -0,0 +1,111 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+/// Send Tab and Close Tabs payloads can be compressed before they're encrypted,
+/// so that large ones still fit in the size the FxA server accepts for a command.
+///
+/// A device advertises the compression methods it can handle next to its public
+/// keys in the `PublicSendTabKeys` bundle of its command data. Senders only
+/// compress payloads for devices that advertise a method, and tag the encrypted
+/// payload with the method they used, so devices that don't know about
+/// compression keep receiving plain payloads.
+use std::io::{Read, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder};
+
+use crate::{Error, Result};
+
+/// Raw DEFLATE, as described in RFC 1951.
+pub const DEFLATE: &str = "deflate";
+
+/// The compression methods we can handle, in order of preference.
+pub const SUPPORTED: &[&str] = &[DEFLATE];
+
+// Command payloads are a few KB at most, so anything that expands past this
+// while decompressing is malformed or malicious.
+const MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024;
+
+/// Picks the compression method to use for a device that supports `methods`.
+pub(crate) fn negotiate(methods: &[String]) -> Option<&'static str> {
+    SUPPORTED
+        .iter()
+        .find(|supported| methods.iter().any(|m| m == *supported))
+        .copied()
+}
+
+pub(crate) fn compress(method: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    match method {
+        DEFLATE => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(bytes)
+                .map_err(|e| Error::CompressionError(e.to_string()))?;
+            encoder
+                .finish()
+                .map_err(|e| Error::CompressionError(e.to_string()))
+        }
+        _ => Err(Error::UnsupportedCompression(method.to_owned())),
+    }
+}
+
+/// Decompresses a decrypted payload that was compressed with `method`, or
+/// returns it as is if it wasn't compressed.
+pub(crate) fn decompress(method: Option<&str>, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    match method {
+        None => Ok(bytes),
+        Some(DEFLATE) => {
+            let mut decompressed = Vec::new();
+            DeflateDecoder::new(bytes.as_slice())
+                .take(MAX_DECOMPRESSED_SIZE + 1)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| Error::CompressionError(e.to_string()))?;
+            if decompressed.len() as u64 > MAX_DECOMPRESSED_SIZE {
+                return Err(Error::CompressionError(
+                    "decompressed payload is too large".to_owned(),
+                ));
+            }
+            Ok(decompressed)
+        }
+        Some(method) => Err(Error::UnsupportedCompression(method.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&[]), None);
+        assert_eq!(negotiate(&["zstd".to_owned()]), None);
+        assert_eq!(
+            negotiate(&["zstd".to_owned(), DEFLATE.to_owned()]),
+            Some(DEFLATE)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let url = format!("https://example.com/?q={}", "a".repeat(10_000));
+        let compressed = compress(DEFLATE, url.as_bytes())?;
+        assert!(compressed.len() < url.len());
+        assert_eq!(decompress(Some(DEFLATE), compressed)?, url.as_bytes());
+        assert_eq!(decompress(None, url.as_bytes().to_vec())?, url.as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_errors() -> Result<()> {
+        assert!(matches!(
+            decompress(Some("zstd"), Vec::new()),
+            Err(Error::UnsupportedCompression(_))
+        ));
+        let bomb = compress(DEFLATE, &vec![0; MAX_DECOMPRESSED_SIZE as usize + 1])?;
+        assert!(matches!(
+            decompress(Some(DEFLATE), bomb),
+            Err(Error::CompressionError(_))
+        ));
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 pub mod close_tabs;
+mod compression;
 pub mod send_tab;
 
 pub use close_tabs::CloseTabsPayload;
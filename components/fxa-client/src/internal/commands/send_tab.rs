@@ -20,6 +20,7 @@ use rc_crypto::ece::{self, EcKeyComponents};
 use sync15::{EncryptedPayload, KeyBundle};
 
 use super::super::{device::Device, scopes, telemetry};
+use super::compression;
 use crate::{Error, Result, ScopedKey};
 
 pub const COMMAND_NAME: &str = "https://identity.mozilla.com/cmd/open-uri";
@@ -28,6 +29,9 @@ pub const COMMAND_NAME: &str = "https://identity.mozilla.com/cmd/open-uri";
 pub struct EncryptedSendTabPayload {
     /// URL Safe Base 64 encrypted send-tab payload.
     encrypted: String,
+    /// How the payload was compressed before it was encrypted, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compression: Option<String>,
 }
 
 impl EncryptedSendTabPayload {
@@ -35,6 +39,7 @@ impl EncryptedSendTabPayload {
         rc_crypto::ensure_initialized();
         let encrypted = URL_SAFE_NO_PAD.decode(self.encrypted)?;
         let decrypted = ece::decrypt(keys.p256key(), keys.auth_secret(), &encrypted)?;
+        let decrypted = compression::decompress(self.compression.as_deref(), decrypted)?;
         Ok(serde_json::from_slice(&decrypted)?)
     }
 }
@@ -75,12 +80,19 @@ impl SendTabPayload {
     }
     fn encrypt(&self, keys: PublicSendTabKeys) -> Result<EncryptedSendTabPayload> {
         rc_crypto::ensure_initialized();
-        let bytes = serde_json::to_vec(&self)?;
+        let mut bytes = serde_json::to_vec(&self)?;
+        let compression = keys.compression();
+        if let Some(method) = compression {
+            bytes = compression::compress(method, &bytes)?;
+        }
         let public_key = URL_SAFE_NO_PAD.decode(keys.public_key())?;
         let auth_secret = URL_SAFE_NO_PAD.decode(keys.auth_secret())?;
         let encrypted = ece::encrypt(&public_key, &auth_secret, &bytes)?;
         let encrypted = URL_SAFE_NO_PAD.encode(encrypted);
-        Ok(EncryptedSendTabPayload { encrypted })
+        Ok(EncryptedSendTabPayload {
+            encrypted,
+            compression: compression.map(Into::into),
+        })
     }
 }
 
@@ -186,6 +198,10 @@ pub struct PublicSendTabKeys {
     /// URL Safe Base 64 encoded auth secret.
     #[serde(rename = "authSecret")]
     auth_secret: String,
+    /// The methods the device can decompress command payloads with. Older
+    /// devices don't include this, and only receive uncompressed payloads.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    compression: Vec<String>,
 }
 
 impl PublicSendTabKeys {
@@ -210,6 +226,10 @@ impl PublicSendTabKeys {
     pub(crate) fn auth_secret(&self) -> &str {
         &self.auth_secret
     }
+    /// The method to compress payloads for this device with, if any.
+    pub(crate) fn compression(&self) -> Option<&'static str> {
+        compression::negotiate(&self.compression)
+    }
 }
 
 impl From<PrivateSendTabKeys> for PublicSendTabKeys {
@@ -217,6 +237,10 @@ impl From<PrivateSendTabKeys> for PublicSendTabKeys {
         Self {
             public_key: URL_SAFE_NO_PAD.encode(internal.p256key.public_key()),
             auth_secret: URL_SAFE_NO_PAD.encode(&internal.auth_secret),
+            compression: compression::SUPPORTED
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
         }
     }
 }
@@ -272,4 +296,24 @@ mod tests {
         assert_eq!(payload.flow_id, p2.flow_id);
         assert_eq!(payload.stream_id, p2.stream_id);
     }
+
+    #[test]
+    fn test_encrypt_compresses_for_devices_that_support_it() -> Result<()> {
+        let url = format!("https://example.com/?q={}", "a".repeat(10_000));
+        let (payload, _) = SendTabPayload::single_tab("title", &url);
+        let keys = PrivateSendTabKeys::from_random()?;
+
+        let encrypted = payload.encrypt(keys.clone().into())?;
+        assert_eq!(encrypted.compression.as_deref(), Some(compression::DEFLATE));
+        assert!(encrypted.encrypted.len() < url.len());
+        assert_eq!(encrypted.decrypt(&keys)?.entries[0].url, url);
+
+        // Devices that don't advertise any compression get plain payloads.
+        let mut old_public_keys = PublicSendTabKeys::from(keys.clone());
+        old_public_keys.compression.clear();
+        let encrypted = payload.encrypt(old_public_keys)?;
+        assert_eq!(encrypted.compression, None);
+        assert_eq!(encrypted.decrypt(&keys)?.entries[0].url, url);
+        Ok(())
+    }
 }
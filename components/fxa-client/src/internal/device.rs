@@ -2,7 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 pub use super::http_client::{GetDeviceResponse as Device, PushSubscription};
 use super::{
@@ -12,7 +15,8 @@ use super::{
         IncomingDeviceCommand,
     },
     http_client::{
-        DeviceUpdateRequest, DeviceUpdateRequestBuilder, PendingCommand, UpdateDeviceResponse,
+        DeviceUpdateRequest, DeviceUpdateRequestBuilder, PendingCommand, PendingCommandsResponse,
+        UpdateDeviceResponse,
     },
     scopes, telemetry, util, CachedResponse, FirefoxAccount,
 };
@@ -22,6 +26,12 @@ use sync15::DeviceType;
 // An devices response is considered fresh for `DEVICES_FRESHNESS_THRESHOLD` ms.
 const DEVICES_FRESHNESS_THRESHOLD: u64 = 60_000; // 1 minute
 
+// Commands tend to arrive in bursts (eg, sending several tabs at once), so after
+// we see one we recommend polling more often for a while.
+const ACTIVE_POLL_PERIOD: Duration = Duration::from_secs(10 * 60);
+const ACTIVE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
 /// The reason we are fetching commands.
 #[derive(Clone, Copy)]
 pub enum CommandFetchReason {
@@ -54,6 +64,7 @@ impl FirefoxAccount {
             cached_at: util::now(),
             etag: "".into(),
         });
+        self.update_push_endpoint_expired(&response);
 
         Ok(response)
     }
@@ -65,6 +76,31 @@ impl FirefoxAccount {
             .find(|d| d.is_current_device))
     }
 
+    /// Returns true if the server says the push subscription for our device
+    /// has expired. The application should then get a new subscription from
+    /// its push service and pass it to `set_push_subscription`, otherwise it
+    /// won't receive account events or commands via push.
+    pub fn is_push_subscription_expired(&mut self) -> Result<bool> {
+        Ok(self
+            .get_current_device()?
+            .map_or(false, |d| d.push_endpoint_expired))
+    }
+
+    // The devices list is where the server tells us that our push endpoint has
+    // expired, so we keep the local device info in sync with it.
+    fn update_push_endpoint_expired(&mut self, devices: &[Device]) {
+        let Some(current_device) = devices.iter().find(|d| d.is_current_device) else {
+            return;
+        };
+        if let Some(local_device) = self.state.server_local_device_info() {
+            if local_device.push_endpoint_expired != current_device.push_endpoint_expired {
+                let mut local_device = local_device.clone();
+                local_device.push_endpoint_expired = current_device.push_endpoint_expired;
+                self.state.update_server_local_device_info(local_device);
+            }
+        }
+    }
+
     /// Replaces the internal set of "tracked" device capabilities by re-registering
     /// new capabilities and returns a set of device commands to register with the
     /// server.
@@ -188,15 +224,29 @@ impl FirefoxAccount {
         self.fetch_and_parse_commands(last_command_index + 1, None, reason)
     }
 
+    /// Returns how long applications which can't receive push messages should
+    /// wait before polling for commands again.
+    ///
+    /// This is based on how recently we've received commands and on any backoff
+    /// the server asked us to respect.
+    pub fn get_recommended_poll_interval(&self) -> Duration {
+        self.command_poll_scheduler
+            .recommended_interval(Instant::now())
+    }
+
     pub fn get_command_for_index(&mut self, index: u64) -> Result<IncomingDeviceCommand> {
-        let refresh_token = self.get_refresh_token()?;
-        let pending_commands =
-            self.client
-                .get_pending_commands(self.state.config(), refresh_token, index, Some(1))?;
-        self.parse_commands_messages(pending_commands.messages, CommandFetchReason::Push(index))?
+        let pending_commands = self.get_pending_commands(index, Some(1))?;
+        let handled_index = pending_commands.messages.first().map(|msg| msg.index);
+        let command = self
+            .parse_commands_messages(pending_commands.messages, CommandFetchReason::Push(index))?
             .into_iter()
             .next()
-            .ok_or_else(|| Error::CommandNotFound)
+            .ok_or_else(|| Error::CommandNotFound)?;
+        // Remember the command, so polling doesn't return it a second time.
+        if let Some(handled_index) = handled_index {
+            self.state.mark_command_handled_via_push(handled_index);
+        }
+        Ok(command)
     }
 
     fn fetch_and_parse_commands(
@@ -205,25 +255,55 @@ impl FirefoxAccount {
         limit: Option<u64>,
         reason: CommandFetchReason,
     ) -> Result<Vec<IncomingDeviceCommand>> {
-        let refresh_token = self.get_refresh_token()?;
-        let pending_commands =
-            self.client
-                .get_pending_commands(self.state.config(), refresh_token, index, limit)?;
+        let pending_commands = self.get_pending_commands(index, limit)?;
         if pending_commands.messages.is_empty() {
             return Ok(Vec::new());
         }
-        log::info!("Handling {} messages", pending_commands.messages.len());
-        let device_commands = self.parse_commands_messages(pending_commands.messages, reason)?;
+        // Skip the commands we already handled from push messages.
+        let messages = pending_commands
+            .messages
+            .into_iter()
+            .filter(|msg| !self.state.was_command_handled(msg.index))
+            .collect::<Vec<_>>();
+        log::info!("Handling {} messages", messages.len());
+        let device_commands = self.parse_commands_messages(messages, reason)?;
         self.state
             .set_last_handled_command_index(pending_commands.index);
         Ok(device_commands)
     }
 
+    // Fetches pending commands, remembering what we need to recommend a poll
+    // interval.
+    fn get_pending_commands(
+        &mut self,
+        index: u64,
+        limit: Option<u64>,
+    ) -> Result<PendingCommandsResponse> {
+        let refresh_token = self.get_refresh_token()?;
+        let result =
+            self.client
+                .get_pending_commands(self.state.config(), refresh_token, index, limit);
+        let now = Instant::now();
+        match &result {
+            Ok(pending_commands) if !pending_commands.messages.is_empty() => {
+                self.command_poll_scheduler.on_commands_received(now)
+            }
+            Err(Error::BackoffError(seconds)) => {
+                self.command_poll_scheduler.on_backoff(*seconds, now)
+            }
+            _ => (),
+        }
+        result
+    }
+
     fn parse_commands_messages(
         &mut self,
         messages: Vec<PendingCommand>,
         reason: CommandFetchReason,
     ) -> Result<Vec<IncomingDeviceCommand>> {
+        if messages.is_empty() {
+            return Ok(Vec::new());
+        }
         let devices = self.get_devices(false)?;
         let parsed_commands = messages
             .into_iter()
@@ -270,11 +350,34 @@ impl FirefoxAccount {
         }
     }
 
+    /// Sets the name of the current device, unless it already has that name.
+    ///
+    /// **💾 This method alters the persisted account state.**
     pub fn set_device_name(&mut self, name: &str) -> Result<LocalDevice> {
+        if let Some(local_device) = self.state.server_local_device_info() {
+            if local_device.display_name == name {
+                return Ok(local_device.clone());
+            }
+        }
         let update = DeviceUpdateRequestBuilder::new().display_name(name).build();
         self.update_device(update)
     }
 
+    /// Sets the type of the current device, unless it already has that type.
+    ///
+    /// **💾 This method alters the persisted account state.**
+    pub fn set_device_type(&mut self, device_type: DeviceType) -> Result<LocalDevice> {
+        if let Some(local_device) = self.state.server_local_device_info() {
+            if local_device.device_type == device_type {
+                return Ok(local_device.clone());
+            }
+        }
+        let update = DeviceUpdateRequestBuilder::new()
+            .device_type(&device_type)
+            .build();
+        self.update_device(update)
+    }
+
     pub fn clear_device_name(&mut self) -> Result<()> {
         let update = DeviceUpdateRequestBuilder::new()
             .clear_display_name()
@@ -283,10 +386,26 @@ impl FirefoxAccount {
         Ok(())
     }
 
+    /// Registers a push subscription for the current device. If the device is
+    /// already registered with this subscription, and the server hasn't told
+    /// us it expired, this doesn't hit the server.
+    ///
+    /// **💾 This method alters the persisted account state.**
     pub fn set_push_subscription(
         &mut self,
         push_subscription: PushSubscription,
     ) -> Result<LocalDevice> {
+        if let Some(local_device) = self.state.server_local_device_info() {
+            let registered = local_device
+                .push_subscription
+                .clone()
+                .map(PushSubscription::from);
+            if !local_device.push_endpoint_expired
+                && registered.as_ref() == Some(&push_subscription)
+            {
+                return Ok(local_device.clone());
+            }
+        }
         let update = DeviceUpdateRequestBuilder::new()
             .push_subscription(&push_subscription)
             .build();
@@ -399,19 +518,25 @@ impl From<UpdateDeviceResponse> for LocalDevice {
     }
 }
 
+/// Returns the capabilities of a device with the given registered commands,
+/// ignoring commands we don't know about.
+pub(crate) fn capabilities_from_commands(
+    available_commands: &HashMap<String, String>,
+) -> Vec<DeviceCapability> {
+    available_commands
+        .keys()
+        .filter_map(|k| match k.as_str() {
+            commands::send_tab::COMMAND_NAME => Some(DeviceCapability::SendTab),
+            commands::close_tabs::COMMAND_NAME => Some(DeviceCapability::CloseTabs),
+            _ => None,
+        })
+        .collect()
+}
+
 impl TryFrom<Device> for crate::Device {
     type Error = Error;
     fn try_from(d: Device) -> Result<Self> {
-        let capabilities: Vec<_> = d
-            .available_commands
-            .keys()
-            .filter_map(|k| match k.as_str() {
-                commands::send_tab::COMMAND_NAME => Some(DeviceCapability::SendTab),
-                commands::close_tabs::COMMAND_NAME => Some(DeviceCapability::CloseTabs),
-                _ => None,
-            })
-            .map(Into::into)
-            .collect();
+        let capabilities = capabilities_from_commands(&d.available_commands);
         Ok(crate::Device {
             id: d.common.id,
             display_name: d.common.display_name,
@@ -425,8 +550,39 @@ impl TryFrom<Device> for crate::Device {
     }
 }
 
+/// Tracks what we need to recommend how often applications which can't receive
+/// push messages should poll for commands.
+#[derive(Default)]
+pub(crate) struct CommandPollScheduler {
+    last_command_received: Option<Instant>,
+    backoff_until: Option<Instant>,
+}
+
+impl CommandPollScheduler {
+    fn on_commands_received(&mut self, now: Instant) {
+        self.last_command_received = Some(now);
+    }
+
+    fn on_backoff(&mut self, seconds: u64, now: Instant) {
+        self.backoff_until = now.checked_add(Duration::from_secs(seconds));
+    }
+
+    fn recommended_interval(&self, now: Instant) -> Duration {
+        let interval = match self.last_command_received {
+            Some(received) if now.saturating_duration_since(received) < ACTIVE_POLL_PERIOD => {
+                ACTIVE_POLL_INTERVAL
+            }
+            _ => IDLE_POLL_INTERVAL,
+        };
+        match self.backoff_until {
+            Some(until) => interval.max(until.saturating_duration_since(now)),
+            None => interval,
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::internal::http_client::*;
     use crate::internal::oauth::RefreshToken;
@@ -437,7 +593,7 @@ mod tests {
     use std::collections::HashSet;
     use std::sync::Arc;
 
-    fn setup() -> FirefoxAccount {
+    pub(crate) fn setup() -> FirefoxAccount {
         // I'd love to be able to configure a single mocked client here,
         // but can't work out how to do that within the typesystem.
         let config = Config::stable_dev("12345678", "https://foo.bar");
@@ -497,6 +653,46 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_device_updates_do_not_hit_the_server_if_nothing_has_changed() {
+        let mut fxa = setup();
+        let push_subscription = PushSubscription {
+            endpoint: "https://push.example.com/endpoint".to_owned(),
+            public_key: "public-key".to_owned(),
+            auth_key: "auth-key".to_owned(),
+        };
+
+        let mut client = MockFxAClient::new();
+        let response_push_subscription = push_subscription.clone();
+        client
+            .expect_update_device_record()
+            .with(always(), eq("refreshtok"), always())
+            .times(2)
+            .returning(move |_, _, _| {
+                Ok(UpdateDeviceResponse {
+                    id: "device1".to_string(),
+                    display_name: "My Device".to_string(),
+                    device_type: DeviceType::Mobile,
+                    push_subscription: Some(response_push_subscription.clone()),
+                    available_commands: HashMap::default(),
+                    push_endpoint_expired: false,
+                })
+            });
+        fxa.set_client(Arc::new(client));
+
+        fxa.set_device_name("My Device").unwrap();
+        fxa.set_device_name("My Device").unwrap();
+        fxa.set_device_type(DeviceType::Mobile).unwrap();
+        fxa.set_push_subscription(push_subscription.clone())
+            .unwrap();
+
+        // Once the server tells us the subscription expired, we re-register it.
+        let mut local_device = fxa.state.server_local_device_info().unwrap().clone();
+        local_device.push_endpoint_expired = true;
+        fxa.state.update_server_local_device_info(local_device);
+        fxa.set_push_subscription(push_subscription).unwrap();
+    }
+
     #[test]
     fn test_ensure_capabilities_updates_the_server_if_capabilities_increase() {
         let mut fxa = setup();
@@ -859,6 +1055,53 @@ mod tests {
         assert_eq!(cached_devices[0].id, cached_devices2[0].id);
     }
 
+    #[test]
+    fn test_push_subscription_expired() {
+        let mut fxa = setup();
+        fxa.state.update_server_local_device_info(LocalDevice {
+            id: "device1".to_string(),
+            display_name: "".to_string(),
+            device_type: DeviceType::Desktop,
+            capabilities: vec![],
+            push_subscription: None,
+            push_endpoint_expired: false,
+        });
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_devices()
+            .with(always(), always())
+            .times(1)
+            .returning(|_, _| {
+                Ok(vec![Device {
+                    common: DeviceResponseCommon {
+                        id: "device1".into(),
+                        display_name: "".to_string(),
+                        device_type: DeviceType::Desktop,
+                        push_subscription: None,
+                        available_commands: HashMap::new(),
+                        push_endpoint_expired: true,
+                    },
+                    is_current_device: true,
+                    location: DeviceLocation {
+                        city: None,
+                        country: None,
+                        state: None,
+                        state_code: None,
+                    },
+                    last_access_time: None,
+                }])
+            });
+        fxa.set_client(Arc::new(client));
+
+        assert!(fxa.is_push_subscription_expired().unwrap());
+        assert!(
+            fxa.state
+                .server_local_device_info()
+                .unwrap()
+                .push_endpoint_expired
+        );
+    }
+
     #[test]
     fn test_get_devices_network_errors() {
         let mut fxa = setup();
@@ -885,4 +1128,87 @@ mod tests {
         assert!(res.is_err());
         assert!(fxa.devices_cache.is_none());
     }
+
+    #[test]
+    fn test_recommended_poll_interval() {
+        let start = Instant::now();
+        let mut scheduler = CommandPollScheduler::default();
+        assert_eq!(scheduler.recommended_interval(start), IDLE_POLL_INTERVAL);
+
+        scheduler.on_commands_received(start);
+        assert_eq!(scheduler.recommended_interval(start), ACTIVE_POLL_INTERVAL);
+        let later = start + ACTIVE_POLL_PERIOD;
+        assert_eq!(scheduler.recommended_interval(later), IDLE_POLL_INTERVAL);
+
+        // A server backoff longer than the interval takes precedence...
+        scheduler.on_backoff(3600, later);
+        assert_eq!(
+            scheduler.recommended_interval(later),
+            Duration::from_secs(3600)
+        );
+        // ...until it's over.
+        let after_backoff = later + Duration::from_secs(3600);
+        assert_eq!(
+            scheduler.recommended_interval(after_backoff),
+            IDLE_POLL_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_poll_device_commands_backoff() {
+        let mut fxa = setup();
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_pending_commands()
+            .with(always(), eq("refreshtok"), eq(1), eq(None))
+            .times(1)
+            .returning(|_, _, _, _| Err(Error::BackoffError(7200)));
+        fxa.set_client(Arc::new(client));
+
+        assert!(fxa.poll_device_commands(CommandFetchReason::Poll).is_err());
+        let interval = fxa.get_recommended_poll_interval();
+        assert!(interval > IDLE_POLL_INTERVAL);
+        assert!(interval <= Duration::from_secs(7200));
+    }
+
+    #[test]
+    fn test_poll_device_commands_skips_commands_handled_via_push() {
+        let mut fxa = setup();
+        fxa.state.set_last_handled_command_index(3);
+        fxa.state.mark_command_handled_via_push(5);
+        assert!(fxa.state.was_command_handled(3));
+        assert!(!fxa.state.was_command_handled(4));
+        assert!(fxa.state.was_command_handled(5));
+
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_pending_commands()
+            .with(always(), eq("refreshtok"), eq(4), eq(None))
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(PendingCommandsResponse {
+                    index: 5,
+                    last: Some(true),
+                    messages: vec![PendingCommand {
+                        index: 5,
+                        data: CommandData {
+                            command: commands::send_tab::COMMAND_NAME.to_owned(),
+                            payload: serde_json::json!({}),
+                            sender: None,
+                        },
+                    }],
+                })
+            });
+        // The command was already handled, so we don't even need the devices
+        // to parse it.
+        client.expect_get_devices().times(0);
+        fxa.set_client(Arc::new(client));
+
+        let commands = fxa.poll_device_commands(CommandFetchReason::Poll).unwrap();
+        assert!(commands.is_empty());
+        assert_eq!(fxa.state.last_handled_command_index(), Some(5));
+        // Command 5 is now covered by the index, and command 6 is new.
+        assert!(fxa.state.was_command_handled(5));
+        assert!(!fxa.state.was_command_handled(6));
+    }
 }
@@ -697,7 +697,7 @@ pub struct CommandData {
     pub sender: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PushSubscription {
     #[serde(rename = "pushCallback")]
     pub endpoint: String,
@@ -14,7 +14,7 @@ use self::{
 use crate::{DeviceConfig, Error, FxaConfig, FxaRustAuthState, FxaState, Result};
 use serde_derive::*;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     sync::Arc,
 };
 use url::Url;
@@ -54,6 +54,7 @@ pub struct FirefoxAccount {
     attached_clients_cache: Option<CachedResponse<Vec<http_client::GetAttachedClientResponse>>>,
     devices_cache: Option<CachedResponse<Vec<http_client::GetDeviceResponse>>>,
     auth_circuit_breaker: AuthCircuitBreaker,
+    command_poll_scheduler: device::CommandPollScheduler,
     telemetry: FxaTelemetry,
     // TODO: Cleanup our usage of the word "state" and change this field name to `state`
     // https://bugzilla.mozilla.org/show_bug.cgi?id=1868610
@@ -70,6 +71,7 @@ impl FirefoxAccount {
             attached_clients_cache: None,
             devices_cache: None,
             auth_circuit_breaker: Default::default(),
+            command_poll_scheduler: Default::default(),
             telemetry: FxaTelemetry::new(),
             auth_state: FxaState::Uninitialized,
             device_config: None,
@@ -91,6 +93,7 @@ impl FirefoxAccount {
             last_seen_profile: None,
             access_token_cache: HashMap::new(),
             logged_out_from_auth_issues: false,
+            commands_handled_via_push: BTreeSet::new(),
         })
     }
 
@@ -235,6 +238,7 @@ impl FirefoxAccount {
         }
         self.state.disconnect();
         self.clear_devices_and_attached_clients_cache();
+        self.command_poll_scheduler = Default::default();
         self.telemetry = FxaTelemetry::new();
     }
 
@@ -3,7 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 pub use super::super::http_client::GetAttachedClientResponse as AttachedClient;
-use super::super::{util, CachedResponse, FirefoxAccount};
+use super::super::{device::capabilities_from_commands, util, CachedResponse, FirefoxAccount};
 use crate::{Error, Result};
 
 // An attached clients response is considered fresh for `ATTACHED_CLIENTS_FRESHNESS_THRESHOLD` ms.
@@ -30,6 +30,33 @@ impl FirefoxAccount {
 
         Ok(response)
     }
+
+    /// Fetches the list of attached clients, along with the capabilities of
+    /// the clients that are registered as devices.
+    ///
+    /// If the devices can't be fetched, the clients are still returned, but
+    /// without capabilities.
+    pub fn get_attached_clients_with_capabilities(&mut self) -> Result<Vec<crate::AttachedClient>> {
+        let attached_clients = self.get_attached_clients()?;
+        let devices = self.get_devices(false).unwrap_or_else(|e| {
+            log::warn!("Couldn't fetch devices for attached clients: {e}");
+            Vec::new()
+        });
+        attached_clients
+            .into_iter()
+            .map(|attached_client| {
+                let capabilities = attached_client
+                    .device_id
+                    .as_ref()
+                    .and_then(|id| devices.iter().find(|d| d.id == *id))
+                    .map(|d| capabilities_from_commands(&d.available_commands))
+                    .unwrap_or_default();
+                let mut attached_client = crate::AttachedClient::try_from(attached_client)?;
+                attached_client.capabilities = capabilities;
+                Ok(attached_client)
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<AttachedClient> for crate::AttachedClient {
@@ -44,6 +71,7 @@ impl TryFrom<AttachedClient> for crate::AttachedClient {
             created_time: c.created_time.map(TryInto::try_into).transpose()?,
             last_access_time: c.last_access_time.map(TryInto::try_into).transpose()?,
             scope: c.scope,
+            capabilities: Vec::new(),
         })
     }
 }
@@ -51,9 +79,13 @@ impl TryFrom<AttachedClient> for crate::AttachedClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::internal::{config::Config, http_client::MockFxAClient};
+    use crate::internal::{
+        commands::send_tab, config::Config, http_client::*, oauth::RefreshToken,
+    };
+    use crate::DeviceCapability;
     use mockall::predicate::always;
     use mockall::predicate::eq;
+    use std::collections::{HashMap, HashSet};
     use std::sync::Arc;
     use sync15::DeviceType;
 
@@ -132,4 +164,81 @@ mod tests {
         assert!(res.is_err());
         assert!(fxa.attached_clients_cache.is_none());
     }
+
+    #[test]
+    fn test_get_attached_clients_with_capabilities() {
+        let config = Config::stable_dev("12345678", "https://foo.bar");
+        let mut fxa = FirefoxAccount::with_config(config);
+        fxa.set_session_token("session");
+        fxa.state.force_refresh_token(RefreshToken {
+            token: "refreshtok".to_string(),
+            scopes: HashSet::default(),
+        });
+
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_attached_clients()
+            .with(always(), eq("session"))
+            .times(1)
+            .returning(|_, _| {
+                let attached_client = |device_id: Option<&str>| AttachedClient {
+                    client_id: Some("12345678".into()),
+                    session_token_id: None,
+                    refresh_token_id: None,
+                    device_id: device_id.map(Into::into),
+                    device_type: DeviceType::Desktop,
+                    is_current_session: false,
+                    name: None,
+                    created_time: None,
+                    last_access_time: Some(1_700_000_000_000),
+                    scope: None,
+                    user_agent: "attachedClientsUserAgent".into(),
+                    os: None,
+                };
+                Ok(vec![
+                    attached_client(Some("device1")),
+                    attached_client(None),
+                ])
+            });
+        client
+            .expect_get_devices()
+            .with(always(), eq("refreshtok"))
+            .times(1)
+            .returning(|_, _| {
+                Ok(vec![GetDeviceResponse {
+                    common: DeviceResponseCommon {
+                        id: "device1".into(),
+                        display_name: "".to_string(),
+                        device_type: DeviceType::Desktop,
+                        push_subscription: None,
+                        available_commands: HashMap::from([(
+                            send_tab::COMMAND_NAME.to_owned(),
+                            "".to_owned(),
+                        )]),
+                        push_endpoint_expired: false,
+                    },
+                    is_current_device: false,
+                    location: DeviceLocation {
+                        city: None,
+                        country: None,
+                        state: None,
+                        state_code: None,
+                    },
+                    last_access_time: None,
+                }])
+            });
+        fxa.set_client(Arc::new(client));
+
+        let attached_clients = fxa.get_attached_clients_with_capabilities().unwrap();
+        assert_eq!(attached_clients.len(), 2);
+        assert_eq!(
+            attached_clients[0].capabilities,
+            vec![DeviceCapability::SendTab]
+        );
+        assert_eq!(
+            attached_clients[0].last_access_time,
+            Some(1_700_000_000_000)
+        );
+        assert!(attached_clients[1].capabilities.is_empty());
+    }
 }
@@ -10,6 +10,7 @@ use super::{
         },
         IncomingDeviceCommand,
     },
+    device::Device,
     http_client::GetDeviceResponse,
     scopes, telemetry, FirefoxAccount,
 };
@@ -34,11 +35,6 @@ impl FirefoxAccount {
     }
 
     /// Send a single tab to another device designated by its device ID.
-    /// XXX - We need a new send_tabs_to_devices() so we can correctly record
-    /// telemetry for these cases.
-    /// This probably requires a new "Tab" struct with the title and url.
-    /// android-components has SendToAllUseCase(), so this isn't just theoretical.
-    /// See <https://github.com/mozilla/application-services/issues/3402>
     pub fn send_single_tab(
         &mut self,
         target_device_id: &str,
@@ -46,16 +42,55 @@ impl FirefoxAccount {
         url: &str,
     ) -> Result<()> {
         let devices = self.get_devices(false)?;
+        let (payload, sent_telemetry) = SendTabPayload::single_tab(title, url);
+        let result = self.send_tab_payload(&devices, target_device_id, &payload);
+        self.telemetry.record_send_result(sent_telemetry, &result);
+        result
+    }
+
+    /// Send a single tab to several devices designated by their device IDs.
+    ///
+    /// Returns the result of sending to each device, in the same order as
+    /// `target_device_ids`, so that failing to send to one device doesn't stop
+    /// us from sending to the others. An authentication error would fail the
+    /// other sends too, so we stop and return it instead. The sends share a
+    /// telemetry flow ID, with a different stream ID for each device.
+    pub fn send_single_tab_to_devices<T: AsRef<str>>(
+        &mut self,
+        target_device_ids: &[T],
+        title: &str,
+        url: &str,
+    ) -> Result<Vec<Result<()>>> {
+        let devices = self.get_devices(false)?;
+        let flow_id = telemetry::SentCommand::for_send_tab().flow_id;
+        let mut results = Vec::with_capacity(target_device_ids.len());
+        for target_device_id in target_device_ids {
+            let (mut payload, mut sent_telemetry) = SendTabPayload::single_tab(title, url);
+            payload.flow_id.clone_from(&flow_id);
+            sent_telemetry.flow_id.clone_from(&flow_id);
+            let result = self.send_tab_payload(&devices, target_device_id.as_ref(), &payload);
+            self.telemetry.record_send_result(sent_telemetry, &result);
+            match result {
+                Err(e) if e.is_auth_error() => return Err(e),
+                result => results.push(result),
+            }
+        }
+        Ok(results)
+    }
+
+    fn send_tab_payload(
+        &self,
+        devices: &[Device],
+        target_device_id: &str,
+        payload: &SendTabPayload,
+    ) -> Result<()> {
         let target = devices
             .iter()
             .find(|d| d.id == target_device_id)
             .ok_or_else(|| Error::UnknownTargetDevice(target_device_id.to_owned()))?;
-        let (payload, sent_telemetry) = SendTabPayload::single_tab(title, url);
         let oldsync_key = self.get_scoped_key(scopes::OLD_SYNC)?;
-        let command_payload = send_tab::build_send_command(oldsync_key, target, &payload)?;
-        self.invoke_command(send_tab::COMMAND_NAME, target, &command_payload, None)?;
-        self.telemetry.record_command_sent(sent_telemetry);
-        Ok(())
+        let command_payload = send_tab::build_send_command(oldsync_key, target, payload)?;
+        self.invoke_command(send_tab::COMMAND_NAME, target, &command_payload, None)
     }
 
     pub(crate) fn handle_send_tab_command(
@@ -82,8 +117,13 @@ impl FirefoxAccount {
                 Ok(IncomingDeviceCommand::TabReceived { sender, payload })
             }
             Err(e) => {
-                // XXX - this seems ripe for telemetry collection!?
-                // It also seems like it might be possible to recover - ie, one
+                self.telemetry
+                    .record_command_failed(telemetry::FailedCommand::received(
+                        telemetry::Command::SendTab,
+                        reason,
+                        &e,
+                    ));
+                // XXX - It seems like it might be possible to recover - ie, one
                 // of the reasons is that there are key mismatches. Doesn't that
                 // mean the "other" key might work?
                 log::warn!("Could not decrypt Send Tab payload. Diagnosing then resetting the Send Tab keys.");
@@ -145,3 +185,142 @@ impl FirefoxAccount {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::device::tests::setup;
+    use crate::internal::http_client::*;
+    use crate::DeviceCapability;
+    use mockall::predicate::always;
+    use mockall::predicate::eq;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use sync15::DeviceType;
+
+    fn make_device(id: &str, available_commands: HashMap<String, String>) -> Device {
+        Device {
+            common: DeviceResponseCommon {
+                id: id.into(),
+                display_name: "".to_string(),
+                device_type: DeviceType::Desktop,
+                push_subscription: None,
+                available_commands,
+                push_endpoint_expired: false,
+            },
+            is_current_device: false,
+            location: DeviceLocation {
+                city: None,
+                country: None,
+                state: None,
+                state_code: None,
+            },
+            last_access_time: None,
+        }
+    }
+
+    #[test]
+    fn test_send_single_tab_to_devices() {
+        let mut fxa = setup();
+        // The target device can use our own send-tab keys.
+        let command_data = fxa
+            .generate_command_data(DeviceCapability::SendTab)
+            .unwrap();
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_devices()
+            .with(always(), always())
+            .times(1)
+            .returning(move |_, _| {
+                Ok(vec![
+                    make_device(
+                        "device1",
+                        HashMap::from([(send_tab::COMMAND_NAME.to_owned(), command_data.clone())]),
+                    ),
+                    // A device that can't receive tabs.
+                    make_device("device2", HashMap::new()),
+                ])
+            });
+        client
+            .expect_invoke_command()
+            .with(
+                always(),
+                eq("refreshtok"),
+                eq(send_tab::COMMAND_NAME),
+                eq("device1"),
+                always(),
+                eq(None),
+            )
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(()));
+        fxa.set_client(Arc::new(client));
+
+        let results = fxa
+            .send_single_tab_to_devices(
+                &["device1", "device2", "device3"],
+                "Example",
+                "https://example.com",
+            )
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::UnsupportedCommand(_))));
+        assert!(matches!(results[2], Err(Error::UnknownTargetDevice(_))));
+    }
+
+    #[test]
+    fn test_send_single_tab_to_devices_stops_on_auth_error() {
+        let mut fxa = setup();
+        let command_data = fxa
+            .generate_command_data(DeviceCapability::SendTab)
+            .unwrap();
+        let mut client = MockFxAClient::new();
+        client
+            .expect_get_devices()
+            .with(always(), always())
+            .times(1)
+            .returning(move |_, _| {
+                Ok(["device1", "device2"]
+                    .into_iter()
+                    .map(|id| {
+                        make_device(
+                            id,
+                            HashMap::from([(
+                                send_tab::COMMAND_NAME.to_owned(),
+                                command_data.clone(),
+                            )]),
+                        )
+                    })
+                    .collect())
+            });
+        // Only the first device should be tried.
+        client
+            .expect_invoke_command()
+            .with(
+                always(),
+                eq("refreshtok"),
+                eq(send_tab::COMMAND_NAME),
+                eq("device1"),
+                always(),
+                eq(None),
+            )
+            .times(1)
+            .returning(|_, _, _, _, _, _| {
+                Err(Error::RemoteError {
+                    code: 401,
+                    errno: 110,
+                    error: "Unauthorized".to_owned(),
+                    message: "Invalid authentication token in request signature".to_owned(),
+                    info: "".to_owned(),
+                })
+            });
+        fxa.set_client(Arc::new(client));
+
+        let result = fxa.send_single_tab_to_devices(
+            &["device1", "device2"],
+            "Example",
+            "https://example.com",
+        );
+        assert!(matches!(result, Err(Error::RemoteError { code: 401, .. })));
+    }
+}
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::{
     internal::{
@@ -105,7 +105,28 @@ impl StateManager {
     }
 
     pub fn set_last_handled_command_index(&mut self, idx: u64) {
-        self.persisted_state.last_handled_command = Some(idx)
+        self.persisted_state.last_handled_command = Some(idx);
+        // Commands up to `idx` are now covered by the index itself.
+        self.persisted_state
+            .commands_handled_via_push
+            .retain(|handled| *handled > idx);
+    }
+
+    /// Remembers that the command at `idx` was handled from a push message,
+    /// without moving the last handled command index past commands we might
+    /// have missed.
+    pub fn mark_command_handled_via_push(&mut self, idx: u64) {
+        if !self.was_command_handled(idx) {
+            self.persisted_state.commands_handled_via_push.insert(idx);
+        }
+    }
+
+    pub fn was_command_handled(&self, idx: u64) -> bool {
+        let handled_via_push = &self.persisted_state.commands_handled_via_push;
+        match self.last_handled_command_index() {
+            Some(last) if idx <= last => true,
+            _ => handled_via_push.contains(&idx),
+        }
     }
 
     pub fn current_device_id(&self) -> Option<&str> {
@@ -191,6 +212,7 @@ impl StateManager {
         self.persisted_state.refresh_token = None;
         self.persisted_state.scoped_keys = HashMap::new();
         self.persisted_state.last_handled_command = None;
+        self.persisted_state.commands_handled_via_push = BTreeSet::new();
         self.persisted_state.commands_data = HashMap::new();
         self.persisted_state.access_token_cache = HashMap::new();
         self.persisted_state.device_capabilities = HashSet::new();
@@ -209,6 +231,7 @@ impl StateManager {
     ///   * `current_device_id`
     ///   * `device_capabilities`
     ///   * `last_handled_command`
+    ///   * `commands_handled_via_push`
     pub fn on_auth_issues(&mut self) {
         self.persisted_state.refresh_token = None;
         self.persisted_state.scoped_keys = HashMap::new();
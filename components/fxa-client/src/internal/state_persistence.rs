@@ -30,7 +30,7 @@
 //! The code that was deleted demonstrates how we can implement the migration
 
 use serde_derive::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use super::{
     config::Config,
@@ -110,6 +110,10 @@ pub(crate) struct StateV2 {
     pub(crate) server_local_device_info: Option<LocalDevice>,
     #[serde(default)]
     pub(crate) logged_out_from_auth_issues: bool,
+    // Indexes of commands after `last_handled_command` that were already
+    // handled from a push message, so polling doesn't return them again.
+    #[serde(default)]
+    pub(crate) commands_handled_via_push: BTreeSet<u64>,
 }
 
 #[cfg(test)]
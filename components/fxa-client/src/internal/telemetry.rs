@@ -2,8 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use super::{commands, FirefoxAccount};
-use crate::Result;
+use super::{commands, util, FirefoxAccount};
+use crate::{Error, Result};
 use serde_derive::*;
 use sync_guid::Guid;
 
@@ -57,6 +57,8 @@ pub struct SentCommand {
     pub command: Command,
     pub flow_id: String,
     pub stream_id: String,
+    /// When the command was sent, in milliseconds since the epoch.
+    pub timestamp: u64,
 }
 
 impl SentCommand {
@@ -73,6 +75,19 @@ impl SentCommand {
             command,
             flow_id: Guid::random().to_string(),
             stream_id: Guid::random().to_string(),
+            timestamp: util::now(),
+        }
+    }
+
+    /// Turns this into the telemetry for a command we failed to send.
+    pub fn failed(self, error: &Error) -> FailedCommand {
+        FailedCommand {
+            command: self.command,
+            flow_id: self.flow_id,
+            stream_id: self.stream_id,
+            reason: None,
+            error: error_category(error),
+            timestamp: util::now(),
         }
     }
 }
@@ -83,6 +98,8 @@ pub struct ReceivedCommand {
     pub flow_id: String,
     pub stream_id: String,
     pub reason: ReceivedReason,
+    /// When the command was received, in milliseconds since the epoch.
+    pub timestamp: u64,
 }
 
 impl ReceivedCommand {
@@ -92,19 +109,111 @@ impl ReceivedCommand {
             flow_id: payload.flow_id.clone(),
             stream_id: payload.stream_id.clone(),
             reason,
+            timestamp: util::now(),
         }
     }
 
     pub fn for_close_tabs(payload: &commands::CloseTabsPayload, reason: ReceivedReason) -> Self {
         Self {
-            command: Command::SendTab,
+            command: Command::CloseTabs,
             flow_id: payload.flow_id.clone(),
             stream_id: payload.stream_id.clone(),
             reason,
+            timestamp: util::now(),
         }
     }
 }
 
+/// A command we failed to send, or received but failed to handle.
+#[derive(Debug, Serialize)]
+pub struct FailedCommand {
+    pub command: Command,
+    /// Empty for received commands we couldn't decrypt.
+    pub flow_id: String,
+    pub stream_id: String,
+    /// Why we received the command. `None` for commands we failed to send.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<ReceivedReason>,
+    /// The kind of error, from [error_category].
+    pub error: String,
+    /// When the command failed, in milliseconds since the epoch.
+    pub timestamp: u64,
+}
+
+impl FailedCommand {
+    pub fn received(command: Command, reason: ReceivedReason, error: &Error) -> Self {
+        Self {
+            command,
+            flow_id: String::new(),
+            stream_id: String::new(),
+            reason: Some(reason),
+            error: error_category(error),
+            timestamp: util::now(),
+        }
+    }
+}
+
+/// Returns a fixed category for an error, which is safe to include in
+/// telemetry. Error messages aren't, because they can include device IDs and
+/// messages from the server.
+fn error_category(error: &Error) -> String {
+    match error {
+        Error::RemoteError { code, errno, .. } => format!("RemoteError:{code}:{errno}"),
+        _ => error_name(error).to_string(),
+    }
+}
+
+/// Returns the name of an error's variant. These names are reported in
+/// telemetry, so they shouldn't change when a variant is renamed.
+fn error_name(error: &Error) -> &'static str {
+    match error {
+        Error::BackoffError(_) => "BackoffError",
+        Error::UnknownOAuthState => "UnknownOAuthState",
+        Error::MultipleScopesRequested => "MultipleScopesRequested",
+        Error::NoCachedToken(_) => "NoCachedToken",
+        Error::NoScopedKey(_) => "NoScopedKey",
+        Error::NoRefreshToken => "NoRefreshToken",
+        Error::NoSessionToken => "NoSessionToken",
+        Error::NoMigrationData => "NoMigrationData",
+        Error::NoCurrentDeviceId => "NoCurrentDeviceId",
+        Error::UnknownTargetDevice(_) => "UnknownTargetDevice",
+        Error::ApiClientError(_) => "ApiClientError",
+        Error::IllegalState(_) => "IllegalState",
+        Error::UnknownCommand(_) => "UnknownCommand",
+        Error::SendTabDiagnosisError(_) => "SendTabDiagnosisError",
+        Error::XorLengthMismatch(..) => "XorLengthMismatch",
+        Error::OriginMismatch(_) => "OriginMismatch",
+        Error::MismatchedKeys => "MismatchedKeys",
+        Error::SyncScopedKeyMissingInServerResponse => "SyncScopedKeyMissingInServerResponse",
+        Error::ScopeNotAllowed(..) => "ScopeNotAllowed",
+        Error::CompressionError(_) => "CompressionError",
+        Error::UnsupportedCompression(_) => "UnsupportedCompression",
+        Error::UnsupportedCommand(_) => "UnsupportedCommand",
+        Error::MissingUrlParameter(_) => "MissingUrlParameter",
+        Error::NullPointer => "NullPointer",
+        Error::InvalidBufferLength(_) => "InvalidBufferLength",
+        Error::AuthCircuitBreakerError => "AuthCircuitBreakerError",
+        Error::RemoteError { .. } => "RemoteError",
+        Error::CryptoError(_) => "CryptoError",
+        Error::EceError(_) => "EceError",
+        Error::HexDecodeError(_) => "HexDecodeError",
+        Error::Base64Decode(_) => "Base64Decode",
+        Error::JsonError(_) => "JsonError",
+        Error::JwCryptoError(_) => "JwCryptoError",
+        Error::UTF8DecodeError(_) => "UTF8DecodeError",
+        Error::RequestError(_) => "RequestError",
+        Error::MalformedUrl(_) => "MalformedUrl",
+        Error::UnexpectedStatus(_) => "UnexpectedStatus",
+        Error::SyncError(_) => "SyncError",
+        Error::HawkError(_) => "HawkError",
+        Error::IntegerConversionError(_) => "IntegerConversionError",
+        Error::CommandNotFound => "CommandNotFound",
+        Error::InvalidPushEvent => "InvalidPushEvent",
+        Error::InvalidStateTransition(_) => "InvalidStateTransition",
+        Error::StateMachineLogicError(_) => "StateMachineLogicError",
+    }
+}
+
 // We have a naive strategy to avoid unbounded memory growth - the intention
 // is that if any platform lets things grow to hit these limits, it's probably
 // never going to consume anything - so it doesn't matter what we discard (ie,
@@ -115,6 +224,7 @@ const MAX_TAB_EVENTS: usize = 200;
 pub struct FxaTelemetry {
     commands_sent: Vec<SentCommand>,
     commands_received: Vec<ReceivedCommand>,
+    commands_failed: Vec<FailedCommand>,
 }
 
 impl FxaTelemetry {
@@ -135,4 +245,95 @@ impl FxaTelemetry {
             self.commands_received.push(recd);
         }
     }
+
+    pub fn record_command_failed(&mut self, failed: FailedCommand) {
+        if self.commands_failed.len() < MAX_TAB_EVENTS {
+            self.commands_failed.push(failed);
+        }
+    }
+
+    /// Records a command we tried to send, depending on whether we managed to.
+    pub fn record_send_result(&mut self, sent: SentCommand, result: &Result<()>) {
+        match result {
+            Ok(()) => self.record_command_sent(sent),
+            Err(e) => self.record_command_failed(sent.failed(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_tabs_telemetry_roundtrip() {
+        let (payload, sent) =
+            commands::CloseTabsPayload::with_urls(vec!["https://www.mozilla.org".into()]);
+        let recd = ReceivedCommand::for_close_tabs(&payload, ReceivedReason::Push);
+        assert_eq!(recd.flow_id, sent.flow_id);
+        assert_eq!(recd.stream_id, sent.stream_id);
+
+        let mut telem = FxaTelemetry::new();
+        telem.record_command_sent(sent);
+        telem.record_command_received(recd);
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&telem).unwrap()).unwrap();
+        assert_eq!(json["commands_sent"][0]["command"], "close_tabs");
+        assert_eq!(json["commands_received"][0]["command"], "close_tabs");
+        assert_eq!(json["commands_received"][0]["reason"], "push");
+        assert_eq!(
+            json["commands_sent"][0]["flow_id"],
+            json["commands_received"][0]["flow_id"]
+        );
+        assert!(json["commands_sent"][0]["timestamp"].as_u64().unwrap() > 0);
+        assert!(json["commands_received"][0]["timestamp"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_failed_commands() {
+        let mut telem = FxaTelemetry::new();
+        let sent = SentCommand::for_send_tab();
+        let flow_id = sent.flow_id.clone();
+        telem.record_send_result(sent, &Err(Error::UnknownTargetDevice("device1".into())));
+        telem.record_command_failed(FailedCommand::received(
+            Command::CloseTabs,
+            ReceivedReason::Poll,
+            &Error::MismatchedKeys,
+        ));
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&telem).unwrap()).unwrap();
+        assert_eq!(json["commands_sent"].as_array().unwrap().len(), 0);
+        assert_eq!(json["commands_failed"][0]["command"], "send_tab");
+        assert_eq!(json["commands_failed"][0]["flow_id"], flow_id);
+        assert!(json["commands_failed"][0].get("reason").is_none());
+        assert_eq!(json["commands_failed"][0]["error"], "UnknownTargetDevice");
+        assert_eq!(json["commands_failed"][1]["command"], "close_tabs");
+        assert_eq!(json["commands_failed"][1]["reason"], "poll");
+        assert_eq!(json["commands_failed"][1]["error"], "MismatchedKeys");
+    }
+
+    #[test]
+    fn test_error_category() {
+        assert_eq!(
+            error_category(&Error::RemoteError {
+                code: 400,
+                errno: 107,
+                error: "Bad Request".into(),
+                message: "Invalid parameter in request body: device1".into(),
+                info: "".into(),
+            }),
+            "RemoteError:400:107"
+        );
+        assert_eq!(
+            error_category(&Error::RequestError(viaduct::Error::NetworkError(
+                "https://accounts.firefox.com".into()
+            ))),
+            "RequestError"
+        );
+        assert_eq!(error_category(&Error::CommandNotFound), "CommandNotFound");
+        assert_eq!(
+            error_category(&Error::ScopeNotAllowed("client".into(), "scope".into())),
+            "ScopeNotAllowed"
+        );
+    }
 }
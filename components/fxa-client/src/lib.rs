@@ -20,6 +20,10 @@
 //!     * For subsequent startups the object can be persisted using the
 //!       [`to_json`](FirefoxAccount::to_json) method and re-created by
 //!       calling [`FirefoxAccount::from_json`].
+//!     * Applications which support more than one account in the same process (for
+//!       example, one per profile) can create a [`FirefoxAccount`] for each of them.
+//!       Instances don't share any state, so each one must be persisted separately, and
+//!       stores synced on behalf of an account should be registered using that account's id.
 //!
 //! * When the user wants to sign in to your application, direct them through
 //!   a web-based OAuth flow using [`begin_oauth_flow`](FirefoxAccount::begin_oauth_flow)
@@ -60,7 +64,7 @@ use parking_lot::Mutex;
 pub use profile::Profile;
 pub use push::{
     AccountEvent, CloseTabsPayload, DevicePushSubscription, IncomingDeviceCommand, SendTabPayload,
-    TabHistoryEntry,
+    SendTabResult, TabHistoryEntry,
 };
 pub use token::{AccessTokenInfo, AuthorizationParameters, ScopedKey};
 
@@ -238,6 +242,9 @@ impl FxaConfig {
 
 uniffi::include_scaffolding!("fxa_client");
 
+pub const UNIFFI_NAMESPACE: &str = "fxa_client";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg(test)]
 mod tests {
     use super::*;
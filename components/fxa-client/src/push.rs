@@ -2,10 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use error_support::handle_error;
+use error_support::{convert_log_report_error, handle_error};
 use serde::{Deserialize, Serialize};
 
-use crate::{internal, ApiResult, Device, Error, FirefoxAccount, LocalDevice};
+use crate::{internal, ApiResult, Device, Error, FirefoxAccount, FxaError, LocalDevice};
 
 impl FirefoxAccount {
     /// Set or update a push subscription endpoint for this device.
@@ -18,6 +18,9 @@ impl FirefoxAccount {
     /// endpoint, it should decrypt the payload and pass it to the [`handle_push_message`](
     /// FirefoxAccount::handle_push_message) method for processing.
     ///
+    /// If the device record already has this subscription, and the server hasn't marked it
+    /// as expired, this doesn't hit the server.
+    ///
     /// # Arguments
     ///
     ///    - `subscription` - the [`DevicePushSubscription`] details to register with the server.
@@ -36,6 +39,25 @@ impl FirefoxAccount {
             .set_push_subscription(subscription.into())
     }
 
+    /// Check whether the push subscription for this device has expired.
+    ///
+    /// **💾 This method alters the persisted account state.**
+    ///
+    /// The FxA server marks a device's push subscription as expired when it can no longer
+    /// deliver messages to it, e.g. because the push service rotated the endpoint. If this
+    /// returns `true`, the application should get a new subscription from its push service
+    /// and pass it to [`set_push_subscription`](FirefoxAccount::set_push_subscription),
+    /// otherwise it will stop receiving account events and device commands via push.
+    ///
+    /// # Notes
+    ///
+    ///    - This uses the same cached list of devices as [`get_devices`](
+    ///      FirefoxAccount::get_devices), so it only hits the server if that list is stale.
+    #[handle_error(Error)]
+    pub fn is_push_subscription_expired(&self) -> ApiResult<bool> {
+        self.internal.lock().is_push_subscription_expired()
+    }
+
     /// Process and respond to a server-delivered account update message
     ///
     /// **💾 This method alters the persisted account state.**
@@ -66,6 +88,7 @@ impl FirefoxAccount {
     ///    - Device commands are typically delivered via push message and the [`CommandReceived`](
     ///      AccountEvent::CommandReceived) event. Polling should only be used as a backup delivery
     ///      mechanism, f the application has reason to believe that push messages may have been missed.
+    ///    - Commands which were already delivered via push message are not returned again.
     ///    - Device commands functionality is only available to applications that have been
     ///      granted the `https://identity.mozilla.com/apps/oldsync` scope.
     #[handle_error(Error)]
@@ -78,6 +101,19 @@ impl FirefoxAccount {
             .collect::<Result<_, _>>()
     }
 
+    /// Get the number of seconds to wait before calling [`poll_device_commands`](
+    /// FirefoxAccount::poll_device_commands) again.
+    ///
+    /// Applications that can't receive push messages can use this to schedule polling for
+    /// device commands. The interval is shorter for a while after commands have been received,
+    /// since they tend to arrive in bursts, and respects any backoff requested by the server.
+    pub fn get_recommended_poll_interval(&self) -> u64 {
+        self.internal
+            .lock()
+            .get_recommended_poll_interval()
+            .as_secs()
+    }
+
     /// Use device commands to send a single tab to another device.
     ///
     /// **💾 This method alters the persisted account state.**
@@ -101,6 +137,35 @@ impl FirefoxAccount {
             .send_single_tab(target_device_id, title, url)
     }
 
+    /// Use device commands to send a single tab to several other devices.
+    ///
+    /// **💾 This method alters the persisted account state.**
+    ///
+    /// This is like calling [`send_single_tab`](FirefoxAccount::send_single_tab) for each
+    /// device, except that failing to send the tab to one device doesn't stop it from being
+    /// sent to the others. It returns a [`SendTabResult`] for each of the given device ids,
+    /// in the same order. If there's an authentication error, no more devices are tried
+    /// and it's thrown instead.
+    #[handle_error(Error)]
+    pub fn send_single_tab_to_devices(
+        &self,
+        target_device_ids: Vec<String>,
+        title: &str,
+        url: &str,
+    ) -> ApiResult<Vec<SendTabResult>> {
+        Ok(self
+            .internal
+            .lock()
+            .send_single_tab_to_devices(&target_device_ids, title, url)?
+            .into_iter()
+            .zip(target_device_ids)
+            .map(|(result, device_id)| SendTabResult {
+                device_id,
+                error: result.err().map(convert_log_report_error),
+            })
+            .collect())
+    }
+
     /// Use device commands to close one or more tabs on another device.
     ///
     /// **💾 This method alters the persisted account state.**
@@ -225,10 +290,27 @@ pub struct SendTabPayload {
     pub stream_id: String,
 }
 
+/// The result of sending a tab to one of the devices passed to
+/// [`send_single_tab_to_devices`](FirefoxAccount::send_single_tab_to_devices).
+#[derive(Debug)]
+pub struct SendTabResult {
+    pub device_id: String,
+    /// `None` if the tab was sent, or the reason it wasn't.
+    pub error: Option<FxaError>,
+}
+
 /// The payload sent when invoking a "close tabs" command.
 #[derive(Debug)]
 pub struct CloseTabsPayload {
     pub urls: Vec<String>,
+    /// A unique identifier to be included in close-tabs metrics.
+    ///
+    /// The application should treat this as opaque.
+    pub flow_id: String,
+    /// A unique identifier to be included in close-tabs metrics.
+    ///
+    /// The application should treat this as opaque.
+    pub stream_id: String,
 }
 
 /// An individual entry in the navigation history of a sent tab.
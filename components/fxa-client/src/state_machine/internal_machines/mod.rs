@@ -197,8 +197,11 @@ impl<'a> CallErrorHandler<'a> {
         // Report the error and convert it to `FxaError` which makes it easier to handle.
         // For example, multiple `Error` variants map to `FxaError::Authentication`.
         log::warn!("handling error: {e}");
+        // Server errors are reported to the app as network errors, so that it retries later.
+        // Retrying them right away would only add load to a server that's already struggling.
+        let is_server_error = e.is_server_error();
         match convert_log_report_error(e) {
-            FxaError::Network => {
+            FxaError::Network if !is_server_error => {
                 if self.network_retries < NETWORK_RETRY_LIMIT {
                     self.network_retries += 1;
                     CallResult::Retry
@@ -289,3 +292,51 @@ impl<T: InternalStateMachine> StateMachineTester<T> {
             })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::internal::config::Config;
+
+    fn remote_error(code: u64) -> Error {
+        Error::RemoteError {
+            code,
+            errno: 999,
+            error: "error".to_string(),
+            message: "message".to_string(),
+            info: "info".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_network_errors_are_retried() {
+        let mut account =
+            FirefoxAccount::with_config(Config::stable_dev("12345678", "https://foo.bar"));
+        let state = State::GetAuthState;
+        let mut handler = CallErrorHandler::new(&state);
+        for _ in 0..NETWORK_RETRY_LIMIT {
+            let e = Error::RequestError(viaduct::Error::NetworkError("offline".to_string()));
+            assert!(matches!(
+                handler.handle_error(e, &mut account),
+                CallResult::Retry
+            ));
+        }
+        let e = Error::RequestError(viaduct::Error::NetworkError("offline".to_string()));
+        assert!(matches!(
+            handler.handle_error(e, &mut account),
+            CallResult::Finished(Event::CallError)
+        ));
+    }
+
+    #[test]
+    fn test_server_errors_are_not_retried() {
+        let mut account =
+            FirefoxAccount::with_config(Config::stable_dev("12345678", "https://foo.bar"));
+        let state = State::GetAuthState;
+        let mut handler = CallErrorHandler::new(&state);
+        assert!(matches!(
+            handler.handle_error(remote_error(503), &mut account),
+            CallResult::Finished(Event::CallError)
+        ));
+    }
+}
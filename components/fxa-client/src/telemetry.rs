@@ -5,14 +5,14 @@
 //! # Telemetry Methods
 //!
 //! This component does not currently submit telemetry via Glean, but it *does* gather
-//! a small amount of telemetry about send-tab that the application may submit on its
+//! a small amount of telemetry about send-tab and close-tabs that the application may submit on its
 //! behalf.
 
 use crate::{ApiResult, Error, FirefoxAccount};
 use error_support::handle_error;
 
 impl FirefoxAccount {
-    /// Collect and return telemetry about send-tab attempts.
+    /// Collect and return telemetry about send-tab and close-tabs attempts.
     ///
     /// Applications that register the [`SendTab`](DeviceCapability::SendTab) capability
     /// should also arrange to submit "sync ping" telemetry. Calling this method will
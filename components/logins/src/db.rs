@@ -22,6 +22,7 @@
 ///     server.
 ///   - After we sync, we move all records from loginsL to loginsM, overwriting any previous data.
 ///     loginsL will be an empty table after this.  See mark_as_synchronized() for the details.
+use crate::decrypt_cache::{DecryptCache, LoginsCacheMetrics};
 use crate::encryption::EncryptorDecryptor;
 use crate::error::*;
 use crate::login::*;
@@ -36,6 +37,7 @@ use rusqlite::{
     Connection,
 };
 use sql_support::ConnExt;
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
@@ -46,6 +48,7 @@ use url::{Host, Url};
 pub struct LoginDb {
     pub db: Connection,
     interrupt_handle: Arc<SqlInterruptHandle>,
+    decrypt_cache: RefCell<DecryptCache>,
 }
 
 impl LoginDb {
@@ -64,6 +67,7 @@ impl LoginDb {
         let mut logins = Self {
             interrupt_handle: Arc::new(SqlInterruptHandle::new(&db)),
             db,
+            decrypt_cache: RefCell::new(DecryptCache::default()),
         };
         let tx = logins.db.transaction()?;
         schema::init(&tx)?;
@@ -87,6 +91,30 @@ impl LoginDb {
     pub fn begin_interrupt_scope(&self) -> Result<SqlInterruptScope> {
         Ok(self.interrupt_handle.begin_interrupt_scope()?)
     }
+
+    /// Sets the maximum number of decrypted logins to keep in memory. Zero
+    /// (the default) disables the cache.
+    pub fn set_decrypt_cache_capacity(&self, capacity: u32) {
+        self.decrypt_cache.borrow_mut().set_capacity(capacity)
+    }
+
+    pub fn decrypt_cache_metrics(&self) -> LoginsCacheMetrics {
+        self.decrypt_cache.borrow().metrics()
+    }
+
+    pub(crate) fn invalidate_decrypt_cache(&self) {
+        self.decrypt_cache.borrow_mut().invalidate_all()
+    }
+
+    fn decrypt_fields_cached(
+        &self,
+        login: &EncryptedLogin,
+        encdec: &EncryptorDecryptor,
+    ) -> Result<SecureLoginFields> {
+        self.decrypt_cache
+            .borrow_mut()
+            .decrypt_fields(login, encdec)
+    }
 }
 
 impl ConnExt for LoginDb {
@@ -218,7 +246,13 @@ impl LoginDb {
         let logins = self
             .get_by_entry_target(&look)?
             .into_iter()
-            .map(|enc_login| enc_login.decrypt(encdec))
+            .map(|enc_login| {
+                Ok(Login {
+                    sec_fields: self.decrypt_fields_cached(&enc_login, encdec)?,
+                    record: enc_login.record,
+                    fields: enc_login.fields,
+                })
+            })
             .collect::<Result<Vec<Login>>>()?;
         Ok(logins
             // First, try to match the username
@@ -414,12 +448,12 @@ impl LoginDb {
             Some(e) => e,
             None => return Err(Error::NoSuchRecord(sguid.to_owned())),
         };
-        let time_password_changed =
-            if existing.decrypt_fields(encdec)?.password == entry.sec_fields.password {
-                existing.record.time_password_changed
-            } else {
-                now_ms
-            };
+        let existing_sec_fields = self.decrypt_fields_cached(&existing, encdec)?;
+        let time_password_changed = if existing_sec_fields.password == entry.sec_fields.password {
+            existing.record.time_password_changed
+        } else {
+            now_ms
+        };
 
         // Make the final object here - every column will be updated.
         let result = EncryptedLogin {
@@ -436,6 +470,7 @@ impl LoginDb {
 
         self.update_existing_login(&result)?;
         tx.commit()?;
+        self.decrypt_cache.borrow_mut().invalidate(sguid);
         Ok(result)
     }
 
@@ -492,7 +527,7 @@ impl LoginDb {
     ) -> Result<Option<Guid>> {
         for possible in self.get_by_entry_target(entry)? {
             if possible.guid() != *guid {
-                let pos_sec_fields = possible.decrypt_fields(encdec)?;
+                let pos_sec_fields = self.decrypt_fields_cached(&possible, encdec)?;
                 if pos_sec_fields.username == entry.sec_fields.username {
                     return Ok(Some(possible.guid()));
                 }
@@ -626,6 +661,7 @@ impl LoginDb {
             changed = SyncStatus::Changed as u8),
             named_params! { ":now_ms": now_ms, ":guid": id })?;
         tx.commit()?;
+        self.decrypt_cache.borrow_mut().invalidate(id);
         Ok(exists)
     }
 
@@ -673,6 +709,7 @@ impl LoginDb {
             "DELETE FROM loginsSyncMeta",
         ])?;
         tx.commit()?;
+        self.invalidate_decrypt_cache();
         Ok(())
     }
 }
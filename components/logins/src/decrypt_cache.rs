@@ -0,0 +1,264 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// An optional in-memory cache of decrypted `SecureLoginFields`.
+//
+// Autofill ends up looking at the same handful of logins many times during a
+// browsing session, and every lookup means decrypting every candidate record.
+// This cache lets us skip that work.
+//
+// Entries are keyed by the login's GUID, but we also remember the ciphertext
+// we decrypted - every write re-encrypts the secure fields with a fresh IV,
+// so a cached entry is only ever used if the stored ciphertext is identical.
+// That means a missed invalidation can cost us memory but never correctness.
+// We still explicitly invalidate on writes and after syncs so we don't hold
+// on to plaintext for records which no longer exist.
+//
+// The app passes the key with every call, so we remember a digest of the key
+// which populated the cache and throw everything away if a different key shows
+// up. Hashing the key is much cheaper than the decryption we're avoiding.
+//
+// The cache is disabled (ie, has a capacity of zero) by default.
+
+use crate::encryption::EncryptorDecryptor;
+use crate::error::*;
+use crate::login::{EncryptedLogin, SecureLoginFields};
+use std::collections::{HashMap, VecDeque};
+
+/// Metrics about the decrypted login cache, which apps can use to tune its
+/// capacity.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoginsCacheMetrics {
+    /// The maximum number of entries the cache will hold. Zero means the cache
+    /// is disabled.
+    pub capacity: u32,
+    /// The number of entries currently in the cache.
+    pub size: u32,
+    /// The number of lookups satisfied by the cache.
+    pub hits: u64,
+    /// The number of lookups which needed to decrypt the record.
+    pub misses: u64,
+    /// The number of entries dropped because the cache was full.
+    pub evictions: u64,
+    /// The number of entries dropped because the record was written, synced,
+    /// or because a different encryption key was used.
+    pub invalidations: u64,
+}
+
+struct CacheEntry {
+    ciphertext: String,
+    sec_fields: SecureLoginFields,
+}
+
+#[derive(Default)]
+pub(crate) struct DecryptCache {
+    entries: HashMap<String, CacheEntry>,
+    // Insertion order, so we can evict the oldest entry when full.
+    order: VecDeque<String>,
+    key_digest: Option<Vec<u8>>,
+    metrics: LoginsCacheMetrics,
+}
+
+impl DecryptCache {
+    pub(crate) fn set_capacity(&mut self, capacity: u32) {
+        self.metrics.capacity = capacity;
+        while self.entries.len() > capacity as usize {
+            self.evict_oldest();
+        }
+        if capacity == 0 {
+            self.key_digest = None;
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> LoginsCacheMetrics {
+        LoginsCacheMetrics {
+            size: self.entries.len() as u32,
+            ..self.metrics
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.metrics.capacity > 0
+    }
+
+    /// Returns the decrypted secure fields for a login, using the cached copy
+    /// if we have one for the exact same ciphertext.
+    pub(crate) fn decrypt_fields(
+        &mut self,
+        login: &EncryptedLogin,
+        encdec: &EncryptorDecryptor,
+    ) -> Result<SecureLoginFields> {
+        if !self.is_enabled() {
+            return login.decrypt_fields(encdec);
+        }
+        self.check_key(encdec)?;
+        if let Some(entry) = self.entries.get(&login.record.id) {
+            if entry.ciphertext == login.sec_fields {
+                self.metrics.hits += 1;
+                return Ok(entry.sec_fields.clone());
+            }
+        }
+        self.metrics.misses += 1;
+        let sec_fields = login.decrypt_fields(encdec)?;
+        self.insert(login, sec_fields.clone());
+        Ok(sec_fields)
+    }
+
+    /// Drops the cached entry for a single login.
+    pub(crate) fn invalidate(&mut self, guid: &str) {
+        if self.entries.remove(guid).is_some() {
+            self.order.retain(|g| g != guid);
+            self.metrics.invalidations += 1;
+        }
+    }
+
+    /// Drops every cached entry.
+    pub(crate) fn invalidate_all(&mut self) {
+        self.metrics.invalidations += self.entries.len() as u64;
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    // Makes sure the cache was populated with the same key as `encdec`,
+    // clearing it if not.
+    fn check_key(&mut self, encdec: &EncryptorDecryptor) -> Result<()> {
+        let key_digest = encdec.key_digest()?;
+        if self.key_digest.as_ref() != Some(&key_digest) {
+            if self.key_digest.is_some() {
+                self.invalidate_all();
+            }
+            self.key_digest = Some(key_digest);
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, login: &EncryptedLogin, sec_fields: SecureLoginFields) {
+        let guid = login.record.id.clone();
+        if self.entries.contains_key(&guid) {
+            self.order.retain(|g| *g != guid);
+        } else {
+            while self.entries.len() >= self.metrics.capacity as usize {
+                self.evict_oldest();
+            }
+        }
+        self.order.push_back(guid.clone());
+        self.entries.insert(
+            guid,
+            CacheEntry {
+                ciphertext: login.sec_fields.clone(),
+                sec_fields,
+            },
+        );
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some(guid) = self.order.pop_front() {
+            self.entries.remove(&guid);
+            self.metrics.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encryption::test_utils::{TEST_ENCRYPTION_KEY, TEST_ENCRYPTOR};
+    use crate::login::{LoginFields, RecordFields};
+
+    fn make_login(id: &str, password: &str) -> EncryptedLogin {
+        EncryptedLogin {
+            record: RecordFields {
+                id: id.to_string(),
+                ..Default::default()
+            },
+            fields: LoginFields {
+                origin: "https://www.example.com".into(),
+                http_realm: Some("realm".into()),
+                ..Default::default()
+            },
+            sec_fields: SecureLoginFields {
+                username: "user".into(),
+                password: password.into(),
+            }
+            .encrypt(&TEST_ENCRYPTOR)
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let mut cache = DecryptCache::default();
+        let login = make_login("aaaaaaaaaaaa", "password");
+        cache.decrypt_fields(&login, &TEST_ENCRYPTOR).unwrap();
+        cache.decrypt_fields(&login, &TEST_ENCRYPTOR).unwrap();
+        assert_eq!(cache.metrics(), LoginsCacheMetrics::default());
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let mut cache = DecryptCache::default();
+        cache.set_capacity(10);
+        let login = make_login("aaaaaaaaaaaa", "password");
+        for _ in 0..3 {
+            let fields = cache.decrypt_fields(&login, &TEST_ENCRYPTOR).unwrap();
+            assert_eq!(fields.password, "password");
+        }
+        let metrics = cache.metrics();
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.size, 1);
+
+        // A new ciphertext for the same guid must not use the stale entry.
+        let updated = make_login("aaaaaaaaaaaa", "new-password");
+        let fields = cache.decrypt_fields(&updated, &TEST_ENCRYPTOR).unwrap();
+        assert_eq!(fields.password, "new-password");
+        assert_eq!(cache.metrics().misses, 2);
+        assert_eq!(cache.metrics().size, 1);
+
+        // Every store call creates its own `EncryptorDecryptor`, so entries
+        // must still be used with another instance for the same key.
+        let same_key = EncryptorDecryptor::new(&TEST_ENCRYPTION_KEY).unwrap();
+        cache.decrypt_fields(&updated, &same_key).unwrap();
+        assert_eq!(cache.metrics().hits, 3);
+        assert_eq!(cache.metrics().invalidations, 0);
+    }
+
+    #[test]
+    fn test_eviction_and_invalidation() {
+        let mut cache = DecryptCache::default();
+        cache.set_capacity(2);
+        let a = make_login("aaaaaaaaaaaa", "a");
+        let b = make_login("bbbbbbbbbbbb", "b");
+        let c = make_login("cccccccccccc", "c");
+        cache.decrypt_fields(&a, &TEST_ENCRYPTOR).unwrap();
+        cache.decrypt_fields(&b, &TEST_ENCRYPTOR).unwrap();
+        cache.decrypt_fields(&c, &TEST_ENCRYPTOR).unwrap();
+        assert_eq!(cache.metrics().evictions, 1);
+        assert_eq!(cache.metrics().size, 2);
+
+        cache.invalidate("bbbbbbbbbbbb");
+        assert_eq!(cache.metrics().invalidations, 1);
+        assert_eq!(cache.metrics().size, 1);
+
+        cache.invalidate_all();
+        assert_eq!(cache.metrics().invalidations, 2);
+        assert_eq!(cache.metrics().size, 0);
+    }
+
+    #[test]
+    fn test_key_change_clears_cache() {
+        let mut cache = DecryptCache::default();
+        cache.set_capacity(10);
+        let login = make_login("aaaaaaaaaaaa", "password");
+        cache.decrypt_fields(&login, &TEST_ENCRYPTOR).unwrap();
+
+        let other_key = EncryptorDecryptor::create_key().unwrap();
+        assert_ne!(other_key, *TEST_ENCRYPTION_KEY);
+        let other = EncryptorDecryptor::new(&other_key).unwrap();
+        // The cached plaintext must not be handed out for the wrong key.
+        assert!(cache.decrypt_fields(&login, &other).is_err());
+        assert_eq!(cache.metrics().hits, 0);
+        assert_eq!(cache.metrics().invalidations, 1);
+    }
+}
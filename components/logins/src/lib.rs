@@ -10,6 +10,7 @@ mod error;
 mod login;
 
 mod db;
+mod decrypt_cache;
 pub mod encryption;
 mod schema;
 mod store;
@@ -18,7 +19,11 @@ mod util;
 
 uniffi::include_scaffolding!("logins");
 
+pub const UNIFFI_NAMESPACE: &str = "logins";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub use crate::db::LoginDb;
+pub use crate::decrypt_cache::LoginsCacheMetrics;
 use crate::encryption::{check_canary, create_canary, create_key};
 pub use crate::error::*;
 pub use crate::login::*;
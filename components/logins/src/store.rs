@@ -2,35 +2,48 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 use crate::db::LoginDb;
+use crate::decrypt_cache::LoginsCacheMetrics;
 use crate::encryption::EncryptorDecryptor;
 use crate::error::*;
 use crate::login::{EncryptedLogin, Login, LoginEntry};
 use crate::LoginsSyncEngine;
 use parking_lot::Mutex;
 use std::path::Path;
-use std::sync::{Arc, Weak};
-use sync15::engine::{EngineSyncAssociation, SyncEngine, SyncEngineId};
+use std::sync::Arc;
+use sync15::engine::{EngineRegistry, EngineSyncAssociation, SyncEngine, SyncEngineId};
 
-// Our "sync manager" will use whatever is stashed here.
+// Our "sync manager" will use whatever is registered here.
 lazy_static::lazy_static! {
-    // Mutex: just taken long enough to update the inner stuff - needed
-    //        to wrap the RefCell as they aren't `Sync`
-    static ref STORE_FOR_MANAGER: Mutex<Weak<LoginStore>> = Mutex::new(Weak::new());
+    static ref STORES_FOR_MANAGER: EngineRegistry<LoginStore> = EngineRegistry::new();
 }
 
 /// Called by the sync manager to get a sync engine via the store previously
 /// registered with the sync manager.
 pub fn get_registered_sync_engine(engine_id: &SyncEngineId) -> Option<Box<dyn SyncEngine>> {
-    let weak = STORE_FOR_MANAGER.lock();
-    match weak.upgrade() {
-        None => None,
-        Some(store) => match create_sync_engine(store, engine_id) {
-            Ok(engine) => Some(engine),
-            Err(e) => {
-                report_error!("logins-sync-engine-create-error", "{e}");
-                None
-            }
-        },
+    let store = STORES_FOR_MANAGER.get()?;
+    create_registered_sync_engine(store, engine_id)
+}
+
+/// Called by the sync manager to get a sync engine via the store previously
+/// registered with the sync manager for the specified account.
+pub fn get_registered_sync_engine_for_account(
+    account_id: &str,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn SyncEngine>> {
+    let store = STORES_FOR_MANAGER.get_for_account(account_id)?;
+    create_registered_sync_engine(store, engine_id)
+}
+
+fn create_registered_sync_engine(
+    store: Arc<LoginStore>,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn SyncEngine>> {
+    match create_sync_engine(store, engine_id) {
+        Ok(engine) => Some(engine),
+        Err(e) => {
+            report_error!("logins-sync-engine-create-error", "{e}");
+            None
+        }
     }
 }
 
@@ -102,6 +115,16 @@ impl LoginStore {
         self.db.lock().delete(id)
     }
 
+    /// Sets the maximum number of decrypted logins the store keeps in memory
+    /// to speed up repeated lookups. Zero (the default) disables the cache.
+    pub fn set_decrypt_cache_capacity(&self, capacity: u32) {
+        self.db.lock().set_decrypt_cache_capacity(capacity)
+    }
+
+    pub fn get_decrypt_cache_metrics(&self) -> LoginsCacheMetrics {
+        self.db.lock().decrypt_cache_metrics()
+    }
+
     #[handle_error(Error)]
     pub fn wipe_local(&self) -> ApiResult<()> {
         self.db.lock().wipe_local()?;
@@ -142,8 +165,14 @@ impl LoginStore {
     // `register_with_sync_manager()` is logically what's happening so that's
     // the name it gets.
     pub fn register_with_sync_manager(self: Arc<Self>) {
-        let mut state = STORE_FOR_MANAGER.lock();
-        *state = Arc::downgrade(&self);
+        STORES_FOR_MANAGER.register(&self);
+    }
+
+    // Like `register_with_sync_manager()`, but only makes this instance
+    // available to a sync manager created for the same account, so stores
+    // belonging to different accounts don't replace each other.
+    pub fn register_with_sync_manager_for_account(self: Arc<Self>, account_id: String) {
+        STORES_FOR_MANAGER.register_for_account(account_id, &self);
     }
 
     // this isn't exposed by uniffi - currently the
@@ -297,6 +326,61 @@ mod test {
         assert_eq!(b_after_update.record.times_used, 2);
     }
 
+    #[test]
+    fn test_decrypt_cache() {
+        let store = LoginStore::new_in_memory().unwrap();
+        store.set_decrypt_cache_capacity(10);
+        let entry = LoginEntry {
+            fields: LoginFields {
+                origin: "https://www.example.com".into(),
+                form_action_origin: Some("https://www.example.com".into()),
+                ..Default::default()
+            },
+            sec_fields: SecureLoginFields {
+                username: "user".into(),
+                password: "password".into(),
+            },
+        };
+        let id = store
+            .add(entry.clone(), &TEST_ENCRYPTION_KEY)
+            .unwrap()
+            .record
+            .id;
+
+        for _ in 0..3 {
+            let found = store
+                .find_login_to_update(entry.clone(), &TEST_ENCRYPTION_KEY)
+                .unwrap()
+                .expect("should find the login");
+            assert_eq!(found.record.id, id);
+        }
+        let metrics = store.get_decrypt_cache_metrics();
+        assert_eq!(metrics.capacity, 10);
+        assert_eq!(metrics.size, 1);
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hits, 2);
+
+        // Writing the login invalidates the cached entry, and we must see the
+        // new password.
+        let updated = LoginEntry {
+            sec_fields: SecureLoginFields {
+                username: "user".into(),
+                password: "new-password".into(),
+            },
+            ..entry.clone()
+        };
+        store.update(&id, updated, &TEST_ENCRYPTION_KEY).unwrap();
+        let found = store
+            .find_login_to_update(entry, &TEST_ENCRYPTION_KEY)
+            .unwrap()
+            .expect("should find the login");
+        assert_eq!(found.sec_fields.password, "new-password");
+        assert_eq!(store.get_decrypt_cache_metrics().invalidations, 1);
+
+        store.wipe_local().unwrap();
+        assert_eq!(store.get_decrypt_cache_metrics().size, 0);
+    }
+
     #[test]
     fn test_sync_manager_registration() {
         let store = Arc::new(LoginStore::new_in_memory().unwrap());
@@ -305,7 +389,7 @@ mod test {
         Arc::clone(&store).register_with_sync_manager();
         assert_eq!(Arc::strong_count(&store), 1);
         assert_eq!(Arc::weak_count(&store), 1);
-        let registered = STORE_FOR_MANAGER.lock().upgrade().expect("should upgrade");
+        let registered = STORES_FOR_MANAGER.get().expect("should upgrade");
         assert!(Arc::ptr_eq(&store, &registered));
         drop(registered);
         // should be no new references
@@ -313,7 +397,34 @@ mod test {
         assert_eq!(Arc::weak_count(&store), 1);
         // dropping the registered object should drop the registration.
         drop(store);
-        assert!(STORE_FOR_MANAGER.lock().upgrade().is_none());
+        assert!(STORES_FOR_MANAGER.get().is_none());
+    }
+
+    #[test]
+    fn test_sync_manager_registration_for_account() {
+        let store1 = Arc::new(LoginStore::new_in_memory().unwrap());
+        let store2 = Arc::new(LoginStore::new_in_memory().unwrap());
+        Arc::clone(&store1).register_with_sync_manager_for_account("account-1".to_string());
+        Arc::clone(&store2).register_with_sync_manager_for_account("account-2".to_string());
+        let registered = STORES_FOR_MANAGER
+            .get_for_account("account-1")
+            .expect("should upgrade");
+        assert!(Arc::ptr_eq(&store1, &registered));
+        drop(registered);
+        assert!(
+            get_registered_sync_engine_for_account("account-2", &SyncEngineId::Passwords).is_some()
+        );
+        assert!(
+            get_registered_sync_engine_for_account("account-3", &SyncEngineId::Passwords).is_none()
+        );
+        // dropping one account's store shouldn't impact the other.
+        drop(store1);
+        assert!(
+            get_registered_sync_engine_for_account("account-1", &SyncEngineId::Passwords).is_none()
+        );
+        assert!(
+            get_registered_sync_engine_for_account("account-2", &SyncEngineId::Passwords).is_some()
+        );
     }
 }
 
@@ -13,7 +13,7 @@ use crate::schema;
 use crate::util;
 use crate::LoginDb;
 use crate::LoginStore;
-use interrupt_support::SqlInterruptScope;
+use interrupt_support::{SqlInterruptHandle, SqlInterruptScope};
 use rusqlite::named_params;
 use sql_support::ConnExt;
 use std::cell::RefCell;
@@ -129,6 +129,8 @@ impl LoginsSyncEngine {
         let tx = db.unchecked_transaction()?;
         plan.execute(&tx, &self.scope)?;
         tx.commit()?;
+        // Incoming changes may have replaced any of our local records.
+        db.invalidate_decrypt_cache();
         Ok(())
     }
 
@@ -438,6 +440,10 @@ impl SyncEngine for LoginsSyncEngine {
         "passwords".into()
     }
 
+    fn interrupt_handle(&self) -> Option<Arc<SqlInterruptHandle>> {
+        Some(self.store.db.lock().new_interrupt_handle())
+    }
+
     fn set_local_encryption_key(&mut self, key: &str) -> anyhow::Result<()> {
         self.encdec = Some(EncryptorDecryptor::new(key)?);
         Ok(())
@@ -502,6 +508,14 @@ impl SyncEngine for LoginsSyncEngine {
         self.do_reset(assoc)?;
         Ok(())
     }
+
+    // Called when another device sends us a `wipeEngine` command for
+    // passwords, eg, because this device was lost. This deletes all local
+    // logins and their sync metadata, so the next sync starts from scratch.
+    fn wipe(&self) -> anyhow::Result<()> {
+        self.store.db.lock().wipe_local()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1056,6 +1070,26 @@ mod tests {
         assert_eq!(count(&engine, "LoginsM"), 1);
     }
 
+    #[test]
+    fn test_wipe() {
+        let store = LoginStore::new_in_memory().unwrap();
+        let engine = LoginsSyncEngine::new(Arc::new(store)).unwrap();
+        {
+            let db = engine.store.db.lock();
+            insert_login(&db, "dummy_000001", None, Some("mirror"));
+            insert_login(&db, "dummy_000002", Some("local"), None);
+            engine.set_last_sync(&db, ServerTimestamp(1000)).unwrap();
+        }
+        assert_eq!(count(&engine, "LoginsL"), 1);
+        assert_eq!(count(&engine, "LoginsM"), 1);
+
+        engine.wipe().unwrap();
+        assert_eq!(count(&engine, "LoginsL"), 0);
+        assert_eq!(count(&engine, "LoginsM"), 0);
+        let db = engine.store.db.lock();
+        assert_eq!(engine.get_last_sync(&db).unwrap(), None);
+    }
+
     #[test]
     fn test_incoming_non_mirror_tombstone_local_newer() {
         do_test_incoming_with_local_unmirrored_tombstone(true);
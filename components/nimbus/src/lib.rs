@@ -11,6 +11,7 @@ mod json;
 mod sampling;
 mod strings;
 mod targeting;
+mod validation;
 
 pub mod error;
 pub mod metrics;
@@ -23,6 +24,7 @@ pub use error::{NimbusError, Result};
 pub use evaluator::evaluate_enrollment;
 pub use schema::*;
 pub use targeting::NimbusTargetingHelper;
+pub use validation::{validate_experiment, ValidationIssue};
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "stateful")] {
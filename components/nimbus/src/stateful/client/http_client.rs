@@ -2,29 +2,119 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-//! This is a simple HTTP client that uses viaduct to retrieve experiment data from the server.
-//! Currently configured to use Kinto and the old schema, although that would change once we start
-//! working on the real Nimbus schema.
+//! Fetches experiments from Remote Settings using the `remote_settings` client.
 //!
-//! In the future we might replace this with a more fully-feature Remote Settings client, such as:
+//! The client honours the server's `Backoff` and `Retry-After` headers, so while
+//! the server has asked us to back off, `fetch_experiments` fails with a backoff
+//! error rather than making a request. Apps are expected to retry on their next
+//! scheduled fetch.
 //!
-//!   https://github.com/mozilla-services/remote-settings-client
-//!   Issue: https://github.com/mozilla/application-services/issues/3475
-//!
-//! But the simple subset implemented here meets our needs for now.
+//! The last experiment list is cached along with its `ETag`, and later fetches
+//! ask the server to only send the list if it's changed. A `304 Not Modified`
+//! response returns the cached list again, so that Nimbus still sees every
+//! experiment. After a failed fetch, later fetches also fail with a backoff
+//! error for an exponentially increasing, jittered interval, so that clients
+//! don't all retry a struggling server at the same time.
 
-use crate::error::Result;
+use crate::error::{NimbusError, Result};
 use crate::schema::parse_experiments;
 use crate::stateful::client::{Experiment, SettingsClient};
-use remote_settings::Client;
+use rand::Rng;
+use remote_settings::{Client, RemoteSettingsConfig, RemoteSettingsError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const HEADER_ETAG: &str = "ETag";
+
+/// How long to wait before fetching again after a failed fetch. The delay
+/// doubles after each consecutive failure, up to [MAX_RETRY_DELAY].
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+pub(crate) struct RemoteSettingsClient {
+    client: Client,
+    state: Mutex<FetchState>,
+}
+
+#[derive(Default)]
+struct FetchState {
+    /// The `ETag` and body of the last successful response.
+    cached: Option<(String, String)>,
+    /// The number of consecutive failed fetches.
+    failures: u32,
+    /// When we can fetch again after a failed fetch.
+    retry_at: Option<Instant>,
+}
+
+impl RemoteSettingsClient {
+    pub(crate) fn new(config: RemoteSettingsConfig) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(config)?,
+            state: Default::default(),
+        })
+    }
+
+    fn fetch_experiments_text(&self, state: &mut FetchState) -> Result<String> {
+        let resp = match &state.cached {
+            Some((etag, text)) => match self.client.get_records_raw_if_none_match(etag)? {
+                Some(resp) => resp,
+                None => return Ok(text.clone()),
+            },
+            None => self.client.get_records_raw()?,
+        };
+        let text = resp.text().into_owned();
+        state.cached = resp
+            .headers
+            .get(HEADER_ETAG)
+            .map(|etag| (etag.to_string(), text.clone()));
+        Ok(text)
+    }
+}
+
+impl FetchState {
+    fn ensure_no_retry_delay(&mut self) -> Result<()> {
+        if let Some(retry_at) = self.retry_at {
+            let now = Instant::now();
+            if now < retry_at {
+                let remaining = retry_at - now;
+                return Err(RemoteSettingsError::BackoffError(remaining.as_secs()).into());
+            }
+            self.retry_at = None;
+        }
+        Ok(())
+    }
+
+    fn note_failure(&mut self) {
+        self.failures = self.failures.saturating_add(1);
+        let delay = MIN_RETRY_DELAY
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(MAX_RETRY_DELAY);
+        // Wait somewhere between half and all of the delay.
+        let jittered = delay / 2 + rand::thread_rng().gen_range(Duration::ZERO..=delay / 2);
+        self.retry_at = Some(Instant::now() + jittered);
+    }
+}
 
-impl SettingsClient for Client {
+impl SettingsClient for RemoteSettingsClient {
     fn get_experiments_metadata(&self) -> Result<String> {
         unimplemented!();
     }
 
     fn fetch_experiments(&self) -> Result<Vec<Experiment>> {
-        let resp = self.get_records_raw()?;
-        parse_experiments(&resp.text())
+        let mut state = self.state.lock().unwrap();
+        state.ensure_no_retry_delay()?;
+        match self.fetch_experiments_text(&mut state) {
+            Ok(text) => {
+                state.failures = 0;
+                parse_experiments(&text)
+            }
+            // The server asked us to back off, so the client won't make
+            // requests until it says we can.
+            Err(e @ NimbusError::ClientError(RemoteSettingsError::BackoffError(_))) => Err(e),
+            Err(e) => {
+                state.note_failure();
+                Err(e)
+            }
+        }
     }
 }
@@ -50,6 +50,15 @@ impl From<AppContext> for TargetingAttributes {
 }
 
 impl TargetingAttributes {
+    pub(crate) fn set_app_context(&mut self, app_context: AppContext) {
+        (self.language, self.region) = app_context
+            .locale
+            .clone()
+            .map(split_locale)
+            .unwrap_or_else(|| (None, None));
+        self.app_context = app_context;
+    }
+
     pub(crate) fn set_recorded_context(&mut self, recorded_context: &dyn RecordedContext) {
         self.recorded_context = Some(recorded_context.to_json());
     }
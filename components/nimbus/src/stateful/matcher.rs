@@ -3,7 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 //! This module defines all the information needed to match a user with an experiment.
-//! Soon it will also include a `match` function of some sort that does the matching.
+//! The matching itself is done by evaluating the experiment's targeting expression
+//! against this information, in [`crate::targeting::jexl_eval`].
 //!
 //! It contains the `AppContext`
 //! provided by the consuming client.
@@ -35,7 +36,7 @@ use serde_json::{Map, Value};
 /// - `installation_date`: The date the application installed the app
 /// - `home_directory`: The application's home directory
 /// - `custom_targeting_attributes`: Contains attributes specific to the application, derived by the application
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 pub struct AppContext {
     pub app_name: String,
     pub app_id: String,
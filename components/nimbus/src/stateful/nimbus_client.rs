@@ -46,11 +46,12 @@ use uuid::Uuid;
 #[cfg(test)]
 use crate::tests::helpers::{TestMetrics, TestRecordedContext};
 
-const DB_KEY_NIMBUS_ID: &str = "nimbus-id";
+pub(crate) const DB_KEY_NIMBUS_ID: &str = "nimbus-id";
 pub const DB_KEY_INSTALLATION_DATE: &str = "installation-date";
 pub const DB_KEY_UPDATE_DATE: &str = "update-date";
 pub const DB_KEY_APP_VERSION: &str = "app-version";
 pub const DB_KEY_FETCH_ENABLED: &str = "fetch-enabled";
+pub const DB_KEY_RECORDED_EXPOSURES: &str = "recorded-exposures";
 
 // The main `NimbusClient` struct must not expose any methods that make an `&mut self`,
 // in order to be compatible with the uniffi's requirements on objects. This is a helper
@@ -59,6 +60,9 @@ pub const DB_KEY_FETCH_ENABLED: &str = "fetch-enabled";
 #[derive(Default)]
 pub struct InternalMutableState {
     pub(crate) available_randomization_units: AvailableRandomizationUnits,
+    // The context the client was created with, or was last given by
+    // `update_app_context()`. The install and update dates are derived from it.
+    pub(crate) app_context: AppContext,
     pub(crate) install_date: Option<DateTime<Utc>>,
     pub(crate) update_date: Option<DateTime<Utc>>,
     // Application level targeting attributes
@@ -78,7 +82,6 @@ impl InternalMutableState {
 pub struct NimbusClient {
     settings_client: Mutex<Box<dyn SettingsClient + Send>>,
     pub(crate) mutable_state: Mutex<InternalMutableState>,
-    app_context: AppContext,
     pub(crate) db: OnceCell<Database>,
     // Manages an in-memory cache so that we can answer certain requests
     // without doing (or waiting for) IO.
@@ -109,6 +112,7 @@ impl NimbusClient {
         }
         let mutable_state = Mutex::new(InternalMutableState {
             available_randomization_units: Default::default(),
+            app_context,
             targeting_attributes,
             install_date: Default::default(),
             update_date: Default::default(),
@@ -117,7 +121,6 @@ impl NimbusClient {
         Ok(Self {
             settings_client,
             mutable_state,
-            app_context,
             database_cache: Default::default(),
             db_path: db_path.into(),
             coenrolling_feature_ids,
@@ -240,6 +243,43 @@ impl NimbusClient {
         Ok(events)
     }
 
+    /// Replaces the app context used for targeting, and re-evaluates the
+    /// enrollments in the known experiments if it changed. This lets changes
+    /// like a new locale take effect without waiting for the next fetch.
+    ///
+    /// The installation and update dates are recalculated if the fields they
+    /// come from changed, so a new `app_version` counts as an update.
+    pub fn update_app_context(
+        &self,
+        app_context: AppContext,
+    ) -> Result<Vec<EnrollmentChangeEvent>> {
+        let db = self.db()?;
+        let mut writer = db.write()?;
+        let mut state = self.mutable_state.lock().unwrap();
+        if state.app_context == app_context {
+            return Ok(vec![]);
+        }
+        if state.app_context.installation_date != app_context.installation_date
+            || state.app_context.home_directory != app_context.home_directory
+        {
+            state.install_date = None;
+        }
+        if state.app_context.app_version != app_context.app_version {
+            state.update_date = None;
+        }
+        state.app_context = app_context.clone();
+        state.targeting_attributes.set_app_context(app_context);
+        self.update_ta_install_dates(db, &mut writer, &mut state)?;
+
+        let existing_experiments: Vec<Experiment> =
+            db.get_store(StoreId::Experiments).collect_all(&writer)?;
+        // As in `set_global_user_participation`, we pass the existing
+        // experiments as "updated experiments" to the evolver.
+        let events = self.evolve_experiments(db, &mut writer, &mut state, &existing_experiments)?;
+        self.end_initialize(db, writer, &mut state)?;
+        Ok(events)
+    }
+
     pub fn get_active_experiments(&self) -> Result<Vec<EnrolledExperiment>> {
         self.database_cache.get_active_experiments()
     }
@@ -330,11 +370,11 @@ impl NimbusClient {
         // the test methods set_install_date() and set_update_date() to set up
         // scenarios for test.
         if state.install_date.is_none() {
-            let installation_date = self.get_installation_date(db, writer)?;
+            let installation_date = self.get_installation_date(db, writer, &state.app_context)?;
             state.install_date = Some(installation_date);
         }
         if state.update_date.is_none() {
-            let update_date = self.get_update_date(db, writer)?;
+            let update_date = self.get_update_date(db, writer, &state.app_context)?;
             state.update_date = Some(update_date);
         }
         state.update_time_to_now(Utc::now());
@@ -413,9 +453,14 @@ impl NimbusClient {
         Ok(res)
     }
 
-    fn get_installation_date(&self, db: &Database, writer: &mut Writer) -> Result<DateTime<Utc>> {
+    fn get_installation_date(
+        &self,
+        db: &Database,
+        writer: &mut Writer,
+        app_context: &AppContext,
+    ) -> Result<DateTime<Utc>> {
         // we first check our context
-        if let Some(context_installation_date) = self.app_context.installation_date {
+        if let Some(context_installation_date) = app_context.installation_date {
             let res = DateTime::<Utc>::from_naive_utc_and_offset(
                 NaiveDateTime::from_timestamp_opt(context_installation_date / 1_000, 0).unwrap(),
                 Utc,
@@ -429,7 +474,7 @@ impl NimbusClient {
         Ok(
             if let Some(installation_date) = persisted_installation_date {
                 installation_date
-            } else if let Some(home_directory) = &self.app_context.home_directory {
+            } else if let Some(home_directory) = &app_context.home_directory {
                 let installation_date = match self.get_creation_date_from_path(home_directory) {
                     Ok(installation_date) => installation_date,
                     Err(e) => {
@@ -446,17 +491,18 @@ impl NimbusClient {
         )
     }
 
-    fn get_update_date(&self, db: &Database, writer: &mut Writer) -> Result<DateTime<Utc>> {
+    fn get_update_date(
+        &self,
+        db: &Database,
+        writer: &mut Writer,
+        app_context: &AppContext,
+    ) -> Result<DateTime<Utc>> {
         let store = db.get_store(StoreId::Meta);
 
         let persisted_app_version: Option<String> = store.get(writer, DB_KEY_APP_VERSION)?;
         let update_date: Option<DateTime<Utc>> = store.get(writer, DB_KEY_UPDATE_DATE)?;
         Ok(
-            match (
-                persisted_app_version,
-                &self.app_context.app_version,
-                update_date,
-            ) {
+            match (persisted_app_version, &app_context.app_version, update_date) {
                 // The app been run before, but has not just been updated.
                 (Some(persisted), Some(current), Some(date)) if persisted == *current => date,
                 // The app has been run before, and just been updated.
@@ -773,7 +819,68 @@ impl NimbusClient {
     }
 
     pub fn record_feature_exposure(&self, feature_id: String, slug: Option<String>) {
-        let event = if let Some(slug) = slug {
+        if let Some(event) = self.get_feature_exposure_event(feature_id, slug) {
+            self.metrics_handler.record_feature_exposure(event);
+        }
+    }
+
+    /// Records a feature exposure, but only the first time it is called for
+    /// each enrollment. Which exposures have been recorded is persisted, so
+    /// this is safe to call every time a feature is rendered.
+    pub fn record_exposure(&self, feature_id: String) -> Result<()> {
+        let event = match self.get_feature_exposure_event(feature_id, None) {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        let key = format!(
+            "{}:{}:{}",
+            event.slug,
+            event.branch.as_deref().unwrap_or_default(),
+            event.feature_id
+        );
+        let db = self.db()?;
+        // This is called every time a feature is rendered, so avoid a write
+        // when the exposure has already been recorded.
+        let recorded: Vec<String> = db
+            .get(StoreId::Meta, DB_KEY_RECORDED_EXPOSURES)?
+            .unwrap_or_default();
+        if recorded.contains(&key) {
+            return Ok(());
+        }
+        // Forget about the exposures of enrollments which have since ended, so
+        // that they are recorded again if the client is enrolled again.
+        let enrolled_prefixes = self
+            .database_cache
+            .get_active_experiments()?
+            .into_iter()
+            .map(|e| format!("{}:{}:", e.slug, e.branch_slug))
+            .collect::<Vec<_>>();
+
+        let mut writer = db.write()?;
+        let store = db.get_store(StoreId::Meta);
+        let mut recorded: Vec<String> = store
+            .get(&writer, DB_KEY_RECORDED_EXPOSURES)?
+            .unwrap_or_default();
+        recorded.retain(|k| enrolled_prefixes.iter().any(|p| k.starts_with(p)));
+        let is_new = !recorded.contains(&key);
+        if is_new {
+            recorded.push(key);
+        }
+        store.put(&mut writer, DB_KEY_RECORDED_EXPOSURES, &recorded)?;
+        writer.commit()?;
+
+        if is_new {
+            self.metrics_handler.record_feature_exposure(event);
+        }
+        Ok(())
+    }
+
+    fn get_feature_exposure_event(
+        &self,
+        feature_id: String,
+        slug: Option<String>,
+    ) -> Option<FeatureExposureExtraDef> {
+        if let Some(slug) = slug {
             if let Ok(Some(branch)) = self.database_cache.get_experiment_branch(&slug) {
                 Some(FeatureExposureExtraDef {
                     feature_id,
@@ -791,10 +898,6 @@ impl NimbusClient {
             }
         } else {
             None
-        };
-
-        if let Some(event) = event {
-            self.metrics_handler.record_feature_exposure(event);
         }
     }
 
@@ -893,3 +996,6 @@ impl UniffiCustomTypeConverter for JsonObject {
 
 #[cfg(feature = "stateful-uniffi-bindings")]
 uniffi::include_scaffolding!("nimbus");
+
+pub const UNIFFI_NAMESPACE: &str = "nimbus";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -11,6 +11,7 @@ use crate::error::{NimbusError, Result};
 // production/release environments at Mozilla, you may do so with the "SafeMode"
 // backend", so we really should get more guidance here.)
 use crate::enrollment::ExperimentEnrollment;
+use crate::stateful::nimbus_client::DB_KEY_NIMBUS_ID;
 use crate::Experiment;
 use core::iter::Iterator;
 use rkv::{StoreError, StoreOptions};
@@ -124,6 +125,9 @@ pub enum StoreId {
     ///   * "update-date": a UTC DateTime string, defining the date the consuming app was
     ///                     last updated
     ///   * "app-version": String, the version of the app last persisted
+    ///   * "recorded-exposures": Vec<String>, the exposures recorded by
+    ///                     `record_exposure`, so they are only recorded once
+    ///                     per enrollment.
     Meta,
     /// Store containing pending updates to experiment data.
     ///
@@ -286,14 +290,16 @@ impl Database {
     fn maybe_upgrade(&self) -> Result<()> {
         log::debug!("entered maybe upgrade");
         let mut writer = self.rkv.write()?;
-        let db_version = self.meta_store.get::<u16, _>(&writer, DB_KEY_DB_VERSION)?;
+        // If the version can't be read, we treat it like an unknown version,
+        // rather than failing here on every launch.
+        let db_version = self.meta_store.get::<u16, _>(&writer, DB_KEY_DB_VERSION);
         match db_version {
-            Some(DB_VERSION) => {
+            Ok(Some(DB_VERSION)) => {
                 // Already at the current version, no migration required.
                 log::info!("Already at version {}, no upgrade needed", DB_VERSION);
                 return Ok(());
             }
-            Some(1) => {
+            Ok(Some(1)) => {
                 log::info!("Migrating database from v1 to v2");
                 match self.migrate_v1_to_v2(&mut writer) {
                     Ok(_) => (),
@@ -313,7 +319,7 @@ impl Database {
                     }
                 };
             }
-            None => {
+            Ok(None) => {
                 log::info!("maybe_upgrade: no version number; wiping most stores");
                 // The "first" version of the database (= no version number) had un-migratable data
                 // for experiments and enrollments, start anew.
@@ -324,10 +330,10 @@ impl Database {
             _ => {
                 error_support::report_error!(
                     "nimbus-unknown-database-version",
-                    "Unknown database version. Wiping all stores."
+                    "Unknown or unreadable database version. Wiping all stores."
                 );
                 self.clear_experiments_and_enrollments(&mut writer)?;
-                self.meta_store.clear(&mut writer)?;
+                self.clear_meta_except_nimbus_id(&mut writer)?;
             }
         }
         // It is safe to clear the update store (i.e. the pending experiments) on all schema upgrades
@@ -351,6 +357,23 @@ impl Database {
         Ok(())
     }
 
+    // Clears the meta store, but keeps the Nimbus ID if it can be read, so the
+    // client stays in the same buckets when it enrolls again.
+    fn clear_meta_except_nimbus_id(&self, writer: &mut Writer) -> Result<()> {
+        let nimbus_id = self
+            .meta_store
+            .get::<String, _>(writer, DB_KEY_NIMBUS_ID)
+            .unwrap_or_else(|e| {
+                log::warn!("Discarding unreadable Nimbus ID: {}", e);
+                None
+            });
+        self.meta_store.clear(writer)?;
+        if let Some(nimbus_id) = nimbus_id {
+            self.meta_store.put(writer, DB_KEY_NIMBUS_ID, &nimbus_id)?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn clear_event_count_data(&self, writer: &mut Writer) -> Result<(), NimbusError> {
         self.event_count_store.clear(writer)?;
         Ok(())
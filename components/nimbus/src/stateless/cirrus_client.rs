@@ -214,3 +214,6 @@ impl CirrusClient {
 }
 
 uniffi::include_scaffolding!("cirrus");
+
+pub const UNIFFI_NAMESPACE: &str = "cirrus";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
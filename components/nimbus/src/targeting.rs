@@ -4,6 +4,7 @@
 
 use crate::{versioning::Version, NimbusError, Result};
 use jexl_eval::Evaluator;
+use regex::RegexBuilder;
 use serde::Serialize;
 use serde_json::{json, Value};
 
@@ -89,8 +90,9 @@ pub fn jexl_eval<Context: serde::Serialize>(
     context: &Context,
     #[cfg(feature = "stateful")] event_store: Arc<Mutex<EventStore>>,
 ) -> Result<bool> {
-    let evaluator =
-        Evaluator::new().with_transform("versionCompare", |args| Ok(version_compare(args)?));
+    let evaluator = Evaluator::new()
+        .with_transform("versionCompare", |args| Ok(version_compare(args)?))
+        .with_transform("regExpMatch", |args| Ok(regexp_match(args)?));
 
     #[cfg(feature = "stateful")]
     let evaluator = evaluator
@@ -162,6 +164,40 @@ fn version_compare(args: &[Value]) -> Result<Value> {
     }))
 }
 
+// Matches a string against a regular expression, like desktop's `regExpMatch`
+// transform. Unlike desktop, this returns a bool rather than the matches, since
+// that's all targeting expressions need.
+//
+// `flags` is optional; only `i`, for case-insensitive matching, is supported.
+fn regexp_match(args: &[Value]) -> Result<Value> {
+    let input = args.first().ok_or_else(|| {
+        NimbusError::TransformParameterError("input doesn't exist in jexl transform".into())
+    })?;
+    let pattern = args.get(1).and_then(Value::as_str).ok_or_else(|| {
+        NimbusError::TransformParameterError(
+            "pattern in jexl transform is missing or not a string".into(),
+        )
+    })?;
+    let flags = match args.get(2) {
+        None => "",
+        Some(flags) => flags.as_str().ok_or_else(|| {
+            NimbusError::TransformParameterError("flags in jexl transform is not a string".into())
+        })?,
+    };
+    if let Some(flag) = flags.chars().find(|flag| *flag != 'i') {
+        return Err(NimbusError::TransformParameterError(format!(
+            "unsupported regular expression flag '{flag}' in jexl transform"
+        )));
+    }
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(flags.contains('i'))
+        .build()
+        .map_err(|e| NimbusError::TransformParameterError(e.to_string()))?;
+    // Like desktop, missing values (e.g. an unset attribute) never match.
+    let matched = input.as_str().is_some_and(|s| regex.is_match(s));
+    Ok(Value::Bool(matched))
+}
+
 #[cfg(feature = "stateful")]
 fn bucket_sample(args: &[Value]) -> anyhow::Result<Value> {
     fn get_arg_as_u32(args: &[Value], idx: usize, name: &str) -> anyhow::Result<u32> {
@@ -150,6 +150,7 @@ impl TestMetrics {
         let mut state = self.state.lock().unwrap();
         state.activations.clear();
         state.enrollment_statuses.clear();
+        state.exposures.clear();
         state.malformeds.clear();
     }
 
@@ -157,6 +158,10 @@ impl TestMetrics {
         self.state.lock().unwrap().activations.clone()
     }
 
+    pub fn get_exposures(&self) -> Vec<FeatureExposureExtraDef> {
+        self.state.lock().unwrap().exposures.clone()
+    }
+
     pub fn get_malformeds(&self) -> Vec<MalformedFeatureConfigExtraDef> {
         self.state.lock().unwrap().malformeds.clone()
     }
@@ -10,6 +10,7 @@ mod test_evaluator;
 mod test_lib_bw_compat;
 mod test_sampling;
 mod test_schema;
+mod test_validation;
 mod test_versioning;
 
 #[cfg(feature = "stateful")]
@@ -3,7 +3,25 @@
 * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::schema::parse_experiments;
+use crate::stateful::client::{http_client::RemoteSettingsClient, SettingsClient};
 use crate::{Branch, BucketConfig, Experiment, FeatureConfig, NimbusError, RandomizationUnit};
+use mockito::mock;
+use remote_settings::{RemoteSettingsConfig, RemoteSettingsError, RemoteSettingsServer};
+
+const RECORDS_PATH: &str = "/v1/buckets/main/collections/nimbus-mobile-experiments/records";
+
+fn test_client() -> RemoteSettingsClient {
+    viaduct_reqwest::use_reqwest_backend();
+    RemoteSettingsClient::new(RemoteSettingsConfig {
+        server: Some(RemoteSettingsServer::Custom {
+            url: mockito::server_url(),
+        }),
+        server_url: None,
+        bucket_name: None,
+        collection_name: "nimbus-mobile-experiments".to_string(),
+    })
+    .unwrap()
+}
 
 #[test]
 fn test_fetch_experiments_from_schema() {
@@ -69,6 +87,55 @@ fn test_malformed_payload() {
     assert!(matches!(result, NimbusError::JSONError(_)));
 }
 
+#[test]
+fn test_fetch_experiments_not_modified() {
+    let modified_m = mock("GET", RECORDS_PATH)
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_body(response_body())
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"1000\"")
+        .create();
+    let not_modified_m = mock("GET", RECORDS_PATH)
+        .match_header("if-none-match", "\"1000\"")
+        .with_status(304)
+        .with_header("etag", "\"1000\"")
+        .create();
+    let client = test_client();
+
+    // The cached experiments are returned when they haven't changed.
+    let first = client.fetch_experiments().unwrap();
+    let second = client.fetch_experiments().unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(first, second);
+    modified_m.expect(1).assert();
+    not_modified_m.expect(1).assert();
+}
+
+#[test]
+fn test_fetch_experiments_retry_delay() {
+    let m = mock("GET", RECORDS_PATH)
+        .with_body("Boom!")
+        .with_status(500)
+        .create();
+    let client = test_client();
+
+    assert!(matches!(
+        client.fetch_experiments(),
+        Err(NimbusError::ClientError(
+            RemoteSettingsError::ResponseError(_)
+        ))
+    ));
+    // We wait before making another request after a failure.
+    assert!(matches!(
+        client.fetch_experiments(),
+        Err(NimbusError::ClientError(RemoteSettingsError::BackoffError(
+            _
+        )))
+    ));
+    m.expect(1).assert();
+}
+
 // This response body includes a matching schema version, a non-matching schema version,
 // and a malformed experiment.
 fn response_body() -> String {
@@ -20,7 +20,7 @@ use crate::{
         TestMetrics,
     },
     AppContext, Experiment, NimbusClient, TargetingAttributes, DB_KEY_APP_VERSION,
-    DB_KEY_UPDATE_DATE,
+    DB_KEY_RECORDED_EXPOSURES, DB_KEY_UPDATE_DATE,
 };
 use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
@@ -1508,6 +1508,60 @@ fn test_feature_activation_events() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_record_exposure_once_per_enrollment() -> Result<()> {
+    let slug_exp = "my-experiment";
+    let feature_exp = "experimental-feature";
+    let rec_exp = get_single_feature_experiment(slug_exp, feature_exp, json!({}));
+
+    let slug_ro = "my-rollout";
+    let feature_ro = "rollout-feature";
+    let rec_ro = get_single_feature_rollout(slug_ro, feature_ro, json!({}));
+
+    let metrics = TestMetrics::new();
+    let client = with_metrics(&metrics, "coenrolling-feature")?;
+    client.set_experiments_locally(to_local_experiments_string(&[rec_exp, rec_ro])?)?;
+    client.apply_pending_experiments()?;
+
+    // Rollouts don't record exposures.
+    client.record_exposure(feature_ro.to_string())?;
+    assert!(client.get_metrics_handler().get_exposures().is_empty());
+
+    // Experiments record an exposure the first time only.
+    client.record_exposure(feature_exp.to_string())?;
+    client.record_exposure(feature_exp.to_string())?;
+    let events = client.get_metrics_handler().get_exposures();
+    assert_eq!(1, events.len());
+    assert_eq!(slug_exp, &events[0].slug);
+    assert_eq!(Some("control"), events[0].branch.as_deref());
+
+    // Explicit exposures are never deduped.
+    client.record_feature_exposure(feature_exp.to_string(), None);
+    assert_eq!(2, client.get_metrics_handler().get_exposures().len());
+
+    // Once the enrollment ends, the recorded exposure is forgotten.
+    metrics.clear();
+    client.set_experiments_locally(to_local_experiments_string(&[] as &[Experiment])?)?;
+    client.apply_pending_experiments()?;
+    client.record_exposure(feature_exp.to_string())?;
+    assert!(client.get_metrics_handler().get_exposures().is_empty());
+
+    let slug_exp_2 = "my-experiment-2";
+    let rec_exp_2 = get_single_feature_experiment(slug_exp_2, feature_exp, json!({}));
+    client.set_experiments_locally(to_local_experiments_string(&[rec_exp_2])?)?;
+    client.apply_pending_experiments()?;
+    client.record_exposure(feature_exp.to_string())?;
+    assert_eq!(1, client.get_metrics_handler().get_exposures().len());
+    let recorded: Option<Vec<String>> =
+        client.db()?.get(StoreId::Meta, DB_KEY_RECORDED_EXPOSURES)?;
+    assert_eq!(
+        recorded,
+        Some(vec![format!("{slug_exp_2}:control:{feature_exp}")])
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_malformed_feature_events() -> Result<()> {
     let slug_exp = "my-experiment";
@@ -1686,3 +1740,119 @@ fn test_recorded_context_recorded() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_update_app_context_reevaluates_enrollments() -> Result<()> {
+    let metrics = TestMetrics::new();
+    let temp_dir = tempfile::tempdir()?;
+
+    let app_context = AppContext {
+        app_name: "fenix".to_string(),
+        app_id: "org.mozilla.fenix".to_string(),
+        channel: "nightly".to_string(),
+        locale: Some("en-US".to_string()),
+        ..Default::default()
+    };
+    let client = NimbusClient::new(
+        app_context.clone(),
+        None,
+        Default::default(),
+        temp_dir.path(),
+        None,
+        Box::new(metrics),
+    )?;
+    client.set_nimbus_id(&Uuid::from_str("00000000-0000-0000-0000-000000000004")?)?;
+    client.initialize()?;
+
+    let exp_1 = get_targeted_experiment("english-only", "language == 'en'");
+    let mut exp_2 = get_targeted_experiment("german-only", "language == 'de'");
+    exp_2["featureIds"] = json!(["some-feature-2"]);
+    client.set_experiments_locally(to_local_experiments_string(&[exp_1, exp_2])?)?;
+    client.apply_pending_experiments()?;
+    let active_experiments = client.get_active_experiments()?;
+    assert_eq!(active_experiments.len(), 1);
+    assert_eq!(active_experiments[0].slug, "english-only");
+
+    // An unchanged context doesn't re-evaluate anything.
+    assert!(client.update_app_context(app_context.clone())?.is_empty());
+
+    // Changing the locale disqualifies us from one experiment and enrolls us in the other.
+    let events = client.update_app_context(AppContext {
+        locale: Some("de-DE".to_string()),
+        ..app_context.clone()
+    })?;
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .any(|e| e.experiment_slug == "english-only" && e.reason.as_deref() == Some("targeting")));
+    assert!(events.iter().any(|e| e.experiment_slug == "german-only"));
+    let active_experiments = client.get_active_experiments()?;
+    assert_eq!(active_experiments.len(), 1);
+    assert_eq!(active_experiments[0].slug, "german-only");
+    assert_eq!(
+        client.get_targeting_attributes().language,
+        Some("de".to_string())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_update_app_context_updates_dates() -> Result<()> {
+    let metrics = TestMetrics::new();
+    let tmp_dir = tempfile::tempdir()?;
+
+    let app_context = AppContext {
+        app_version: Some("v94.0.0".into()),
+        installation_date: Some((Utc::now() - Duration::days(3)).timestamp_millis()),
+        ..Default::default()
+    };
+    let client = NimbusClient::new(
+        app_context.clone(),
+        Default::default(),
+        Default::default(),
+        tmp_dir.path(),
+        None,
+        Box::new(metrics.clone()),
+    )?;
+    client.initialize()?;
+
+    // Pretend the app was last updated a few days ago.
+    let db = client.db()?;
+    let mut writer = db.write()?;
+    let update_date = Utc::now() - Duration::days(2);
+    db.get_store(StoreId::Meta)
+        .put(&mut writer, DB_KEY_UPDATE_DATE, &update_date)?;
+    writer.commit()?;
+    let client = NimbusClient::new(
+        app_context.clone(),
+        Default::default(),
+        Default::default(),
+        tmp_dir.path(),
+        None,
+        Box::new(metrics),
+    )?;
+    client.initialize()?;
+    let targeting_attributes = client.get_targeting_attributes();
+    assert!(matches!(targeting_attributes.days_since_install, Some(3)));
+    assert!(matches!(targeting_attributes.days_since_update, Some(2)));
+
+    // A new version is an update, and the installation date comes from the new context.
+    client.update_app_context(AppContext {
+        app_version: Some("v94.0.1".into()),
+        installation_date: Some((Utc::now() - Duration::days(5)).timestamp_millis()),
+        ..app_context
+    })?;
+    let targeting_attributes = client.get_targeting_attributes();
+    assert!(matches!(targeting_attributes.days_since_install, Some(5)));
+    assert!(matches!(targeting_attributes.days_since_update, Some(0)));
+    let db = client.db()?;
+    let reader = db.read()?;
+    let app_version: String = db
+        .get_store(StoreId::Meta)
+        .get(&reader, DB_KEY_APP_VERSION)?
+        .unwrap();
+    assert_eq!(app_version, "v94.0.1");
+
+    Ok(())
+}
@@ -3,12 +3,20 @@
 * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    enrollment::ExperimentEnrollment, error::Result, stateful::persistence::*, Experiment,
+    enrollment::ExperimentEnrollment,
+    error::Result,
+    stateful::{
+        nimbus_client::{DB_KEY_APP_VERSION, DB_KEY_NIMBUS_ID},
+        persistence::*,
+    },
+    Experiment,
 };
 use rkv::StoreOptions;
 use serde_json::json;
 use std::fs;
 
+const NIMBUS_ID: &str = "29686b11-00c0-4905-b5e4-f5f945eda60a";
+
 #[test]
 fn test_db_upgrade_no_version() -> Result<()> {
     let tmp_dir = tempfile::tempdir()?;
@@ -44,6 +52,8 @@ fn test_db_upgrade_unknown_version() -> Result<()> {
         SingleStore::new(rkv.open_single("enrollments", StoreOptions::create())?);
     let mut writer = rkv.write()?;
     meta_store.put(&mut writer, DB_KEY_DB_VERSION, &u16::MAX)?;
+    meta_store.put(&mut writer, DB_KEY_NIMBUS_ID, &NIMBUS_ID.to_owned())?;
+    meta_store.put(&mut writer, DB_KEY_APP_VERSION, &"126.0".to_owned())?;
     enrollment_store.put(&mut writer, "foo", &"bar".to_owned())?;
     experiment_store.put(&mut writer, "bobo", &"tron".to_owned())?;
     writer.commit()?;
@@ -51,6 +61,38 @@ fn test_db_upgrade_unknown_version() -> Result<()> {
     assert_eq!(db.get(StoreId::Meta, DB_KEY_DB_VERSION)?, Some(DB_VERSION));
     assert!(db.collect_all::<String>(StoreId::Enrollments)?.is_empty());
     assert!(db.collect_all::<String>(StoreId::Experiments)?.is_empty());
+    // The Nimbus ID survives, but the rest of the meta store is wiped.
+    assert_eq!(
+        db.get(StoreId::Meta, DB_KEY_NIMBUS_ID)?,
+        Some(NIMBUS_ID.to_owned())
+    );
+    assert_eq!(db.get::<String>(StoreId::Meta, DB_KEY_APP_VERSION)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_db_upgrade_unreadable_version() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+
+    let rkv = Database::open_rkv(&tmp_dir)?;
+    let meta_store = SingleStore::new(rkv.open_single("meta", StoreOptions::create())?);
+    let experiment_store =
+        SingleStore::new(rkv.open_single("experiments", StoreOptions::create())?);
+    let mut writer = rkv.write()?;
+    meta_store.put(&mut writer, DB_KEY_DB_VERSION, &"not a version".to_owned())?;
+    meta_store.put(&mut writer, DB_KEY_NIMBUS_ID, &NIMBUS_ID.to_owned())?;
+    experiment_store.put(&mut writer, "bobo", &"tron".to_owned())?;
+    writer.commit()?;
+
+    // Opening the database must not fail on every launch.
+    let db = Database::new(&tmp_dir)?;
+    assert_eq!(db.get(StoreId::Meta, DB_KEY_DB_VERSION)?, Some(DB_VERSION));
+    assert!(db.collect_all::<String>(StoreId::Experiments)?.is_empty());
+    assert_eq!(
+        db.get(StoreId::Meta, DB_KEY_NIMBUS_ID)?,
+        Some(NIMBUS_ID.to_owned())
+    );
 
     Ok(())
 }
@@ -138,6 +138,46 @@ fn test_minimum_version_targeting_fails() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_regexp_match_targeting() -> Result<()> {
+    let ctx = AppContext {
+        app_version: Some("126.0b3".into()),
+        ..Default::default()
+    };
+    let helper = ctx.into();
+    assert_eq!(
+        targeting("app_version|regExpMatch('^126[.]')", &helper),
+        None
+    );
+    assert_eq!(
+        targeting("app_version|regExpMatch('B3$', 'i')", &helper),
+        None
+    );
+    assert_eq!(
+        targeting("app_version|regExpMatch('B3$')", &helper),
+        Some(EnrollmentStatus::NotEnrolled {
+            reason: NotEnrolledReason::NotTargeted
+        })
+    );
+    // Missing values never match.
+    assert_eq!(
+        targeting("app_build|regExpMatch('.*')", &helper),
+        Some(EnrollmentStatus::NotEnrolled {
+            reason: NotEnrolledReason::NotTargeted
+        })
+    );
+    // Bad patterns and flags are errors.
+    assert!(matches!(
+        targeting("app_version|regExpMatch('(')", &helper),
+        Some(EnrollmentStatus::Error { .. })
+    ));
+    assert!(matches!(
+        targeting("app_version|regExpMatch('126', 'g')", &helper),
+        Some(EnrollmentStatus::Error { .. })
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_targeting_specific_version() -> Result<()> {
     // Here's our valid jexl statement that targets **only** 96 versions
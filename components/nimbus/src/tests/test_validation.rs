@@ -0,0 +1,75 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+* License, v. 2.0. If a copy of the MPL was not distributed with this
+* file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{tests::helpers::get_single_feature_experiment, validate_experiment, Result};
+use serde_json::json;
+
+fn issue_paths(experiment_json: String) -> Vec<String> {
+    validate_experiment(experiment_json, vec!["feature-a".to_string()])
+        .into_iter()
+        .map(|issue| issue.path)
+        .collect()
+}
+
+#[test]
+fn test_validate_valid_experiment() -> Result<()> {
+    let exp = get_single_feature_experiment("exp", "feature-a", json!({}));
+    assert_eq!(
+        issue_paths(serde_json::to_string(&exp)?),
+        Vec::<String>::new()
+    );
+
+    let exp = exp.patch(json!({ "targeting": "app_version|versionCompare('126') >= 0" }));
+    assert_eq!(
+        issue_paths(serde_json::to_string(&exp)?),
+        Vec::<String>::new()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_validate_malformed_experiment() {
+    let issues = validate_experiment("{\"slug\": \"exp\"}".to_string(), vec![]);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].path, "");
+}
+
+#[test]
+fn test_validate_experiment_issues() -> Result<()> {
+    let exp = get_single_feature_experiment("exp", "feature-b", json!({})).patch(json!({
+        "schemaVersion": "2.0.0",
+        "referenceBranch": "treatment",
+        "targeting": "app_version ==",
+        "bucketConfig": {
+            "count": 10_000,
+            "start": 1,
+            "total": 10_000,
+            "namespace": "secure-silver",
+            "randomizationUnit": "nimbus_id"
+        },
+    }));
+    assert_eq!(
+        issue_paths(serde_json::to_string(&exp)?),
+        vec![
+            "schemaVersion",
+            "branches[0]",
+            "featureIds",
+            "referenceBranch",
+            "bucketConfig",
+            "targeting",
+        ]
+    );
+
+    let exp = get_single_feature_experiment("exp", "feature-a", json!({})).patch(json!({
+        "branches": [
+            { "slug": "control", "ratio": 0 },
+            { "slug": "control", "ratio": -1 },
+        ],
+    }));
+    assert_eq!(
+        issue_paths(serde_json::to_string(&exp)?),
+        vec!["branches[1].slug", "branches[1].ratio", "branches"]
+    );
+    Ok(())
+}
@@ -0,0 +1,164 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Checks experiment recipes before they're published, using the same parsing
+//! the SDK uses when it fetches them, so that experiment authors and tooling
+//! can catch mistakes that would otherwise make the SDK ignore an experiment.
+
+use crate::{versioning::Version, Experiment};
+use std::collections::HashSet;
+
+// The major version of the experiment schema this SDK understands.
+const SUPPORTED_SCHEMA_MAJOR: i32 = 1;
+
+/// A problem found in an experiment recipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Where in the recipe the problem is, like `branches[1].ratio`. Empty if
+    /// the problem is with the recipe as a whole.
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Validates a single experiment recipe, as JSON.
+///
+/// `feature_ids` are the features the app's manifest defines; the recipe may
+/// only configure those features. Returns an empty list if the recipe is valid.
+pub fn validate_experiment(
+    experiment_json: String,
+    feature_ids: Vec<String>,
+) -> Vec<ValidationIssue> {
+    let experiment = match serde_json::from_str::<Experiment>(&experiment_json) {
+        Ok(experiment) => experiment,
+        Err(e) => return vec![ValidationIssue::new("", e.to_string())],
+    };
+    let manifest_ids = feature_ids.into_iter().collect::<HashSet<_>>();
+    let mut issues = Vec::new();
+    validate_schema_version(&experiment, &mut issues);
+    validate_branches(&experiment, &manifest_ids, &mut issues);
+    validate_bucket_config(&experiment, &mut issues);
+    validate_targeting(&experiment, &mut issues);
+    issues
+}
+
+fn validate_schema_version(experiment: &Experiment, issues: &mut Vec<ValidationIssue>) {
+    match Version::try_from(experiment.schema_version.as_str()) {
+        Ok(version) if major_version(&version) == Some(SUPPORTED_SCHEMA_MAJOR) => {}
+        Ok(_) => issues.push(ValidationIssue::new(
+            "schemaVersion",
+            format!(
+                "schema version {} is not supported; expected {SUPPORTED_SCHEMA_MAJOR}.x",
+                experiment.schema_version
+            ),
+        )),
+        Err(e) => issues.push(ValidationIssue::new("schemaVersion", e.to_string())),
+    }
+}
+
+fn major_version(version: &Version) -> Option<i32> {
+    version.0.first().map(|part| part.num_a)
+}
+
+fn validate_branches(
+    experiment: &Experiment,
+    manifest_ids: &HashSet<String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if experiment.branches.is_empty() {
+        issues.push(ValidationIssue::new("branches", "no branches"));
+        return;
+    }
+    let mut slugs = HashSet::new();
+    let mut total_ratio = 0i64;
+    for (i, branch) in experiment.branches.iter().enumerate() {
+        if !slugs.insert(branch.slug.as_str()) {
+            issues.push(ValidationIssue::new(
+                format!("branches[{i}].slug"),
+                format!("duplicate branch slug {}", branch.slug),
+            ));
+        }
+        if branch.ratio < 0 {
+            issues.push(ValidationIssue::new(
+                format!("branches[{i}].ratio"),
+                "ratio must not be negative",
+            ));
+        } else {
+            total_ratio += i64::from(branch.ratio);
+        }
+        for feature in branch.get_feature_configs() {
+            if !manifest_ids.contains(&feature.feature_id) {
+                issues.push(ValidationIssue::new(
+                    format!("branches[{i}]"),
+                    format!("unknown feature {}", feature.feature_id),
+                ));
+            } else if !experiment.feature_ids.is_empty()
+                && !experiment.feature_ids.contains(&feature.feature_id)
+            {
+                issues.push(ValidationIssue::new(
+                    format!("branches[{i}]"),
+                    format!("feature {} is missing from featureIds", feature.feature_id),
+                ));
+            }
+        }
+    }
+    if total_ratio == 0 {
+        issues.push(ValidationIssue::new(
+            "branches",
+            "the branch ratios must add up to more than 0",
+        ));
+    }
+    for feature_id in &experiment.feature_ids {
+        if !manifest_ids.contains(feature_id) {
+            issues.push(ValidationIssue::new(
+                "featureIds",
+                format!("unknown feature {feature_id}"),
+            ));
+        }
+    }
+    if let Some(reference_branch) = &experiment.reference_branch {
+        if !experiment.has_branch(reference_branch) {
+            issues.push(ValidationIssue::new(
+                "referenceBranch",
+                format!("no branch with slug {reference_branch}"),
+            ));
+        }
+    }
+}
+
+fn validate_bucket_config(experiment: &Experiment, issues: &mut Vec<ValidationIssue>) {
+    let bucket_config = &experiment.bucket_config;
+    if bucket_config.total == 0 {
+        issues.push(ValidationIssue::new(
+            "bucketConfig.total",
+            "total must be greater than 0",
+        ));
+    } else if u64::from(bucket_config.start) + u64::from(bucket_config.count)
+        > u64::from(bucket_config.total)
+    {
+        issues.push(ValidationIssue::new(
+            "bucketConfig",
+            "start + count must not be greater than total",
+        ));
+    }
+}
+
+fn validate_targeting(experiment: &Experiment, issues: &mut Vec<ValidationIssue>) {
+    if let Some(targeting) = &experiment.targeting {
+        if let Err(e) = jexl_parser::Parser::parse(targeting) {
+            issues.push(ValidationIssue::new(
+                "targeting",
+                format!("invalid targeting expression: {e}"),
+            ));
+        }
+    }
+}
@@ -23,7 +23,7 @@ use std::sync::{
     Arc, Weak,
 };
 use sync15::client::{sync_multiple, MemoryCachedState, Sync15StorageClientInit, SyncResult};
-use sync15::engine::{EngineSyncAssociation, SyncEngine, SyncEngineId};
+use sync15::engine::{EngineRegistry, EngineSyncAssociation, SyncEngine, SyncEngineId};
 use sync15::{telemetry, KeyBundle};
 
 // Not clear if this should be here, but this is the "global sync state"
@@ -33,40 +33,60 @@ use sync15::{telemetry, KeyBundle};
 // per collection.
 pub const GLOBAL_STATE_META_KEY: &str = "global_sync_state_v2";
 
-// Our "sync manager" will use whatever is stashed here.
+// Our "sync manager" will use whatever is registered here.
 lazy_static::lazy_static! {
-    // Mutex: just taken long enough to update the contents - needed to wrap
-    //        the Weak as it isn't `Sync`
-    // [Arc/Weak]: Stores the places api used to create the connection for
-    //             BookmarksSyncEngine/HistorySyncEngine
-    static ref PLACES_API_FOR_SYNC_MANAGER: Mutex<Weak<PlacesApi>> = Mutex::new(Weak::new());
+    // The places api used to create the connection for
+    // BookmarksSyncEngine/HistorySyncEngine
+    static ref PLACES_APIS_FOR_SYNC_MANAGER: EngineRegistry<PlacesApi> = EngineRegistry::new();
 }
 
 // Called by the sync manager to get a sync engine via the PlacesApi previously
 // registered with the sync manager.
 pub fn get_registered_sync_engine(engine_id: &SyncEngineId) -> Option<Box<dyn SyncEngine>> {
-    match PLACES_API_FOR_SYNC_MANAGER.lock().upgrade() {
+    match PLACES_APIS_FOR_SYNC_MANAGER.get() {
         None => {
             log::warn!("places: get_registered_sync_engine: no PlacesApi registered");
             None
         }
-        Some(places_api) => match create_sync_engine(&places_api, engine_id) {
-            Ok(engine) => Some(engine),
-            Err(e) => {
-                // Report this to Sentry, except if it's an open database error.  That indicates
-                // that there is a registered sync engine, but the connection is busy so we can't
-                // open it.  This is a known issue that we don't need more reports for (see
-                // https://github.com/mozilla/application-services/issues/5237 for discussion).
-                if !matches!(e, Error::OpenDatabaseError(_)) {
-                    error_support::report_error!(
-                        "places-no-registered-sync-engine",
-                        "places: get_registered_sync_engine: {}",
-                        e
-                    );
-                }
-                None
+        Some(places_api) => create_registered_sync_engine(&places_api, engine_id),
+    }
+}
+
+// Called by the sync manager to get a sync engine via the PlacesApi previously
+// registered with the sync manager for the specified account.
+pub fn get_registered_sync_engine_for_account(
+    account_id: &str,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn SyncEngine>> {
+    match PLACES_APIS_FOR_SYNC_MANAGER.get_for_account(account_id) {
+        None => {
+            log::warn!("places: get_registered_sync_engine_for_account: no PlacesApi registered");
+            None
+        }
+        Some(places_api) => create_registered_sync_engine(&places_api, engine_id),
+    }
+}
+
+fn create_registered_sync_engine(
+    places_api: &PlacesApi,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn SyncEngine>> {
+    match create_sync_engine(places_api, engine_id) {
+        Ok(engine) => Some(engine),
+        Err(e) => {
+            // Report this to Sentry, except if it's an open database error.  That indicates
+            // that there is a registered sync engine, but the connection is busy so we can't
+            // open it.  This is a known issue that we don't need more reports for (see
+            // https://github.com/mozilla/application-services/issues/5237 for discussion).
+            if !matches!(e, Error::OpenDatabaseError(_)) {
+                error_support::report_error!(
+                    "places-no-registered-sync-engine",
+                    "places: get_registered_sync_engine: {}",
+                    e
+                );
             }
-        },
+            None
+        }
     }
 }
 
@@ -292,7 +312,14 @@ impl PlacesApi {
     // `register_with_sync_manager()` is logically what's happening so that's
     // the name it gets.
     pub fn register_with_sync_manager(self: Arc<Self>) {
-        *PLACES_API_FOR_SYNC_MANAGER.lock() = Arc::downgrade(&self);
+        PLACES_APIS_FOR_SYNC_MANAGER.register(&self);
+    }
+
+    // Like `register_with_sync_manager()`, but only makes this instance
+    // available to a sync manager created for the same account, so places
+    // databases belonging to different accounts don't replace each other.
+    pub fn register_with_sync_manager_for_account(self: Arc<Self>, account_id: String) {
+        PLACES_APIS_FOR_SYNC_MANAGER.register_for_account(account_id, &self);
     }
 
     // NOTE: These should be deprecated as soon as possible - that will be once
@@ -514,6 +541,30 @@ mod tests {
     use super::*;
     use sql_support::ConnExt;
 
+    #[test]
+    fn test_sync_manager_registration_for_account() {
+        let api1 = new_mem_api();
+        let api2 = new_mem_api();
+        Arc::clone(&api1).register_with_sync_manager_for_account("account-1".to_string());
+        Arc::clone(&api2).register_with_sync_manager_for_account("account-2".to_string());
+        let registered = PLACES_APIS_FOR_SYNC_MANAGER
+            .get_for_account("account-1")
+            .expect("should upgrade");
+        assert!(Arc::ptr_eq(&api1, &registered));
+        drop(registered);
+        assert!(
+            get_registered_sync_engine_for_account("account-3", &SyncEngineId::History).is_none()
+        );
+        // dropping one account's api shouldn't impact the other.
+        drop(api1);
+        assert!(PLACES_APIS_FOR_SYNC_MANAGER
+            .get_for_account("account-1")
+            .is_none());
+        assert!(
+            get_registered_sync_engine_for_account("account-2", &SyncEngineId::History).is_some()
+        );
+    }
+
     #[test]
     fn test_multi_writers_fails() {
         let api = new_mem_api();
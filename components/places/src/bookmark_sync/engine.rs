@@ -23,7 +23,7 @@ use dogear::{
     self, AbortSignal, CompletionOps, Content, Item, MergedRoot, TelemetryEvent, Tree, UploadItem,
     UploadTombstone,
 };
-use interrupt_support::SqlInterruptScope;
+use interrupt_support::{SqlInterruptHandle, SqlInterruptScope};
 use rusqlite::Row;
 use sql_support::ConnExt;
 use std::cell::RefCell;
@@ -938,6 +938,10 @@ impl SyncEngine for BookmarksSyncEngine {
         COLLECTION_NAME.into()
     }
 
+    fn interrupt_handle(&self) -> Option<Arc<SqlInterruptHandle>> {
+        Some(self.db.new_interrupt_handle())
+    }
+
     fn stage_incoming(
         &self,
         inbound: Vec<IncomingBso>,
@@ -100,6 +100,51 @@ fn test_new_unknown_fields() {
     .run()
 }
 
+#[test]
+fn test_roundtrip_structured_fields() {
+    // Newer desktop clients might write fields that aren't strings, like arrays or objects. Those
+    // should survive the trip through the mirror unchanged.
+    let api = new_mem_api();
+    let engine = BookmarksSyncEngine::new(api.get_sync_connection().unwrap()).unwrap();
+    let extra = json!({
+        "loadInSidebar": false,
+        "annotations": [{ "name": "bookmarkProperties/description", "value": "Good dogs" }],
+        "futureTagInfo": { "colors": { "dogs": "#ff0000" }, "version": 2 },
+    });
+    let mut record = remote_bookmark(guid('a'), "Dogs", vec![]);
+    record["tags"] = json!(["dogs"]);
+    record["keyword"] = json!("dog");
+    for (key, val) in extra.as_object().unwrap() {
+        record[key] = val.clone();
+    }
+    let mut telem = telemetry::Engine::new("bookmarks");
+    engine
+        .stage_incoming(vec![IncomingBso::from_test_content(record)], &mut telem)
+        .expect("Should stage incoming records");
+    engine.apply(now(), &mut telem).expect("should apply");
+
+    let conn = api
+        .open_connection(crate::ConnectionType::ReadWrite)
+        .unwrap();
+    update_bookmark_from_info(&conn, title_change(guid('a'), "Doggies")).unwrap();
+
+    engine
+        .stage_incoming(vec![], &mut telem)
+        .expect("Should stage incoming records");
+    let outgoing = engine.apply(now(), &mut telem).expect("should apply");
+    let payload = outgoing
+        .iter()
+        .map(|bso| bso.to_test_incoming_t::<Value>())
+        .find(|payload| payload["id"] == guid('a'))
+        .expect("Should upload the changed bookmark");
+    assert_eq!(payload["title"], "Doggies");
+    assert_eq!(payload["tags"], json!(["dogs"]));
+    assert_eq!(payload["keyword"], "dog");
+    for (key, val) in extra.as_object().unwrap() {
+        assert_eq!(&payload[key], val, "Unexpected value for {key}");
+    }
+}
+
 struct RoundtripTest {
     // Mirror records from a previous sync
     initial_remote_records: Vec<Value>,
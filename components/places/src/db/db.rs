@@ -250,6 +250,10 @@ impl SharedPlacesDb {
     pub fn begin_interrupt_scope(&self) -> Result<SqlInterruptScope> {
         Ok(self.interrupt_handle.begin_interrupt_scope()?)
     }
+
+    pub fn new_interrupt_handle(&self) -> Arc<SqlInterruptHandle> {
+        Arc::clone(&self.interrupt_handle)
+    }
 }
 
 // Deref to a Mutex<PlacesDb>, which is how we will use SharedPlacesDb most of the time
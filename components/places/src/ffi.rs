@@ -312,6 +312,15 @@ impl PlacesConnection {
         self.with_conn(|conn| history::get_visit_infos(conn, start_date, end_date, exclude_types))
     }
 
+    #[handle_error(crate::Error)]
+    pub fn get_visits_for_host(
+        &self,
+        host: String,
+        include_subdomains: bool,
+    ) -> ApiResult<Vec<HistoryVisitInfo>> {
+        self.with_conn(|conn| history::get_visits_for_host(conn, &host, include_subdomains))
+    }
+
     #[handle_error(crate::Error)]
     pub fn get_visit_count(&self, exclude_types: VisitTransitionSet) -> ApiResult<i64> {
         self.with_conn(|conn| history::get_visit_count(conn, exclude_types))
@@ -355,6 +364,17 @@ impl PlacesConnection {
         Ok(result)
     }
 
+    // Like `get_visited`, this takes strings instead of urls, so the awesomebar
+    // can pass us its whole result set. Invalid and unknown URLs are skipped.
+    #[handle_error(crate::Error)]
+    pub fn prefetch_page_infos(&self, urls: Vec<String>) -> ApiResult<Vec<PrefetchedPageInfo>> {
+        let urls = urls
+            .iter()
+            .filter_map(|s| Url::parse(s).ok())
+            .collect::<Vec<_>>();
+        self.with_conn(|conn| history::prefetch_page_infos(conn, &urls))
+    }
+
     #[handle_error(crate::Error)]
     pub fn delete_visits_for(&self, url: String) -> ApiResult<()> {
         self.with_conn(|conn| {
@@ -604,6 +624,15 @@ pub struct TopFrecentSiteInfo {
     pub title: Option<String>,
 }
 
+/// What we know about a page which is about to be shown in the awesomebar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefetchedPageInfo {
+    pub url: Url,
+    pub title: Option<String>,
+    pub preview_image_url: Option<Url>,
+    pub is_visited: bool,
+}
+
 pub enum FrecencyThresholdOption {
     None,
     SkipOneTimePages,
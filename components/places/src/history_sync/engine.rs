@@ -6,7 +6,7 @@ use crate::db::{PlacesDb, SharedPlacesDb};
 use crate::error::*;
 use crate::storage::history::{delete_everything, history_sync::reset};
 use crate::storage::{get_meta, put_meta};
-use interrupt_support::SqlInterruptScope;
+use interrupt_support::{SqlInterruptHandle, SqlInterruptScope};
 use std::sync::Arc;
 use sync15::bso::{IncomingBso, OutgoingBso};
 use sync15::engine::{
@@ -80,6 +80,10 @@ impl SyncEngine for HistorySyncEngine {
         "history".into()
     }
 
+    fn interrupt_handle(&self) -> Option<Arc<SqlInterruptHandle>> {
+        Some(self.db.new_interrupt_handle())
+    }
+
     fn stage_incoming(
         &self,
         inbound: Vec<IncomingBso>,
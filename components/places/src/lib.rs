@@ -27,7 +27,9 @@ mod util;
 pub use crate::api::apply_observation;
 #[cfg(test)]
 pub use crate::api::places_api::test;
-pub use crate::api::places_api::{get_registered_sync_engine, ConnectionType, PlacesApi};
+pub use crate::api::places_api::{
+    get_registered_sync_engine, get_registered_sync_engine_for_account, ConnectionType, PlacesApi,
+};
 
 pub use crate::db::PlacesDb;
 pub use crate::error::*;
@@ -39,3 +41,6 @@ pub use crate::types::*;
 pub use ffi::*;
 
 uniffi::include_scaffolding!("places");
+
+pub const UNIFFI_NAMESPACE: &str = "places";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
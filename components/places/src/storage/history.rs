@@ -7,7 +7,9 @@ mod actions;
 use super::{fetch_page_info, new_page_info, PageInfo, RowId};
 use crate::db::PlacesDb;
 use crate::error::Result;
-use crate::ffi::{HistoryVisitInfo, HistoryVisitInfosWithBound, TopFrecentSiteInfo};
+use crate::ffi::{
+    HistoryVisitInfo, HistoryVisitInfosWithBound, PrefetchedPageInfo, TopFrecentSiteInfo,
+};
 use crate::frecency;
 use crate::hash;
 use crate::history_sync::engine::{
@@ -1405,6 +1407,84 @@ pub fn get_visit_infos(
     Ok(infos)
 }
 
+/// Returns all visits to pages on `host`, and optionally on any of its
+/// subdomains, ordered by visit date.
+///
+/// This uses the reversed host stored in `moz_origins`, so "all visits on
+/// *.example.com" is a range scan over the `hostindex` index - a reversed
+/// subdomain such as `moc.elpmaxe.www.` always sorts between `moc.elpmaxe.`
+/// and `moc.elpmaxe/`.
+pub fn get_visits_for_host(
+    db: &PlacesDb,
+    host: &str,
+    include_subdomains: bool,
+) -> Result<Vec<HistoryVisitInfo>> {
+    // Normalize (and punycode) the host the same way we do when storing it.
+    let host = url::Host::parse(host)?.to_string();
+    let host_filter = if include_subdomains {
+        "o.rev_host >= reverse_host(:host)
+           AND o.rev_host < substr(reverse_host(:host), 1, length(:host)) || '/'"
+    } else {
+        "o.rev_host = reverse_host(:host)"
+    };
+    let infos = db.query_rows_and_then_cached(
+        &format!(
+            "SELECT h.url, h.title, v.visit_date, v.visit_type, h.hidden, h.preview_image_url,
+                    v.is_local
+             FROM moz_origins o
+             JOIN moz_places h
+               ON h.origin_id = o.id
+             JOIN moz_historyvisits v
+               ON h.id = v.place_id
+             WHERE {host_filter}
+               AND NOT h.hidden
+             ORDER BY v.visit_date"
+        ),
+        rusqlite::named_params! {
+            ":host": host,
+        },
+        HistoryVisitInfo::from_row,
+    )?;
+    Ok(infos)
+}
+
+/// Fetches the title, preview image and visited flag for a batch of URLs, so
+/// that the awesomebar can populate all of its rows before rendering them
+/// instead of looking each one up on demand. The results are in the same order
+/// as `urls`; URLs we don't know about are skipped.
+pub fn prefetch_page_infos(db: &PlacesDb, urls: &[Url]) -> Result<Vec<PrefetchedPageInfo>> {
+    let mut infos = Vec::with_capacity(urls.len());
+    sql_support::each_chunk_mapped(
+        urls,
+        |url| url.as_str(),
+        |chunk, offset| -> Result<()> {
+            let values_with_idx = sql_support::repeat_display(chunk.len(), ",", |i, f| {
+                let url = &urls[i + offset];
+                write!(f, "({},{},?)", i + offset, hash::hash_url(url.as_str()))
+            });
+            let sql = format!(
+                "WITH to_fetch(fetch_url_index, url_hash, url) AS (VALUES {})
+                 SELECT f.fetch_url_index, h.url, h.title, h.preview_image_url,
+                        (h.visit_count_local + h.visit_count_remote) > 0 AS is_visited
+                 FROM moz_places h
+                 JOIN to_fetch f ON h.url_hash = f.url_hash
+                   AND h.url = f.url
+                 ORDER BY f.fetch_url_index",
+                values_with_idx
+            );
+            let mut stmt = db.prepare(&sql)?;
+            for info in stmt.query_and_then(
+                rusqlite::params_from_iter(chunk),
+                PrefetchedPageInfo::from_row,
+            )? {
+                infos.push(info?);
+            }
+            Ok(())
+        },
+    )?;
+    Ok(infos)
+}
+
 pub fn get_visit_count(db: &PlacesDb, exclude_types: VisitTransitionSet) -> Result<i64> {
     let count = if exclude_types.is_empty() {
         db.query_one::<i64>("SELECT COUNT(*) FROM moz_historyvisits")?
@@ -1526,6 +1606,99 @@ mod tests {
     use sync15::engine::CollSyncIds;
     use types::Timestamp;
 
+    #[test]
+    fn test_get_visits_for_host() {
+        let conn = PlacesDb::open_in_memory(ConnectionType::ReadWrite).expect("no memory db");
+        let to_add = [
+            ("https://example.com/", 1_000),
+            ("https://www.example.com/1", 2_000),
+            ("https://a.b.example.com/2", 3_000),
+            ("https://notexample.com/", 4_000),
+            ("https://example.com.evil.org/", 5_000),
+            ("https://example.org/", 6_000),
+        ];
+        for &(url, when) in &to_add {
+            apply_observation(
+                &conn,
+                VisitObservation::new(Url::parse(url).unwrap())
+                    .with_at(Timestamp(when))
+                    .with_visit_type(VisitType::Link),
+            )
+            .expect("Should apply visit");
+        }
+
+        let urls = |infos: Vec<HistoryVisitInfo>| {
+            infos
+                .into_iter()
+                .map(|info| info.url.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            urls(get_visits_for_host(&conn, "example.com", false).unwrap()),
+            vec!["https://example.com/"]
+        );
+        assert_eq!(
+            urls(get_visits_for_host(&conn, "EXAMPLE.com", true).unwrap()),
+            vec![
+                "https://example.com/",
+                "https://www.example.com/1",
+                "https://a.b.example.com/2",
+            ]
+        );
+        assert_eq!(
+            urls(get_visits_for_host(&conn, "b.example.com", true).unwrap()),
+            vec!["https://a.b.example.com/2"]
+        );
+        assert!(get_visits_for_host(&conn, "mozilla.org", true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prefetch_page_infos() {
+        let conn = PlacesDb::open_in_memory(ConnectionType::ReadWrite).expect("no memory db");
+        let visited = Url::parse("https://www.example.com/visited").unwrap();
+        let unvisited = Url::parse("https://www.example.com/unvisited").unwrap();
+        let unknown = Url::parse("https://www.example.com/unknown").unwrap();
+        let image = Url::parse("https://www.example.com/image.png").unwrap();
+        apply_observation(
+            &conn,
+            VisitObservation::new(visited.clone())
+                .with_title("Visited".to_string())
+                .with_preview_image_url(image.clone())
+                .with_visit_type(VisitType::Link),
+        )
+        .expect("Should apply visit");
+        // An observation without a visit type creates the page without a visit.
+        apply_observation(
+            &conn,
+            VisitObservation::new(unvisited.clone()).with_title("Unvisited".to_string()),
+        )
+        .expect("Should apply observation");
+
+        let infos =
+            prefetch_page_infos(&conn, &[unvisited.clone(), unknown, visited.clone()]).unwrap();
+        assert_eq!(
+            infos,
+            vec![
+                PrefetchedPageInfo {
+                    url: unvisited,
+                    title: Some("Unvisited".into()),
+                    preview_image_url: None,
+                    is_visited: false,
+                },
+                PrefetchedPageInfo {
+                    url: visited,
+                    title: Some("Visited".into()),
+                    preview_image_url: Some(image),
+                    is_visited: true,
+                },
+            ]
+        );
+        assert!(prefetch_page_infos(&conn, &[]).unwrap().is_empty());
+    }
+
     #[test]
     fn test_get_visited_urls() {
         use std::collections::HashSet;
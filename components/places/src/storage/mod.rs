@@ -13,6 +13,7 @@ pub mod tags;
 use crate::db::PlacesDb;
 use crate::error::{Error, InvalidPlaceInfo, Result};
 use crate::ffi::HistoryVisitInfo;
+use crate::ffi::PrefetchedPageInfo;
 use crate::ffi::TopFrecentSiteInfo;
 use crate::frecency::{calculate_frecency, DEFAULT_FRECENCY_SETTINGS};
 use crate::types::{SyncStatus, UnknownFields, VisitType};
@@ -229,6 +230,22 @@ impl TopFrecentSiteInfo {
     }
 }
 
+impl PrefetchedPageInfo {
+    pub(crate) fn from_row(row: &rusqlite::Row<'_>) -> Result<Self> {
+        let url: String = row.get("url")?;
+        let preview_image_url: Option<String> = row.get("preview_image_url")?;
+        Ok(Self {
+            url: Url::parse(&url)?,
+            title: row.get("title")?,
+            preview_image_url: match preview_image_url {
+                Some(s) => Some(Url::parse(&s)?),
+                None => None,
+            },
+            is_visited: row.get("is_visited")?,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct RunMaintenanceMetrics {
     pub pruned_visits: bool,
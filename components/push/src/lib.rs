@@ -181,6 +181,10 @@
 //!```
 
 uniffi::include_scaffolding!("push");
+
+pub const UNIFFI_NAMESPACE: &str = "push";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // All implementation detail lives in the `internal` module
 mod internal;
 use std::{collections::HashMap, sync::Mutex};
@@ -12,7 +12,7 @@ use std::{
     time::{Duration, Instant},
 };
 use url::Url;
-use viaduct::{Request, Response};
+use viaduct::{header_names, status_codes, Request, Response};
 
 const HEADER_BACKOFF: &str = "Backoff";
 const HEADER_ETAG: &str = "ETag";
@@ -103,6 +103,24 @@ impl Client {
     /// Fetches a raw network [Response] for records from this client's
     /// collection with the given options.
     pub fn get_records_raw_with_options(&self, options: &GetItemsOptions) -> Result<Response> {
+        self.make_request(self.records_url(options)?)
+    }
+
+    /// Fetches a raw network [Response] for all records in this client's
+    /// collection, unless they haven't changed since the response with the
+    /// given `ETag`. Returns `None` if the server responded with a
+    /// `304 Not Modified`.
+    pub fn get_records_raw_if_none_match(&self, etag: &str) -> Result<Option<Response>> {
+        let url = self.records_url(&GetItemsOptions::new())?;
+        let req = Request::get(url).header(header_names::IF_NONE_MATCH, etag)?;
+        let resp = self.send_request(req)?;
+        if resp.status == status_codes::NOT_MODIFIED {
+            return Ok(None);
+        }
+        ensure_success(resp).map(Some)
+    }
+
+    fn records_url(&self, options: &GetItemsOptions) -> Result<Url> {
         let path = format!(
             "v1/buckets/{}/collections/{}/records",
             &self.bucket_name, &self.collection_name
@@ -111,7 +129,7 @@ impl Client {
         for (name, value) in options.iter_query_pairs() {
             url.query_pairs_mut().append_pair(&name, &value);
         }
-        self.make_request(url)
+        Ok(url)
     }
 
     /// Downloads an attachment from [attachment_location]. NOTE: there are no
@@ -148,24 +166,19 @@ impl Client {
     }
 
     fn make_request(&self, url: Url) -> Result<Response> {
+        ensure_success(self.send_request(Request::get(url))?)
+    }
+
+    fn send_request(&self, req: Request) -> Result<Response> {
         let mut current_remote_state = self.remote_state.lock();
         self.ensure_no_backoff(&mut current_remote_state.backoff)?;
         drop(current_remote_state);
 
-        let req = Request::get(url);
         let resp = req.send()?;
 
         let mut current_remote_state = self.remote_state.lock();
         self.handle_backoff_hint(&resp, &mut current_remote_state.backoff)?;
-
-        if resp.is_success() {
-            Ok(resp)
-        } else {
-            Err(RemoteSettingsError::ResponseError(format!(
-                "status code: {}",
-                resp.status
-            )))
-        }
+        Ok(resp)
     }
 
     fn ensure_no_backoff(&self, current_state: &mut BackoffState) -> Result<()> {
@@ -272,6 +285,17 @@ impl UniffiCustomTypeConverter for RsJsonObject {
     }
 }
 
+fn ensure_success(resp: Response) -> Result<Response> {
+    if resp.is_success() {
+        Ok(resp)
+    } else {
+        Err(RemoteSettingsError::ResponseError(format!(
+            "status code: {}",
+            resp.status
+        )))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct RemoteState {
     attachments_base_url: Option<Url>,
@@ -889,6 +913,50 @@ mod test {
         m.expect(1).assert();
     }
 
+    #[test]
+    fn test_if_none_match() {
+        viaduct_reqwest::use_reqwest_backend();
+        let not_modified_m = mock(
+            "GET",
+            "/v1/buckets/the-bucket/collections/the-collection/records",
+        )
+        .match_header("if-none-match", "\"1000\"")
+        .with_status(304)
+        .with_header("etag", "\"1000\"")
+        .create();
+        let modified_m = mock(
+            "GET",
+            "/v1/buckets/the-bucket/collections/the-collection/records",
+        )
+        .match_header("if-none-match", "\"900\"")
+        .with_body(response_body())
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"1000\"")
+        .create();
+        let config = RemoteSettingsConfig {
+            server: Some(RemoteSettingsServer::Custom {
+                url: mockito::server_url(),
+            }),
+            server_url: None,
+            collection_name: String::from("the-collection"),
+            bucket_name: Some(String::from("the-bucket")),
+        };
+        let http_client = Client::new(config).unwrap();
+
+        assert!(http_client
+            .get_records_raw_if_none_match("\"1000\"")
+            .unwrap()
+            .is_none());
+        let resp = http_client
+            .get_records_raw_if_none_match("\"900\"")
+            .unwrap()
+            .expect("Records should be returned when the ETag doesn't match");
+        assert_eq!(resp.headers.get("etag"), Some("\"1000\""));
+        not_modified_m.expect(1).assert();
+        modified_m.expect(1).assert();
+    }
+
     #[test]
     fn test_record_fields() {
         viaduct_reqwest::use_reqwest_backend();
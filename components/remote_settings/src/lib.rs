@@ -15,6 +15,9 @@ pub use config::{RemoteSettingsConfig, RemoteSettingsServer};
 
 uniffi::include_scaffolding!("remote_settings");
 
+pub const UNIFFI_NAMESPACE: &str = "remote_settings";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub struct RemoteSettings {
     pub config: RemoteSettingsConfig,
     client: Client,
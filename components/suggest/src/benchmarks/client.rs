@@ -5,7 +5,7 @@
 use crate::{rs, Result};
 use parking_lot::Mutex;
 use remote_settings::{Client, RemoteSettingsConfig};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Remotes settings client that runs during the benchmark warm-up phase.
 ///
@@ -46,6 +46,10 @@ impl rs::Client for RemoteSettingsWarmUpClient {
             .insert(request, response.clone());
         Ok(response)
     }
+
+    fn get_record_ids(&self) -> Result<HashSet<String>> {
+        <Client as rs::Client>::get_record_ids(&self.client)
+    }
 }
 
 #[derive(Clone)]
@@ -61,6 +65,16 @@ impl rs::Client for RemoteSettingsBenchmarkClient {
             .unwrap_or_else(|| panic!("options not found: {request:?}"))
             .clone())
     }
+
+    fn get_record_ids(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .get_records_responses
+            .values()
+            .flatten()
+            .filter(|record| !record.deleted)
+            .map(|record| record.id.clone())
+            .collect())
+    }
 }
 
 impl From<RemoteSettingsWarmUpClient> for RemoteSettingsBenchmarkClient {
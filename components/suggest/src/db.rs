@@ -3,7 +3,12 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, path::Path, sync::Arc};
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use interrupt_support::{SqlInterruptHandle, SqlInterruptScope};
 use parking_lot::{Mutex, MutexGuard};
@@ -22,12 +27,12 @@ use crate::{
     provider::SuggestionProvider,
     rs::{
         DownloadedAmoSuggestion, DownloadedAmpSuggestion, DownloadedAmpWikipediaSuggestion,
-        DownloadedMdnSuggestion, DownloadedPocketSuggestion, DownloadedWeatherData,
-        DownloadedWikipediaSuggestion, Record, SuggestRecordId,
+        DownloadedExposureSuggestion, DownloadedMdnSuggestion, DownloadedPocketSuggestion,
+        DownloadedWeatherData, DownloadedWikipediaSuggestion, Record, SuggestRecordId,
     },
     schema::{clear_database, SuggestConnectionInitializer},
-    suggestion::{cook_raw_suggestion_url, AmpSuggestionType, Suggestion},
-    Result, SuggestionQuery,
+    suggestion::{cook_raw_suggestion_url, AmpSuggestionType, Suggestion, SuggestionInteractions},
+    QueryWithBudgetResult, Result, SuggestionQuery,
 };
 
 /// The metadata key whose value is a JSON string encoding a
@@ -37,10 +42,23 @@ pub const GLOBAL_CONFIG_META_KEY: &str = "global_config";
 /// `SuggestProviderConfig`, which contains per-provider configuration data. The
 /// full key is this prefix plus the `SuggestionProvider` value as a u8.
 pub const PROVIDER_CONFIG_META_KEY_PREFIX: &str = "provider_config_";
+/// Prefix of metadata keys which are set when a provider's suggestions are
+/// cleared, until the provider is ingested again. The full key is this prefix
+/// plus the `SuggestionProvider` value as a u8.
+pub const CLEARED_PROVIDER_META_KEY_PREFIX: &str = "cleared_provider_";
 
 // Default value when Suggestion does not have a value for score
 pub const DEFAULT_SUGGESTION_SCORE: f64 = 0.2;
 
+/// The number of pages freed by each step of an incremental vacuum. We check
+/// the time budget between steps.
+const INCREMENTAL_VACUUM_PAGES: u32 = 256;
+
+/// Vacuuming only starts once the database has at least this many free pages
+/// (4 MiB with the default page size), so maintenance doesn't spend its time
+/// returning a few kilobytes to the OS.
+pub(crate) const MIN_FREE_PAGES_TO_VACUUM: u64 = 1024;
+
 /// The database connection type.
 #[derive(Clone, Copy)]
 pub(crate) enum ConnectionType {
@@ -127,6 +145,43 @@ impl SuggestDb {
             scope: self.interrupt_handle.begin_interrupt_scope()?,
         })
     }
+
+    /// Returns unused pages in the database file to the OS, a few at a time,
+    /// until there are none left or `deadline` passes. Returns the number of
+    /// pages freed, and whether all of them were. Databases with fewer than
+    /// [MIN_FREE_PAGES_TO_VACUUM] free pages aren't vacuumed.
+    ///
+    /// Vacuuming can't run inside a transaction, so this doesn't use `write()`.
+    pub fn incremental_vacuum(&self, deadline: Option<Instant>) -> Result<(u64, bool)> {
+        let conn = self.conn.lock();
+        let scope = self.interrupt_handle.begin_interrupt_scope()?;
+        if conn.query_one::<u64>("PRAGMA freelist_count")? < MIN_FREE_PAGES_TO_VACUUM {
+            return Ok((0, true));
+        }
+        let mut freed = 0;
+        loop {
+            scope.err_if_interrupted()?;
+            let free_pages: u64 = conn.query_one("PRAGMA freelist_count")?;
+            if free_pages == 0 {
+                return Ok((freed, true));
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok((freed, false));
+            }
+            // 2 is `INCREMENTAL`.
+            if conn.query_one::<u32>("PRAGMA auto_vacuum")? != 2 {
+                // Databases created before we used incremental vacuuming need
+                // a full vacuum to switch modes, which frees every page. This
+                // only happens once.
+                conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+            } else {
+                conn.execute_batch(&format!(
+                    "PRAGMA incremental_vacuum({INCREMENTAL_VACUUM_PAGES})"
+                ))?;
+            }
+            freed += free_pages.saturating_sub(conn.query_one("PRAGMA freelist_count")?);
+        }
+    }
 }
 
 pub(crate) struct WriteScope<'a> {
@@ -199,34 +254,51 @@ impl<'a> SuggestDao<'a> {
 
     /// Fetches suggestions that match the given query from the database.
     pub fn fetch_suggestions(&self, query: &SuggestionQuery) -> Result<Vec<Suggestion>> {
-        let unique_providers = query.providers.iter().collect::<HashSet<_>>();
-        unique_providers
-            .iter()
-            .try_fold(vec![], |mut acc, provider| {
-                let suggestions = match provider {
-                    SuggestionProvider::Amp => {
-                        self.fetch_amp_suggestions(query, AmpSuggestionType::Desktop)
-                    }
-                    SuggestionProvider::AmpMobile => {
-                        self.fetch_amp_suggestions(query, AmpSuggestionType::Mobile)
-                    }
-                    SuggestionProvider::Wikipedia => self.fetch_wikipedia_suggestions(query),
-                    SuggestionProvider::Amo => self.fetch_amo_suggestions(query),
-                    SuggestionProvider::Pocket => self.fetch_pocket_suggestions(query),
-                    SuggestionProvider::Yelp => self.fetch_yelp_suggestions(query),
-                    SuggestionProvider::Mdn => self.fetch_mdn_suggestions(query),
-                    SuggestionProvider::Weather => self.fetch_weather_suggestions(query),
-                }?;
-                acc.extend(suggestions);
-                Ok(acc)
-            })
-            .map(|mut suggestions| {
-                suggestions.sort();
-                if let Some(limit) = query.limit.and_then(|limit| usize::try_from(limit).ok()) {
-                    suggestions.truncate(limit);
+        Ok(self.fetch_suggestions_with_budget(query)?.suggestions)
+    }
+
+    /// Fetches suggestions that match the given query from the database,
+    /// stopping once the query's time budget is used up. Providers are queried
+    /// in the order they're listed in the query; any which weren't queried are
+    /// returned in `timed_out_providers`.
+    pub fn fetch_suggestions_with_budget(
+        &self,
+        query: &SuggestionQuery,
+    ) -> Result<QueryWithBudgetResult> {
+        let started_at = Instant::now();
+        let budget = query.time_budget_ms.map(Duration::from_millis);
+        let mut seen = HashSet::new();
+        let mut suggestions = vec![];
+        let mut timed_out_providers = vec![];
+        for provider in query.providers.iter().filter(|p| seen.insert(*p)) {
+            if budget.is_some_and(|budget| started_at.elapsed() >= budget) {
+                timed_out_providers.push(*provider);
+                continue;
+            }
+            suggestions.extend(match provider {
+                SuggestionProvider::Amp => {
+                    self.fetch_amp_suggestions(query, AmpSuggestionType::Desktop)
+                }
+                SuggestionProvider::AmpMobile => {
+                    self.fetch_amp_suggestions(query, AmpSuggestionType::Mobile)
                 }
-                suggestions
-            })
+                SuggestionProvider::Wikipedia => self.fetch_wikipedia_suggestions(query),
+                SuggestionProvider::Amo => self.fetch_amo_suggestions(query),
+                SuggestionProvider::Pocket => self.fetch_pocket_suggestions(query),
+                SuggestionProvider::Yelp => self.fetch_yelp_suggestions(query),
+                SuggestionProvider::Mdn => self.fetch_mdn_suggestions(query),
+                SuggestionProvider::Weather => self.fetch_weather_suggestions(query),
+                SuggestionProvider::Exposure => self.fetch_exposure_suggestions(query),
+            }?);
+        }
+        suggestions.sort();
+        if let Some(limit) = query.limit.and_then(|limit| usize::try_from(limit).ok()) {
+            suggestions.truncate(limit);
+        }
+        Ok(QueryWithBudgetResult {
+            suggestions,
+            timed_out_providers,
+        })
     }
 
     /// Fetches Suggestions of type Amp provider that match the given query
@@ -665,10 +737,113 @@ impl<'a> SuggestDao<'a> {
             },
             |row| -> Result<Suggestion> {
                 Ok(Suggestion::Weather {
+                    city: None,
                     score: row.get::<_, f64>("score")?,
                 })
             },
         )?;
+        if !suggestions.is_empty() {
+            return Ok(suggestions);
+        }
+
+        // Otherwise, the query might name a city along with a full keyword,
+        // like "weather in berlin" or "berlin weather".
+        self.fetch_weather_suggestions_for_city(keyword_lowercased)
+    }
+
+    /// Fetches a weather suggestion for a query that's made up of a weather
+    /// keyword and the name of a city or region, in either order.
+    fn fetch_weather_suggestions_for_city(&self, query: &str) -> Result<Vec<Suggestion>> {
+        let keywords = self.conn.query_rows_and_then_cached(
+            r#"
+            SELECT
+              k.keyword,
+              s.score
+            FROM
+              suggestions s
+            JOIN
+              keywords k
+              ON k.suggestion_id = s.id
+            WHERE
+              s.provider = :provider
+            "#,
+            named_params! {
+                ":provider": SuggestionProvider::Weather
+            },
+            |row| -> Result<(String, f64)> { Ok((row.get("keyword")?, row.get("score")?)) },
+        )?;
+        for (keyword, score) in keywords {
+            let name = if let Some(rest) = query
+                .strip_prefix(keyword.as_str())
+                .and_then(|rest| rest.strip_prefix(' '))
+            {
+                let rest = rest.trim_start();
+                rest.strip_prefix("in ").unwrap_or(rest)
+            } else if let Some(rest) = query
+                .strip_suffix(keyword.as_str())
+                .and_then(|rest| rest.strip_suffix(' '))
+            {
+                rest
+            } else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            if let Some(city) = self.fetch_geonames(name)?.into_iter().next() {
+                return Ok(vec![Suggestion::Weather {
+                    city: Some(city),
+                    score,
+                }]);
+            }
+        }
+        Ok(vec![])
+    }
+
+    /// Fetches exposure suggestions for the types enabled in the query. At most
+    /// one suggestion is returned for each type, and only if the query
+    /// matches one of its keywords exactly.
+    pub fn fetch_exposure_suggestions(&self, query: &SuggestionQuery) -> Result<Vec<Suggestion>> {
+        let keyword_lowercased = &query.keyword.trim().to_lowercase();
+        let mut suggestions = vec![];
+        for suggestion_type in &query.exposure_suggestion_types {
+            self.scope.err_if_interrupted()?;
+            let suggestion = self.conn.try_query_row(
+                r#"
+                SELECT
+                  s.score
+                FROM
+                  suggestions s
+                JOIN
+                  exposure_custom_details d
+                  ON d.suggestion_id = s.id
+                JOIN
+                  keywords k
+                  ON k.suggestion_id = s.id
+                WHERE
+                  s.provider = :provider
+                  AND d.type = :type
+                  AND k.keyword = :keyword
+                ORDER BY
+                  s.score DESC
+                LIMIT 1
+                "#,
+                named_params! {
+                    ":provider": SuggestionProvider::Exposure,
+                    ":type": suggestion_type,
+                    ":keyword": keyword_lowercased,
+                },
+                |row| -> Result<Suggestion> {
+                    Ok(Suggestion::Exposure {
+                        suggestion_type: suggestion_type.clone(),
+                        score: row.get("score")?,
+                    })
+                },
+                true,
+            )?;
+            suggestions.extend(suggestion);
+        }
         Ok(suggestions)
     }
 
@@ -904,6 +1079,40 @@ impl<'a> SuggestDao<'a> {
         Ok(())
     }
 
+    /// Inserts all suggestions from a downloaded exposure attachment into the
+    /// database.
+    pub fn insert_exposure_suggestions(
+        &mut self,
+        record_id: &SuggestRecordId,
+        suggestion_type: &str,
+        suggestions: &[DownloadedExposureSuggestion],
+    ) -> Result<()> {
+        let mut suggestion_insert = SuggestionInsertStatement::new(self.conn)?;
+        let mut keyword_insert = KeywordInsertStatement::new(self.conn)?;
+        for suggestion in suggestions {
+            self.scope.err_if_interrupted()?;
+            let suggestion_id = suggestion_insert.execute(
+                record_id,
+                "",
+                "",
+                DEFAULT_SUGGESTION_SCORE,
+                SuggestionProvider::Exposure,
+            )?;
+            self.conn.execute_cached(
+                "INSERT INTO exposure_custom_details(suggestion_id, type)
+                 VALUES(:suggestion_id, :type)",
+                named_params! {
+                    ":suggestion_id": suggestion_id,
+                    ":type": suggestion_type,
+                },
+            )?;
+            for (index, keyword) in suggestion.keywords.iter().enumerate() {
+                keyword_insert.execute(suggestion_id, &keyword.to_lowercase(), None, index)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Inserts or replaces an icon for a suggestion into the database.
     pub fn put_icon(&mut self, icon_id: &str, data: &[u8], mimetype: &str) -> Result<()> {
         self.conn.execute(
@@ -942,6 +1151,51 @@ impl<'a> SuggestDao<'a> {
         Ok(())
     }
 
+    pub fn increment_impressions(&self, url: &str) -> Result<()> {
+        self.conn.execute_cached(
+            "INSERT INTO suggestion_interactions(url, impressions)
+             VALUES(:url, 1)
+             ON CONFLICT(url) DO UPDATE SET impressions = impressions + 1",
+            named_params! {
+                ":url": url,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn increment_clicks(&self, url: &str) -> Result<()> {
+        self.conn.execute_cached(
+            "INSERT INTO suggestion_interactions(url, clicks)
+             VALUES(:url, 1)
+             ON CONFLICT(url) DO UPDATE SET clicks = clicks + 1",
+            named_params! {
+                ":url": url,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn get_interactions(&self, url: &str) -> Result<SuggestionInteractions> {
+        Ok(self
+            .conn
+            .try_query_row(
+                "SELECT impressions, clicks
+                 FROM suggestion_interactions
+                 WHERE url = :url",
+                named_params! {
+                    ":url": url,
+                },
+                |row| -> Result<_> {
+                    Ok(SuggestionInteractions {
+                        impressions: row.get("impressions")?,
+                        clicks: row.get("clicks")?,
+                    })
+                },
+                true,
+            )?
+            .unwrap_or_default())
+    }
+
     /// Deletes all suggestions associated with a Remote Settings record from
     /// the database.
     pub fn drop_suggestions(&mut self, record_id: &SuggestRecordId) -> Result<()> {
@@ -965,6 +1219,10 @@ impl<'a> SuggestDao<'a> {
             "DELETE FROM yelp_custom_details WHERE record_id = :record_id",
             named_params! { ":record_id": record_id.as_str() },
         )?;
+        self.conn.execute_cached(
+            "DELETE FROM geonames WHERE record_id = :record_id",
+            named_params! { ":record_id": record_id.as_str() },
+        )?;
         Ok(())
     }
 
@@ -977,11 +1235,78 @@ impl<'a> SuggestDao<'a> {
         Ok(())
     }
 
+    /// Returns the IDs of the Remote Settings records that we've stored
+    /// suggestions or icons from.
+    pub fn get_ingested_record_ids(&self) -> Result<Vec<SuggestRecordId<'static>>> {
+        self.conn.query_rows_and_then(
+            "SELECT record_id FROM suggestions
+             UNION SELECT record_id FROM yelp_subjects
+             UNION SELECT record_id FROM yelp_modifiers
+             UNION SELECT record_id FROM yelp_location_signs
+             UNION SELECT record_id FROM yelp_custom_details
+             UNION SELECT record_id FROM geonames
+             UNION SELECT 'icon-' || id FROM icons",
+            (),
+            |row| -> Result<_> { Ok(SuggestRecordId::from(row.get::<_, String>(0)?)) },
+        )
+    }
+
     /// Clears the database, removing all suggestions, icons, and metadata.
     pub fn clear(&mut self) -> Result<()> {
         Ok(clear_database(self.conn)?)
     }
 
+    /// Removes all suggestions and configuration for a provider.
+    ///
+    /// The last ingest times of the record types that only this provider uses
+    /// are removed too. Record types shared with other providers keep theirs,
+    /// so ingesting the other providers won't bring this provider's
+    /// suggestions back; instead, we remember that the provider was cleared,
+    /// and [Self::reset_cleared_provider] downloads them again when the
+    /// provider itself is ingested.
+    pub fn clear_provider(&mut self, provider: SuggestionProvider) -> Result<()> {
+        self.conn.execute_cached(
+            "DELETE FROM suggestions WHERE provider = :provider",
+            named_params! { ":provider": provider },
+        )?;
+        match provider {
+            SuggestionProvider::Yelp => self.conn.execute_batch(
+                "DELETE FROM yelp_subjects;
+                 DELETE FROM yelp_modifiers;
+                 DELETE FROM yelp_location_signs;
+                 DELETE FROM yelp_custom_details;",
+            )?,
+            SuggestionProvider::Weather => self.conn.execute_batch("DELETE FROM geonames;")?,
+            _ => (),
+        }
+        self.delete_meta(&provider_config_meta_key(provider))?;
+        for record_type in provider.records_for_provider() {
+            let shared = SuggestionProvider::all()
+                .iter()
+                .filter(|other| **other != provider)
+                .any(|other| other.records_for_provider().contains(&record_type));
+            if !shared {
+                self.delete_meta(&record_type.last_ingest_meta_key())?;
+            }
+        }
+        self.put_meta(&cleared_provider_meta_key(provider), 1)?;
+        Ok(())
+    }
+
+    /// Removes the last ingest times of all the record types a provider uses
+    /// if its suggestions were cleared, so that ingesting it downloads the
+    /// records it shares with other providers again.
+    pub fn reset_cleared_provider(&mut self, provider: SuggestionProvider) -> Result<()> {
+        let key = cleared_provider_meta_key(provider);
+        if self.get_meta::<u8>(&key)?.is_none() {
+            return Ok(());
+        }
+        for record_type in provider.records_for_provider() {
+            self.delete_meta(&record_type.last_ingest_meta_key())?;
+        }
+        self.delete_meta(&key)
+    }
+
     /// Returns the value associated with a metadata key.
     pub fn get_meta<T: FromSql>(&self, key: &str) -> Result<Option<T>> {
         Ok(self.conn.try_query_one(
@@ -1000,6 +1325,15 @@ impl<'a> SuggestDao<'a> {
         Ok(())
     }
 
+    /// Deletes the value for a metadata key.
+    pub fn delete_meta(&mut self, key: &str) -> Result<()> {
+        self.conn.execute_cached(
+            "DELETE FROM meta WHERE key = :key",
+            named_params! { ":key": key },
+        )?;
+        Ok(())
+    }
+
     /// Updates the last ingest timestamp if the given last modified time is
     /// newer than the existing one recorded.
     pub fn put_last_ingest_if_newer(
@@ -1335,3 +1669,7 @@ impl<'conn> PrefixKeywordInsertStatement<'conn> {
 fn provider_config_meta_key(provider: SuggestionProvider) -> String {
     format!("{}{}", PROVIDER_CONFIG_META_KEY_PREFIX, provider as u8)
 }
+
+fn cleared_provider_meta_key(provider: SuggestionProvider) -> String {
+    format!("{}{}", CLEARED_PROVIDER_META_KEY_PREFIX, provider as u8)
+}
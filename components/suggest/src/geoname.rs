@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use rusqlite::named_params;
+use sql_support::ConnExt;
+
+use crate::{
+    db::SuggestDao,
+    rs::{DownloadedGeoname, SuggestRecordId},
+    Result,
+};
+
+/// A city or region from the geonames data in Remote Settings.
+///
+/// Weather suggestions include the geoname the user asked about, so the app
+/// can fetch the forecast for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geoname {
+    /// The ID of the geoname in the GeoNames database.
+    pub geoname_id: i64,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The ISO 3166-1 alpha-2 code of the country the geoname is in.
+    pub country_code: String,
+    /// The GeoNames code of the geoname's first-level administrative
+    /// division, like a US state.
+    pub admin1_code: String,
+    pub population: u64,
+}
+
+impl<'a> SuggestDao<'a> {
+    /// Inserts the geonames from a geonames attachment into the database.
+    pub(crate) fn insert_geonames(
+        &mut self,
+        record_id: &SuggestRecordId,
+        geonames: &[DownloadedGeoname],
+    ) -> Result<()> {
+        for geoname in geonames {
+            self.scope.err_if_interrupted()?;
+            self.conn.execute_cached(
+                "INSERT OR REPLACE INTO geonames(
+                     id, record_id, name, latitude, longitude, country_code, admin1_code,
+                     population
+                 )
+                 VALUES(
+                     :id, :record_id, :name, :latitude, :longitude, :country_code,
+                     :admin1_code, :population
+                 )",
+                named_params! {
+                    ":id": geoname.id,
+                    ":record_id": record_id.as_str(),
+                    ":name": geoname.name,
+                    ":latitude": geoname.latitude,
+                    ":longitude": geoname.longitude,
+                    ":country_code": geoname.country_code,
+                    ":admin1_code": geoname.admin1_code,
+                    ":population": geoname.population,
+                },
+            )?;
+            for name in std::iter::once(&geoname.name).chain(&geoname.alternate_names) {
+                self.conn.execute_cached(
+                    "INSERT OR IGNORE INTO geonames_alternates(name, geoname_id)
+                     VALUES(:name, :geoname_id)",
+                    named_params! {
+                        ":name": name.to_lowercase(),
+                        ":geoname_id": geoname.id,
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the geonames whose name or alternate names match `name`
+    /// exactly, ignoring case. The most populous geonames are returned first.
+    pub(crate) fn fetch_geonames(&self, name: &str) -> Result<Vec<Geoname>> {
+        self.conn.query_rows_and_then_cached(
+            "SELECT
+               g.id, g.name, g.latitude, g.longitude, g.country_code, g.admin1_code,
+               g.population
+             FROM
+               geonames g
+             JOIN
+               geonames_alternates a
+               ON a.geoname_id = g.id
+             WHERE
+               a.name = :name
+             ORDER BY
+               g.population DESC, g.id ASC",
+            named_params! {
+                ":name": name.to_lowercase(),
+            },
+            |row| -> Result<Geoname> {
+                Ok(Geoname {
+                    geoname_id: row.get("id")?,
+                    name: row.get("name")?,
+                    latitude: row.get("latitude")?,
+                    longitude: row.get("longitude")?,
+                    country_code: row.get("country_code")?,
+                    admin1_code: row.get("admin1_code")?,
+                    population: row.get("population")?,
+                })
+            },
+        )
+    }
+}
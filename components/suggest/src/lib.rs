@@ -9,6 +9,7 @@ pub mod benchmarks;
 mod config;
 mod db;
 mod error;
+mod geoname;
 mod keyword;
 pub mod pocket;
 mod provider;
@@ -23,12 +24,19 @@ mod yelp;
 
 pub use config::{SuggestGlobalConfig, SuggestProviderConfig};
 pub use error::SuggestApiError;
+pub use geoname::Geoname;
 pub use provider::SuggestionProvider;
-pub use query::SuggestionQuery;
-pub use store::{InterruptKind, SuggestIngestionConstraints, SuggestStore, SuggestStoreBuilder};
-pub use suggestion::{raw_suggestion_url_matches, Suggestion};
+pub use query::{QueryWithBudgetResult, SuggestionQuery};
+pub use store::{
+    InterruptKind, SuggestIngestionConstraints, SuggestMaintenanceResult, SuggestStore,
+    SuggestStoreBuilder,
+};
+pub use suggestion::{raw_suggestion_url_matches, Suggestion, SuggestionInteractions};
 
 pub(crate) type Result<T> = std::result::Result<T, error::Error>;
 pub type SuggestApiResult<T> = std::result::Result<T, error::SuggestApiError>;
 
 uniffi::include_scaffolding!("suggest");
+
+pub const UNIFFI_NAMESPACE: &str = "suggest";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
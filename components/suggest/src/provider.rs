@@ -22,6 +22,7 @@ pub enum SuggestionProvider {
     Mdn = 6,
     Weather = 7,
     AmpMobile = 8,
+    Exposure = 9,
 }
 
 impl FromSql for SuggestionProvider {
@@ -35,7 +36,7 @@ impl FromSql for SuggestionProvider {
 }
 
 impl SuggestionProvider {
-    pub fn all() -> [Self; 8] {
+    pub fn all() -> [Self; 9] {
         [
             Self::Amp,
             Self::Wikipedia,
@@ -45,6 +46,7 @@ impl SuggestionProvider {
             Self::Mdn,
             Self::Weather,
             Self::AmpMobile,
+            Self::Exposure,
         ]
     }
 
@@ -58,6 +60,7 @@ impl SuggestionProvider {
             5 => Some(SuggestionProvider::Yelp),
             6 => Some(SuggestionProvider::Mdn),
             7 => Some(SuggestionProvider::Weather),
+            9 => Some(SuggestionProvider::Exposure),
             _ => None,
         }
     }
@@ -95,7 +98,11 @@ impl SuggestionProvider {
                 vec![SuggestRecordType::Mdn, SuggestRecordType::GlobalConfig]
             }
             SuggestionProvider::Weather => {
-                vec![SuggestRecordType::Weather, SuggestRecordType::GlobalConfig]
+                vec![
+                    SuggestRecordType::Weather,
+                    SuggestRecordType::Geonames,
+                    SuggestRecordType::GlobalConfig,
+                ]
             }
             SuggestionProvider::AmpMobile => {
                 vec![
@@ -105,6 +112,9 @@ impl SuggestionProvider {
                     SuggestRecordType::GlobalConfig,
                 ]
             }
+            SuggestionProvider::Exposure => {
+                vec![SuggestRecordType::Exposure, SuggestRecordType::GlobalConfig]
+            }
         }
     }
 }
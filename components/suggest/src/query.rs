@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use crate::SuggestionProvider;
+use crate::{Suggestion, SuggestionProvider};
 
 /// A query for suggestions to show in the address bar.
 #[derive(Clone, Debug, Default)]
@@ -10,6 +10,28 @@ pub struct SuggestionQuery {
     pub keyword: String,
     pub providers: Vec<SuggestionProvider>,
     pub limit: Option<i32>,
+    /// The time, in milliseconds, the query is allowed to spend fetching
+    /// suggestions. Once it's used up, no more providers are queried. Set to
+    /// [`None`] for "no limit".
+    ///
+    /// Providers are queried in the order they're listed in `providers`, so
+    /// the most important providers should come first.
+    pub time_budget_ms: Option<u64>,
+    /// The types of exposure suggestions to match, when
+    /// [`SuggestionProvider::Exposure`] is one of the `providers`. Exposure
+    /// suggestions of other types are never returned.
+    pub exposure_suggestion_types: Vec<String>,
+}
+
+/// The result of a query which may have run out of time before querying every
+/// provider.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryWithBudgetResult {
+    /// Suggestions from the providers which were queried.
+    pub suggestions: Vec<Suggestion>,
+    /// Providers which weren't queried because the query's time budget was
+    /// used up.
+    pub timed_out_providers: Vec<SuggestionProvider>,
 }
 
 impl SuggestionQuery {
@@ -18,6 +40,8 @@ impl SuggestionQuery {
             keyword: keyword.to_string(),
             providers: Vec::from(SuggestionProvider::all()),
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -26,6 +50,8 @@ impl SuggestionQuery {
             keyword: keyword.to_string(),
             providers,
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -44,6 +70,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Amp],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -52,6 +80,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Wikipedia],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -60,6 +90,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::AmpMobile],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -68,6 +100,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Amo],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -76,6 +110,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Pocket],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -84,6 +120,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Yelp],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -92,6 +130,8 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Mdn],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
         }
     }
 
@@ -100,6 +140,18 @@ impl SuggestionQuery {
             keyword: keyword.into(),
             providers: vec![SuggestionProvider::Weather],
             limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
+        }
+    }
+
+    pub fn exposure(keyword: &str, suggestion_types: &[&str]) -> Self {
+        Self {
+            keyword: keyword.into(),
+            providers: vec![SuggestionProvider::Exposure],
+            limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: suggestion_types.iter().map(|s| s.to_string()).collect(),
         }
     }
 
@@ -31,7 +31,7 @@
 //!     the new suggestion in their results, and return `Suggestion::T` variants
 //!     as needed.
 
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, collections::HashSet, fmt};
 
 use remote_settings::{Attachment, GetItemsOptions, RemoteSettingsRecord, RsJsonObject, SortOrder};
 use serde::{Deserialize, Deserializer};
@@ -49,7 +49,7 @@ pub(crate) const SUGGESTIONS_PER_ATTACHMENT: u64 = 200;
 
 /// A list of default record types to download if nothing is specified.
 /// This currently defaults to all of the record types.
-pub(crate) const DEFAULT_RECORDS_TYPES: [SuggestRecordType; 9] = [
+pub(crate) const DEFAULT_RECORDS_TYPES: [SuggestRecordType; 11] = [
     SuggestRecordType::Icon,
     SuggestRecordType::AmpWikipedia,
     SuggestRecordType::Amo,
@@ -59,6 +59,8 @@ pub(crate) const DEFAULT_RECORDS_TYPES: [SuggestRecordType; 9] = [
     SuggestRecordType::Weather,
     SuggestRecordType::GlobalConfig,
     SuggestRecordType::AmpMobile,
+    SuggestRecordType::Geonames,
+    SuggestRecordType::Exposure,
 ];
 
 /// A trait for a client that downloads suggestions from Remote Settings.
@@ -67,6 +69,10 @@ pub(crate) const DEFAULT_RECORDS_TYPES: [SuggestRecordType; 9] = [
 pub(crate) trait Client {
     /// Fetch a list of records and attachment data
     fn get_records(&self, request: RecordRequest) -> Result<Vec<Record>>;
+
+    /// Fetch the IDs of every record currently in the collection, without
+    /// downloading any attachments.
+    fn get_record_ids(&self) -> Result<HashSet<String>>;
 }
 
 impl Client for remote_settings::Client {
@@ -85,6 +91,16 @@ impl Client for remote_settings::Client {
             })
             .collect()
     }
+
+    fn get_record_ids(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .get_records()?
+            .records
+            .into_iter()
+            .filter(|record| !record.deleted)
+            .map(|record| record.id)
+            .collect())
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
@@ -180,6 +196,10 @@ pub(crate) enum SuggestRecord {
     GlobalConfig(DownloadedGlobalConfig),
     #[serde(rename = "amp-mobile-suggestions")]
     AmpMobile,
+    #[serde(rename = "geonames")]
+    Geonames,
+    #[serde(rename = "exposure-suggestions")]
+    Exposure(DownloadedExposureRecord),
 }
 
 /// Enum for the different record types that can be consumed.
@@ -196,6 +216,8 @@ pub enum SuggestRecordType {
     Weather,
     GlobalConfig,
     AmpMobile,
+    Geonames,
+    Exposure,
 }
 
 impl From<SuggestRecord> for SuggestRecordType {
@@ -210,6 +232,8 @@ impl From<SuggestRecord> for SuggestRecordType {
             SuggestRecord::Yelp => Self::Yelp,
             SuggestRecord::GlobalConfig(_) => Self::GlobalConfig,
             SuggestRecord::AmpMobile => Self::AmpMobile,
+            SuggestRecord::Geonames => Self::Geonames,
+            SuggestRecord::Exposure(_) => Self::Exposure,
         }
     }
 }
@@ -226,6 +250,8 @@ impl fmt::Display for SuggestRecordType {
             Self::Weather => write!(f, "weather"),
             Self::GlobalConfig => write!(f, "configuration"),
             Self::AmpMobile => write!(f, "amp-mobile-suggestions"),
+            Self::Geonames => write!(f, "geonames"),
+            Self::Exposure => write!(f, "exposure-suggestions"),
         }
     }
 }
@@ -491,6 +517,32 @@ pub(crate) struct DownloadedWeatherDataInner {
     pub score: Option<f64>,
 }
 
+/// A city or region to ingest from a geonames attachment
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct DownloadedGeoname {
+    pub id: i64,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub country_code: String,
+    pub admin1_code: String,
+    pub population: u64,
+    #[serde(default)]
+    pub alternate_names: Vec<String>,
+}
+
+/// The type of the exposure suggestions in an exposure record's attachment
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct DownloadedExposureRecord {
+    pub suggestion_type: String,
+}
+
+/// An exposure suggestion to ingest from an exposure attachment
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct DownloadedExposureSuggestion {
+    pub keywords: Vec<String>,
+}
+
 /// Global Suggest configuration data to ingest from a configuration record
 #[derive(Clone, Debug, Deserialize)]
 pub(crate) struct DownloadedGlobalConfig {
@@ -15,7 +15,7 @@ use sql_support::open_database::{self, ConnectionInitializer};
 ///     [`SuggestConnectionInitializer::upgrade_from`].
 ///    a. If suggestions should be re-ingested after the migration, call `clear_database()` inside
 ///       the migration.
-pub const VERSION: u32 = 19;
+pub const VERSION: u32 = 22;
 
 /// The current Suggest database schema.
 pub const SQL: &str = "
@@ -124,9 +124,43 @@ CREATE TABLE mdn_custom_details(
     FOREIGN KEY(suggestion_id) REFERENCES suggestions(id) ON DELETE CASCADE
 );
 
+CREATE TABLE exposure_custom_details(
+    suggestion_id INTEGER PRIMARY KEY,
+    type TEXT NOT NULL,
+    FOREIGN KEY(suggestion_id) REFERENCES suggestions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX exposure_custom_details_type ON exposure_custom_details(type);
+
 CREATE TABLE dismissed_suggestions (
     url TEXT PRIMARY KEY
 ) WITHOUT ROWID;
+
+CREATE TABLE suggestion_interactions(
+    url TEXT PRIMARY KEY,
+    impressions INTEGER NOT NULL DEFAULT 0,
+    clicks INTEGER NOT NULL DEFAULT 0
+) WITHOUT ROWID;
+
+CREATE TABLE geonames(
+    id INTEGER PRIMARY KEY,
+    record_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    latitude REAL NOT NULL,
+    longitude REAL NOT NULL,
+    country_code TEXT NOT NULL,
+    admin1_code TEXT NOT NULL,
+    population INTEGER NOT NULL
+);
+
+CREATE INDEX geonames_record_id ON geonames(record_id);
+
+-- Lowercased names that a geoname can be matched by, including its own name.
+CREATE TABLE geonames_alternates(
+    name TEXT NOT NULL,
+    geoname_id INTEGER NOT NULL REFERENCES geonames(id) ON DELETE CASCADE,
+    PRIMARY KEY (name, geoname_id)
+) WITHOUT ROWID;
 ";
 
 /// Initializes an SQLite connection to the Suggest database, performing
@@ -137,7 +171,12 @@ impl ConnectionInitializer for SuggestConnectionInitializer {
     const NAME: &'static str = "suggest db";
     const END_VERSION: u32 = VERSION;
 
-    fn prepare(&self, conn: &Connection, _db_empty: bool) -> open_database::Result<()> {
+    fn prepare(&self, conn: &Connection, db_empty: bool) -> open_database::Result<()> {
+        if db_empty {
+            // This only takes effect before any tables are created. See
+            // `SuggestDb::incremental_vacuum()` for existing databases.
+            conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL;")?;
+        }
         let initial_pragmas = "
             -- Use in-memory storage for TEMP tables.
             PRAGMA temp_store = 2;
@@ -193,6 +232,51 @@ CREATE TABLE IF NOT EXISTS dismissed_suggestions (
                 )?;
                 Ok(())
             }
+            19 => {
+                tx.execute_batch(
+                    "
+CREATE TABLE suggestion_interactions(
+    url TEXT PRIMARY KEY,
+    impressions INTEGER NOT NULL DEFAULT 0,
+    clicks INTEGER NOT NULL DEFAULT 0
+) WITHOUT ROWID;",
+                )?;
+                Ok(())
+            }
+            20 => {
+                tx.execute_batch(
+                    "
+CREATE TABLE geonames(
+    id INTEGER PRIMARY KEY,
+    record_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    latitude REAL NOT NULL,
+    longitude REAL NOT NULL,
+    country_code TEXT NOT NULL,
+    admin1_code TEXT NOT NULL,
+    population INTEGER NOT NULL
+);
+CREATE INDEX geonames_record_id ON geonames(record_id);
+CREATE TABLE geonames_alternates(
+    name TEXT NOT NULL,
+    geoname_id INTEGER NOT NULL REFERENCES geonames(id) ON DELETE CASCADE,
+    PRIMARY KEY (name, geoname_id)
+) WITHOUT ROWID;",
+                )?;
+                Ok(())
+            }
+            21 => {
+                tx.execute_batch(
+                    "
+CREATE TABLE exposure_custom_details(
+    suggestion_id INTEGER PRIMARY KEY,
+    type TEXT NOT NULL,
+    FOREIGN KEY(suggestion_id) REFERENCES suggestions(id) ON DELETE CASCADE
+);
+CREATE INDEX exposure_custom_details_type ON exposure_custom_details(type);",
+                )?;
+                Ok(())
+            }
             _ => Err(open_database::Error::IncompatibleVersion(version)),
         }
     }
@@ -209,6 +293,7 @@ pub fn clear_database(db: &Connection) -> rusqlite::Result<()> {
         DELETE FROM yelp_modifiers;
         DELETE FROM yelp_location_signs;
         DELETE FROM yelp_custom_details;
+        DELETE FROM geonames;
         ",
     )
 }
@@ -7,6 +7,7 @@ use std::{
     collections::BTreeSet,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use error_support::{breadcrumb, handle_error};
@@ -25,7 +26,8 @@ use crate::{
         Client, Record, RecordRequest, SuggestAttachment, SuggestRecord, SuggestRecordId,
         SuggestRecordType, DEFAULT_RECORDS_TYPES, REMOTE_SETTINGS_COLLECTION,
     },
-    Result, SuggestApiResult, Suggestion, SuggestionQuery,
+    QueryWithBudgetResult, Result, SuggestApiResult, Suggestion, SuggestionInteractions,
+    SuggestionQuery,
 };
 
 /// Builder for [SuggestStore]
@@ -180,6 +182,17 @@ impl SuggestStore {
         self.inner.query(query)
     }
 
+    /// Queries the database for suggestions, like [`Self::query`], but also
+    /// reports the providers that weren't queried because the query's
+    /// `time_budget_ms` was used up.
+    #[handle_error(Error)]
+    pub fn query_with_budget(
+        &self,
+        query: SuggestionQuery,
+    ) -> SuggestApiResult<QueryWithBudgetResult> {
+        self.inner.query_with_budget(query)
+    }
+
     /// Dismiss a suggestion
     ///
     /// Dismissed suggestions will not be returned again
@@ -196,6 +209,36 @@ impl SuggestStore {
         self.inner.clear_dismissed_suggestions()
     }
 
+    /// Records that a suggestion was shown to the user.
+    ///
+    /// Returns the URL the app should ping to report the impression, with its
+    /// timestamp filled in, if the suggestion's provider has one.
+    #[handle_error(Error)]
+    pub fn record_impression(&self, suggestion: Suggestion) -> SuggestApiResult<Option<String>> {
+        self.inner.record_impression(&suggestion)
+    }
+
+    /// Records that the user picked a suggestion.
+    ///
+    /// Returns the URL the app should ping to report the click, with its
+    /// timestamp filled in, if the suggestion's provider has one.
+    #[handle_error(Error)]
+    pub fn record_click(&self, suggestion: Suggestion) -> SuggestApiResult<Option<String>> {
+        self.inner.record_click(&suggestion)
+    }
+
+    /// Returns how many impressions and clicks were recorded for a suggestion.
+    ///
+    /// As with [SuggestStore::dismiss_suggestion], in the case of AMP
+    /// suggestions this should be the raw URL.
+    #[handle_error(Error)]
+    pub fn fetch_interactions(
+        &self,
+        raw_suggestion_url: String,
+    ) -> SuggestApiResult<SuggestionInteractions> {
+        self.inner.fetch_interactions(&raw_suggestion_url)
+    }
+
     /// Interrupts any ongoing queries.
     ///
     /// This should be called when the user types new input into the address
@@ -217,6 +260,38 @@ impl SuggestStore {
         self.inner.clear()
     }
 
+    /// Removes all suggestions from a provider.
+    ///
+    /// Apps should call this when they stop showing suggestions from a
+    /// provider, like when the user disables sponsored suggestions or moves
+    /// to a region where the provider isn't available. The freed space is
+    /// returned to the OS the next time [SuggestStore::run_maintenance] runs,
+    /// if there's enough of it. Icons are kept, because other providers'
+    /// suggestions can use them.
+    #[handle_error(Error)]
+    pub fn clear_provider(&self, provider: SuggestionProvider) -> SuggestApiResult<()> {
+        self.inner.clear_provider(provider)
+    }
+
+    /// Cleans up data that ingestion leaves behind, and shrinks the database.
+    ///
+    /// This removes suggestions and icons from records that were deleted from
+    /// Remote Settings without us seeing the deletion. Then, it returns free
+    /// space in the database to the OS until `time_budget_ms` has passed since
+    /// it was called, so it can be run during short idle periods; the next
+    /// call picks up where this one left off.
+    ///
+    /// Icons that no suggestions use are kept as long as their record exists,
+    /// because suggestions we ingest later can use them, and ingestion only
+    /// downloads records that changed.
+    #[handle_error(Error)]
+    pub fn run_maintenance(
+        &self,
+        time_budget_ms: u64,
+    ) -> SuggestApiResult<SuggestMaintenanceResult> {
+        self.inner.run_maintenance(time_budget_ms)
+    }
+
     // Returns global Suggest configuration data.
     #[handle_error(Error)]
     pub fn fetch_global_config(&self) -> SuggestApiResult<SuggestGlobalConfig> {
@@ -247,6 +322,22 @@ pub struct SuggestIngestionConstraints {
     pub empty_only: bool,
 }
 
+/// What a call to [`SuggestStore::run_maintenance`] did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SuggestMaintenanceResult {
+    /// The number of records whose suggestions were removed because the
+    /// record no longer exists in Remote Settings.
+    pub removed_records: u64,
+    /// The number of icons removed because their record no longer exists in
+    /// Remote Settings.
+    pub removed_icons: u64,
+    /// The number of database pages returned to the OS.
+    pub freed_pages: u64,
+    /// Whether the database was fully shrunk within the time budget, or had
+    /// too few free pages to be worth shrinking.
+    pub finished: bool,
+}
+
 /// The implementation of the store. This is generic over the Remote Settings
 /// client, and is split out from the concrete [`SuggestStore`] for testing
 /// with a mock client.
@@ -278,10 +369,16 @@ impl<S> SuggestStoreInner<S> {
     }
 
     fn query(&self, query: SuggestionQuery) -> Result<Vec<Suggestion>> {
+        Ok(self.query_with_budget(query)?.suggestions)
+    }
+
+    fn query_with_budget(&self, query: SuggestionQuery) -> Result<QueryWithBudgetResult> {
         if query.keyword.is_empty() || query.providers.is_empty() {
-            return Ok(Vec::new());
+            return Ok(QueryWithBudgetResult::default());
         }
-        self.dbs()?.reader.read(|dao| dao.fetch_suggestions(&query))
+        self.dbs()?
+            .reader
+            .read(|dao| dao.fetch_suggestions_with_budget(&query))
     }
 
     fn dismiss_suggestion(&self, suggestion_url: String) -> Result<()> {
@@ -295,6 +392,26 @@ impl<S> SuggestStoreInner<S> {
         Ok(())
     }
 
+    fn record_impression(&self, suggestion: &Suggestion) -> Result<Option<String>> {
+        if let Some(url) = suggestion.raw_url() {
+            self.dbs()?
+                .writer
+                .write(|dao| dao.increment_impressions(url))?;
+        }
+        Ok(suggestion.impression_url())
+    }
+
+    fn record_click(&self, suggestion: &Suggestion) -> Result<Option<String>> {
+        if let Some(url) = suggestion.raw_url() {
+            self.dbs()?.writer.write(|dao| dao.increment_clicks(url))?;
+        }
+        Ok(suggestion.click_url())
+    }
+
+    fn fetch_interactions(&self, url: &str) -> Result<SuggestionInteractions> {
+        self.dbs()?.reader.read(|dao| dao.get_interactions(url))
+    }
+
     fn interrupt(&self, kind: Option<InterruptKind>) {
         if let Some(dbs) = self.dbs.get() {
             // Only interrupt if the databases are already open.
@@ -317,6 +434,10 @@ impl<S> SuggestStoreInner<S> {
         self.dbs()?.writer.write(|dao| dao.clear())
     }
 
+    fn clear_provider(&self, provider: SuggestionProvider) -> Result<()> {
+        self.dbs()?.writer.write(|dao| dao.clear_provider(provider))
+    }
+
     pub fn fetch_global_config(&self) -> Result<SuggestGlobalConfig> {
         self.dbs()?.reader.read(|dao| dao.get_global_config())
     }
@@ -355,6 +476,16 @@ where
 
         // Handle ingestion inside single write scope
         let mut write_scope = writer.write_scope()?;
+        let providers = match &constraints.providers {
+            Some(providers) => providers.clone(),
+            None => SuggestionProvider::all().to_vec(),
+        };
+        write_scope.write(|dao| {
+            for provider in providers {
+                dao.reset_cleared_provider(provider)?;
+            }
+            Ok(())
+        })?;
         for ingest_record_type in ingest_record_types {
             breadcrumb!("Ingesting {ingest_record_type}");
             write_scope
@@ -366,6 +497,41 @@ where
         Ok(())
     }
 
+    pub fn run_maintenance(&self, time_budget_ms: u64) -> Result<SuggestMaintenanceResult> {
+        let deadline = Instant::now().checked_add(Duration::from_millis(time_budget_ms));
+        let writer = &self.dbs()?.writer;
+        let mut result = SuggestMaintenanceResult::default();
+
+        // We normally drop a record's data when we ingest its tombstone, but
+        // we'll miss that if the tombstone expires before we next ingest that
+        // record type.
+        // Icons are only removed with their record. Suggestions we ingest
+        // later can use icons that no current suggestions do, and we wouldn't
+        // download those again.
+        let record_ids = self.settings_client.get_record_ids()?;
+        writer.write(|dao| {
+            for record_id in dao.get_ingested_record_ids()? {
+                if record_ids.contains(record_id.as_str()) {
+                    continue;
+                }
+                match record_id.as_icon_id() {
+                    Some(icon_id) => {
+                        dao.drop_icon(icon_id)?;
+                        result.removed_icons += 1;
+                    }
+                    None => {
+                        dao.drop_suggestions(&record_id)?;
+                        result.removed_records += 1;
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        (result.freed_pages, result.finished) = writer.incremental_vacuum(deadline)?;
+        Ok(result)
+    }
+
     fn ingest_records_by_type(
         &self,
         ingest_record_type: SuggestRecordType,
@@ -491,6 +657,28 @@ where
                         },
                     )?;
                 }
+                SuggestRecord::Geonames => {
+                    self.ingest_attachment(
+                        &SuggestRecordType::Geonames.last_ingest_meta_key(),
+                        dao,
+                        record,
+                        |dao, record_id, geonames| dao.insert_geonames(record_id, geonames),
+                    )?;
+                }
+                SuggestRecord::Exposure(data) => {
+                    self.ingest_attachment(
+                        &SuggestRecordType::Exposure.last_ingest_meta_key(),
+                        dao,
+                        record,
+                        |dao, record_id, suggestions| {
+                            dao.insert_exposure_suggestions(
+                                record_id,
+                                &data.suggestion_type,
+                                suggestions,
+                            )
+                        },
+                    )?;
+                }
                 SuggestRecord::Weather(data) => {
                     self.ingest_record(
                         &SuggestRecordType::Weather.last_ingest_meta_key(),
@@ -645,7 +833,12 @@ mod tests {
     use serde_json::json;
     use sql_support::ConnExt;
 
-    use crate::{testing::*, SuggestionProvider};
+    use crate::{
+        db::{DEFAULT_SUGGESTION_SCORE, MIN_FREE_PAGES_TO_VACUUM},
+        raw_suggestion_url_matches,
+        testing::*,
+        Geoname, SuggestionProvider,
+    };
 
     /// In-memory Suggest store for testing
     struct TestStore {
@@ -1075,6 +1268,96 @@ mod tests {
         Ok(())
     }
 
+    /// Tests that maintenance removes data from records that were deleted
+    /// upstream.
+    #[test]
+    fn run_maintenance() -> anyhow::Result<()> {
+        before_each();
+
+        let mut store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_record("data", "1234", json!([los_pollos_amp()]))
+                .with_record("data", "5678", json!([good_place_eats_amp()]))
+                .with_icon(los_pollos_icon())
+                .with_icon(good_place_eats_icon())
+                .with_icon(caltech_icon()),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+        assert_eq!(store.count_rows("icons"), 3);
+
+        // Record "5678" and the Caltech icon were deleted, and we never saw
+        // their tombstones.
+        store.replace_client(
+            MockRemoteSettingsClient::default()
+                .with_record("data", "1234", json!([los_pollos_amp()]))
+                .with_icon(los_pollos_icon())
+                .with_icon(good_place_eats_icon()),
+        );
+        let result = store.inner.run_maintenance(u64::MAX)?;
+        assert_eq!(result.removed_records, 1);
+        assert_eq!(result.removed_icons, 1);
+        assert!(result.finished);
+        // The Good Place Eats icon isn't used anymore, but its record still
+        // exists, so it's kept for suggestions we might ingest later.
+        assert_eq!(store.count_rows("icons"), 2);
+        // Running maintenance again shouldn't remove anything else.
+        let result = store.inner.run_maintenance(u64::MAX)?;
+        assert_eq!((result.removed_records, result.removed_icons), (0, 0));
+
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::amp("lo")),
+            vec![los_pollos_suggestion("los")],
+        );
+        assert_eq!(store.fetch_suggestions(SuggestionQuery::amp("la")), vec![]);
+        assert_eq!(store.count_rows("icons"), 2);
+        // Icons aren't downloaded again by the next ingest.
+        store.read(|dao| {
+            assert!(dao
+                .get_meta::<u64>(SuggestRecordType::Icon.last_ingest_meta_key().as_str())?
+                .is_some());
+            Ok(())
+        })?;
+
+        // Running it again has nothing to do.
+        let result = store.inner.run_maintenance(0)?;
+        assert_eq!(
+            result,
+            SuggestMaintenanceResult {
+                finished: true,
+                ..SuggestMaintenanceResult::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Tests that maintenance only shrinks the database once it has enough
+    /// free pages.
+    #[test]
+    fn run_maintenance_free_pages_threshold() -> anyhow::Result<()> {
+        before_each();
+
+        let store = TestStore::new(MockRemoteSettingsClient::default());
+        let free_pages = |size: usize| -> anyhow::Result<()> {
+            store.write(|dao| dao.put_meta("padding", vec![0u8; size]))?;
+            store.write(|dao| dao.delete_meta("padding"))?;
+            Ok(())
+        };
+
+        // A few free pages aren't worth returning to the OS.
+        free_pages(64 * 1024)?;
+        let result = store.inner.run_maintenance(u64::MAX)?;
+        assert_eq!(result.freed_pages, 0);
+        assert!(result.finished);
+
+        free_pages(8 * 1024 * 1024)?;
+        let result = store.inner.run_maintenance(u64::MAX)?;
+        assert!(result.freed_pages >= MIN_FREE_PAGES_TO_VACUUM);
+        assert!(result.finished);
+
+        Ok(())
+    }
+
     /// Tests ingesting tombstones for previously-ingested suggestions and
     /// icons.
     #[test]
@@ -1144,6 +1427,88 @@ mod tests {
         Ok(())
     }
 
+    /// Tests clearing a single provider.
+    #[test]
+    fn clear_provider() -> anyhow::Result<()> {
+        before_each();
+
+        let store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_record(
+                    "data",
+                    "data-1",
+                    json!([los_pollos_amp(), california_wiki()]),
+                )
+                .with_record("yelp-suggestions", "yelp-1", json!([ramen_yelp()]))
+                .with_icon(los_pollos_icon())
+                .with_icon(california_icon())
+                .with_icon(yelp_favicon()),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+        assert!(store.count_rows("yelp_subjects") > 0);
+        assert_eq!(store.count_rows("icons"), 3);
+
+        store.inner.clear_provider(SuggestionProvider::Yelp)?;
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::yelp("ramen")),
+            vec![]
+        );
+        assert_eq!(store.count_rows("yelp_subjects"), 0);
+        assert_eq!(store.count_rows("yelp_custom_details"), 0);
+        // Icons are kept in case the provider is re-enabled.
+        assert_eq!(store.count_rows("icons"), 3);
+        // Other providers are left alone.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::amp("lo")),
+            vec![los_pollos_suggestion("los")]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::wikipedia("cal")),
+            vec![california_suggestion("california")]
+        );
+
+        store.inner.clear_provider(SuggestionProvider::Amp)?;
+        assert_eq!(store.fetch_suggestions(SuggestionQuery::amp("lo")), vec![]);
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::wikipedia("cal")),
+            vec![california_suggestion("california")]
+        );
+
+        // Yelp suggestions come back when it's ingested again, but AMP
+        // suggestions don't come back when Wikipedia is ingested.
+        store.ingest(SuggestIngestionConstraints {
+            providers: Some(vec![
+                SuggestionProvider::Yelp,
+                SuggestionProvider::Wikipedia,
+            ]),
+            ..SuggestIngestionConstraints::default()
+        });
+        assert!(matches!(
+            store
+                .fetch_suggestions(SuggestionQuery::yelp("ramen"))
+                .as_slice(),
+            [Suggestion::Yelp { .. }]
+        ));
+        assert_eq!(store.fetch_suggestions(SuggestionQuery::amp("lo")), vec![]);
+
+        // Re-enabling AMP brings its suggestions back, even though the record
+        // they're in hasn't changed since Wikipedia was ingested.
+        store.ingest(SuggestIngestionConstraints {
+            providers: Some(vec![SuggestionProvider::Amp]),
+            ..SuggestIngestionConstraints::default()
+        });
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::amp("lo")),
+            vec![los_pollos_suggestion("los")]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::wikipedia("cal")),
+            vec![california_suggestion("california")]
+        );
+
+        Ok(())
+    }
+
     /// Tests querying suggestions.
     #[test]
     fn query() -> anyhow::Result<()> {
@@ -1559,6 +1924,54 @@ mod tests {
         Ok(())
     }
 
+    /// Tests that a query stops querying providers once its time budget is
+    /// used up.
+    #[test]
+    fn query_with_budget() -> anyhow::Result<()> {
+        before_each();
+
+        let store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_record(
+                    "data",
+                    "data-1",
+                    json!([los_pollos_amp(), california_wiki()]),
+                )
+                .with_icon(los_pollos_icon())
+                .with_icon(california_icon()),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+
+        let query = SuggestionQuery {
+            keyword: "lo".into(),
+            providers: vec![SuggestionProvider::Amp, SuggestionProvider::Wikipedia],
+            limit: None,
+            time_budget_ms: None,
+            exposure_suggestion_types: vec![],
+        };
+        assert_eq!(
+            store.inner.query_with_budget(query.clone())?,
+            QueryWithBudgetResult {
+                suggestions: vec![los_pollos_suggestion("los")],
+                timed_out_providers: vec![],
+            }
+        );
+
+        // With no time at all, no providers should be queried.
+        assert_eq!(
+            store.inner.query_with_budget(SuggestionQuery {
+                time_budget_ms: Some(0),
+                ..query
+            })?,
+            QueryWithBudgetResult {
+                suggestions: vec![],
+                timed_out_providers: vec![SuggestionProvider::Amp, SuggestionProvider::Wikipedia],
+            }
+        );
+
+        Ok(())
+    }
+
     // Tests querying AMP / Wikipedia / Pocket
     #[test]
     fn query_with_multiple_providers_and_diff_scores() -> anyhow::Result<()> {
@@ -1976,31 +2389,52 @@ mod tests {
         // Matches
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("xyz")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("wea")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("weat")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("weath")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("weathe")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("weather")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
         assert_eq!(
             store.fetch_suggestions(SuggestionQuery::weather("  weather  ")),
-            vec![Suggestion::Weather { score: 0.24 },]
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
         );
 
         assert_eq!(
@@ -2013,6 +2447,206 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn weather_with_city() -> anyhow::Result<()> {
+        before_each();
+
+        let mut store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_inline_record(
+                    "weather",
+                    "weather-1",
+                    json!({
+                        "min_keyword_length": 3,
+                        "keywords": ["weather", "forecast"],
+                        "score": "0.24"
+                    }),
+                )
+                .with_record(
+                    "geonames",
+                    "geonames-1",
+                    json!([
+                        {
+                            "id": 2950159,
+                            "name": "Berlin",
+                            "latitude": 52.52437,
+                            "longitude": 13.41053,
+                            "country_code": "DE",
+                            "admin1_code": "16",
+                            "population": 3426354,
+                            "alternate_names": ["berlín"]
+                        },
+                        {
+                            "id": 4990729,
+                            "name": "Berlin",
+                            "latitude": 42.3812,
+                            "longitude": -71.6376,
+                            "country_code": "US",
+                            "admin1_code": "MA",
+                            "population": 3144
+                        },
+                        {
+                            "id": 5128581,
+                            "name": "New York City",
+                            "latitude": 40.71427,
+                            "longitude": -74.00597,
+                            "country_code": "US",
+                            "admin1_code": "NY",
+                            "population": 8804190,
+                            "alternate_names": ["nyc", "new york"]
+                        }
+                    ]),
+                ),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+
+        let berlin = Geoname {
+            geoname_id: 2950159,
+            name: "Berlin".into(),
+            latitude: 52.52437,
+            longitude: 13.41053,
+            country_code: "DE".into(),
+            admin1_code: "16".into(),
+            population: 3426354,
+        };
+        let nyc = Geoname {
+            geoname_id: 5128581,
+            name: "New York City".into(),
+            latitude: 40.71427,
+            longitude: -74.00597,
+            country_code: "US".into(),
+            admin1_code: "NY".into(),
+            population: 8804190,
+        };
+        let weather_in = |city: &Geoname| {
+            vec![Suggestion::Weather {
+                city: Some(city.clone()),
+                score: 0.24,
+            }]
+        };
+
+        // The most populous Berlin should be picked.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in berlin")),
+            weather_in(&berlin)
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("Weather Berlin")),
+            weather_in(&berlin)
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("berlín forecast")),
+            weather_in(&berlin)
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in new york")),
+            weather_in(&nyc)
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("  nyc   weather ")),
+            weather_in(&nyc)
+        );
+        // No match since the city isn't known or the name is incomplete.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in paris")),
+            vec![]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in berl")),
+            vec![]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in")),
+            vec![]
+        );
+        // Keywords on their own still match without a city.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("forecast")),
+            vec![Suggestion::Weather {
+                city: None,
+                score: 0.24
+            },]
+        );
+
+        // Geonames are removed along with their record.
+        store.replace_client(
+            MockRemoteSettingsClient::default()
+                .with_inline_record(
+                    "weather",
+                    "weather-1",
+                    json!({
+                        "min_keyword_length": 3,
+                        "keywords": ["weather", "forecast"],
+                        "score": "0.24"
+                    }),
+                )
+                .with_tombstone("geonames", "geonames-1"),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::weather("weather in berlin")),
+            vec![]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn exposure() -> anyhow::Result<()> {
+        before_each();
+
+        let store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_record_and_fields(
+                    "exposure-suggestions",
+                    "exposure-1",
+                    json!({ "suggestion_type": "aaa-type" }),
+                    json!([{ "keywords": ["aaa", "bbb"] }]),
+                )
+                .with_record_and_fields(
+                    "exposure-suggestions",
+                    "exposure-2",
+                    json!({ "suggestion_type": "ccc-type" }),
+                    json!([{ "keywords": ["aaa", "ccc"] }]),
+                ),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+
+        let exposure = |suggestion_type: &str| Suggestion::Exposure {
+            suggestion_type: suggestion_type.into(),
+            score: DEFAULT_SUGGESTION_SCORE,
+        };
+
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("aaa", &["aaa-type"])),
+            vec![exposure("aaa-type")]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("AAA", &["aaa-type", "ccc-type"])),
+            vec![exposure("aaa-type"), exposure("ccc-type")]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("ccc", &["aaa-type", "ccc-type"])),
+            vec![exposure("ccc-type")]
+        );
+        // No match since the type isn't enabled.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("bbb", &["ccc-type"])),
+            vec![]
+        );
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("aaa", &[])),
+            vec![]
+        );
+        // No match since keywords must match exactly.
+        assert_eq!(
+            store.fetch_suggestions(SuggestionQuery::exposure("aa", &["aaa-type"])),
+            vec![]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn fetch_global_config() -> anyhow::Result<()> {
         before_each();
@@ -2146,4 +2780,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn record_interactions() -> anyhow::Result<()> {
+        before_each();
+
+        let store = TestStore::new(
+            MockRemoteSettingsClient::default()
+                .with_record(
+                    "data",
+                    "data-1",
+                    json!([good_place_eats_amp().merge(json!({
+                        "click_url": "https://example.com/click_url?t=%YYYYMMDDHH%",
+                    }))]),
+                )
+                .with_record("amo-suggestions", "amo-1", json!([relay_amo()]))
+                .with_icon(good_place_eats_icon()),
+        );
+        store.ingest(SuggestIngestionConstraints::default());
+
+        let amp = store
+            .fetch_suggestions(SuggestionQuery::all_providers("lasagna"))
+            .pop()
+            .expect("should match the AMP suggestion");
+        let raw_url = amp.raw_url().unwrap().to_string();
+        assert_eq!(
+            store.inner.fetch_interactions(&raw_url)?,
+            SuggestionInteractions::default()
+        );
+
+        for _ in 0..2 {
+            assert_eq!(
+                store.inner.record_impression(&amp)?.as_deref(),
+                Some("https://example.com/impression_url")
+            );
+        }
+        let click_url = store
+            .inner
+            .record_click(&amp)?
+            .expect("AMP suggestions should have a click URL");
+        assert!(raw_suggestion_url_matches(
+            "https://example.com/click_url?t=%YYYYMMDDHH%",
+            &click_url
+        ));
+        assert_ne!(click_url, "https://example.com/click_url?t=%YYYYMMDDHH%");
+        assert_eq!(
+            store.inner.fetch_interactions(&raw_url)?,
+            SuggestionInteractions {
+                impressions: 2,
+                clicks: 1,
+            }
+        );
+
+        // Providers without reporting URLs still have their interactions
+        // counted.
+        let amo = store
+            .fetch_suggestions(SuggestionQuery::all_providers("relay"))
+            .pop()
+            .expect("should match the AMO suggestion");
+        assert_eq!(store.inner.record_click(&amo)?, None);
+        assert_eq!(
+            store
+                .inner
+                .fetch_interactions(amo.raw_url().unwrap())?
+                .clicks,
+            1
+        );
+
+        Ok(())
+    }
 }
@@ -5,7 +5,7 @@
 
 use chrono::Local;
 
-use crate::db::DEFAULT_SUGGESTION_SCORE;
+use crate::{db::DEFAULT_SUGGESTION_SCORE, geoname::Geoname};
 
 /// The template parameter for a timestamp in a "raw" sponsored suggestion URL.
 const TIMESTAMP_TEMPLATE: &str = "%YYYYMMDDHH%";
@@ -79,8 +79,21 @@ pub enum Suggestion {
         score: f64,
     },
     Weather {
+        /// The city or region the user asked about, if the query named one.
+        city: Option<Geoname>,
         score: f64,
     },
+    /// A suggestion that's never shown to the user. Apps record that it
+    /// matched, so experiments can measure how often a new type of suggestion
+    /// would be shown before building UI for it.
+    Exposure { suggestion_type: String, score: f64 },
+}
+
+/// How often a suggestion has been shown to the user and picked by them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SuggestionInteractions {
+    pub impressions: u64,
+    pub clicks: u64,
 }
 
 impl PartialOrd for Suggestion {
@@ -138,6 +151,24 @@ impl Suggestion {
             _ => None,
         }
     }
+
+    /// Get the URL to ping when this suggestion is shown, with its template
+    /// parameters replaced, if the provider has one.
+    pub(crate) fn impression_url(&self) -> Option<String> {
+        match self {
+            Self::Amp { impression_url, .. } => Some(cook_raw_suggestion_url(impression_url)),
+            _ => None,
+        }
+    }
+
+    /// Get the URL to ping when this suggestion is picked, with its template
+    /// parameters replaced, if the provider has one.
+    pub(crate) fn click_url(&self) -> Option<String> {
+        match self {
+            Self::Amp { raw_click_url, .. } => Some(cook_raw_suggestion_url(raw_click_url)),
+            _ => None,
+        }
+    }
 }
 
 impl Eq for Suggestion {}
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use remote_settings::Attachment;
 use serde_json::json;
@@ -37,7 +37,22 @@ impl MockRemoteSettingsClient {
     ///
     /// A single record typically contains multiple items in the attachment data.  Pass all of them
     /// as the `items` param.
-    pub fn with_record(mut self, record_type: &str, record_id: &str, items: JsonValue) -> Self {
+    pub fn with_record(self, record_type: &str, record_id: &str, items: JsonValue) -> Self {
+        self.with_record_and_fields(record_type, record_id, json!({}), items)
+    }
+
+    /// Add a record to the mock data, with extra fields stored in the record itself alongside
+    /// the attachment
+    ///
+    /// Use this for record types like exposure suggestions, where the record describes the items
+    /// in its attachment.
+    pub fn with_record_and_fields(
+        mut self,
+        record_type: &str,
+        record_id: &str,
+        fields: JsonValue,
+        items: JsonValue,
+    ) -> Self {
         let location = format!("{record_type}-{record_id}.json");
         let records = self.records.entry(record_type.to_string()).or_default();
         records.push(Record {
@@ -51,7 +66,7 @@ impl MockRemoteSettingsClient {
                 size: 0,
                 location,
             }),
-            fields: json!({"type": record_type}).into_map(),
+            fields: json!({"type": record_type}).merge(fields).into_map(),
             attachment_data: Some(
                 serde_json::to_vec(&items).expect("error serializing attachment data"),
             ),
@@ -161,4 +176,14 @@ impl Client for MockRemoteSettingsClient {
             None => vec![],
         })
     }
+
+    fn get_record_ids(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .records
+            .values()
+            .flatten()
+            .filter(|record| !record.deleted)
+            .map(|record| record.id.clone())
+            .collect())
+    }
 }
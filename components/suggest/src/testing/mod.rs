@@ -49,6 +49,7 @@ impl Suggestion {
             Self::Yelp { score, .. } => score,
             Self::Mdn { score, .. } => score,
             Self::Weather { score, .. } => score,
+            Self::Exposure { score, .. } => score,
             Self::Wikipedia { .. } => panic!("with_score not valid for wikipedia suggestions"),
         };
         *current_score = score;
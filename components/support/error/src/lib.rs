@@ -160,3 +160,6 @@ macro_rules! define_error {
 }
 
 uniffi::include_scaffolding!("errorsupport");
+
+pub const UNIFFI_NAMESPACE: &str = "errorsupport";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -14,3 +14,8 @@ pub use error::Interrupted;
 pub use interruptee::*;
 pub use shutdown::*;
 pub use sql::*;
+
+uniffi::include_scaffolding!("interrupt_support");
+
+pub const UNIFFI_NAMESPACE: &str = "interrupt_support";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -105,6 +105,15 @@ impl<E: From<EncryptorDecryptorError>> EncryptorDecryptor<E> {
         Ok(serde_json::from_str(&json).to_encdec_result(description)?)
     }
 
+    /// Returns a SHA-256 digest of the key, which callers can use to tell whether two instances
+    /// use the same key without holding on to the key itself.
+    pub fn key_digest(&self) -> Result<Vec<u8>, E> {
+        let key = serde_json::to_vec(&self.jwk).to_encdec_result("key digest (serialization)")?;
+        let digest = rc_crypto::digest::digest(&rc_crypto::digest::SHA256, &key)
+            .to_encdec_result("key digest")?;
+        Ok(digest.as_ref().to_vec())
+    }
+
     // Create canary text.
     //
     // These are used to check if a key is still valid for a database.  Call this when opening a
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A registry of the components in a megazord.
+//!
+//! Each megazord registers the components it contains when it's loaded, so
+//! apps can check at runtime what the native library they're using actually
+//! contains, rather than finding out when a call fails. Megazords do this by
+//! listing their components with [megazord_components].
+
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+lazy_static::lazy_static! {
+    // Versions, keyed by namespace.
+    static ref COMPONENTS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentInfo {
+    pub namespace: String,
+    pub version: String,
+}
+
+/// Register a component, replacing any component previously registered with
+/// the same namespace.
+///
+/// This is only meant to be called by megazords.
+pub fn register_component(namespace: &str, version: &str) {
+    COMPONENTS
+        .lock()
+        .insert(namespace.to_string(), version.to_string());
+}
+
+/// Get the registered components, sorted by namespace.
+pub fn get_component_versions() -> Vec<ComponentInfo> {
+    COMPONENTS
+        .lock()
+        .iter()
+        .map(|(namespace, version)| ComponentInfo {
+            namespace: namespace.clone(),
+            version: version.clone(),
+        })
+        .collect()
+}
+
+/// Check whether a component with the given namespace was registered.
+pub fn has_component(namespace: &str) -> bool {
+    COMPONENTS.lock().contains_key(namespace)
+}
+
+/// Re-exports the given component crates from a megazord, and registers each
+/// one with [register_component] when the megazord library is loaded.
+///
+/// Each component crate defines `UNIFFI_NAMESPACE`, the namespace in its UDL
+/// file, and `CRATE_VERSION`, its `CARGO_PKG_VERSION`, next to its
+/// `uniffi::include_scaffolding!()`.
+#[macro_export]
+macro_rules! megazord_components {
+    ($($component:ident),* $(,)?) => {
+        $(pub use $component;)*
+
+        extern "C" fn register_megazord_components() {
+            $($crate::register_component(
+                $component::UNIFFI_NAMESPACE,
+                $component::CRATE_VERSION,
+            );)*
+        }
+
+        // Have the dynamic loader call `register_megazord_components()` when
+        // the library is loaded, like a C `__attribute__((constructor))`. This
+        // works the same way for every megazord, whether or not it has its
+        // own initialization function.
+        #[used]
+        #[cfg_attr(
+            any(target_os = "linux", target_os = "android"),
+            link_section = ".init_array"
+        )]
+        #[cfg_attr(
+            any(target_os = "macos", target_os = "ios"),
+            link_section = "__DATA,__mod_init_func"
+        )]
+        #[cfg_attr(windows, link_section = ".CRT$XCU")]
+        static REGISTER_MEGAZORD_COMPONENTS: extern "C" fn() = register_megazord_components;
+    };
+}
+
+uniffi::include_scaffolding!("megazord_registry");
+
+pub const UNIFFI_NAMESPACE: &str = "megazord_registry";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    pub mod loaded_component {
+        pub const UNIFFI_NAMESPACE: &str = "loaded_component";
+        pub const CRATE_VERSION: &str = "0.1";
+    }
+
+    megazord_components!(loaded_component);
+
+    #[test]
+    fn test_registered_on_load() {
+        assert!(has_component("loaded_component"));
+    }
+
+    #[test]
+    fn test_registry() {
+        assert!(!has_component("test_component"));
+        register_component("test_component", "1.0");
+        register_component("another_component", "2.0");
+        assert!(has_component("test_component"));
+
+        // Registering the same namespace again replaces the version.
+        register_component("test_component", "1.1");
+        assert_eq!(
+            get_component_versions(),
+            vec![
+                ComponentInfo {
+                    namespace: "another_component".to_string(),
+                    version: "2.0".to_string(),
+                },
+                ComponentInfo {
+                    namespace: "loaded_component".to_string(),
+                    version: "0.1".to_string(),
+                },
+                ComponentInfo {
+                    namespace: "test_component".to_string(),
+                    version: "1.1".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -28,6 +28,8 @@ pub enum ErrorKind {
     SignatureContentError(String),
     #[error("Content signature mismatch error: {0}")]
     SignatureMismatchError(String),
+    #[error("Invalid scrypt parameters: {0}")]
+    ScryptParamsError(String),
 }
 
 error_support::define_error! {
@@ -38,6 +38,7 @@ pub mod hkdf;
 pub mod hmac;
 pub mod pbkdf2;
 pub mod rand;
+pub mod scrypt;
 pub mod signature;
 
 // Expose `hawk` if the hawk feature is on. This avoids consumers needing to
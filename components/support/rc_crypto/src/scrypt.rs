@@ -0,0 +1,227 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// NSS doesn't implement scrypt, so this is a straightforward implementation of
+// https://www.rfc-editor.org/rfc/rfc7914 on top of our NSS-backed PBKDF2.
+// It's only intended for deriving keys from passwords in legacy flows, so it
+// favors simplicity over speed.
+
+use crate::error::*;
+use crate::pbkdf2::{self, HashAlgorithm};
+
+/// The most memory `derive` will allocate for its working state, which is
+/// `128 * r * (n + p + 1)` bytes. This is far more than any parameters we
+/// use need, but stops bad parameters from exhausting memory.
+pub const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Derives a key from a password using scrypt, as described in
+/// [RFC 7914](https://www.rfc-editor.org/rfc/rfc7914).
+/// # Arguments
+///
+///  * `passphrase` - The password to stretch
+///  * `salt` - A salt to use in the generation process
+///  * `n` - The CPU/memory cost parameter. Must be a power of 2 greater than 1
+///  * `r` - The block size parameter
+///  * `p` - The parallelization parameter
+///  * `out` - The slice the algorithm will populate
+///
+/// # Examples
+///
+/// ```
+/// use rc_crypto::scrypt;
+/// let password = b"password";
+/// let salt = b"NaCl";
+/// let mut out = vec![0u8; 32];
+/// // Real code should use a MUCH higher cost (Think 32768+)
+/// scrypt::derive(password, salt, 16, 1, 1, &mut out).unwrap();
+/// assert_eq!(hex::encode(out), "aec6b7483ed26e08802b41f4032086a0e886be7ac48fcfd92ff0cef8109752f4");
+///```
+///
+/// # Errors
+///
+/// Returns an error if the parameters are invalid, would need more than
+/// `MAX_MEMORY` bytes, or if the NSS PBKDF2 implementation returns an error.
+pub fn derive(
+    passphrase: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: u32,
+    p: u32,
+    out: &mut [u8],
+) -> Result<()> {
+    let block_len = check_params(n, r, p)?;
+    let mut b = vec![0u8; block_len * p as usize];
+    pbkdf2::derive(passphrase, salt, 1, HashAlgorithm::SHA256, &mut b)?;
+    let mut v = vec![0u32; block_len / 4 * n as usize];
+    for chunk in b.chunks_exact_mut(block_len) {
+        ro_mix(chunk, r as usize, n as usize, &mut v);
+    }
+    pbkdf2::derive(passphrase, &b, 1, HashAlgorithm::SHA256, out)?;
+    Ok(())
+}
+
+// Checks the parameters as described in the RFC, and that we won't need more
+// than `MAX_MEMORY`. Returns the length of each block in bytes.
+fn check_params(n: u64, r: u32, p: u32) -> Result<usize> {
+    let invalid = |reason: &str| -> Error { ErrorKind::ScryptParamsError(reason.into()).into() };
+    if n < 2 || !n.is_power_of_two() {
+        return Err(invalid("n must be a power of 2 greater than 1"));
+    }
+    if r == 0 || p == 0 {
+        return Err(invalid("r and p must be greater than 0"));
+    }
+    if u64::from(r) * u64::from(p) >= 1 << 30 {
+        return Err(invalid("r * p must be less than 2^30"));
+    }
+    let block_len = 128 * r as usize;
+    let fits = (block_len as u64)
+        .checked_mul(n + u64::from(p) + 1)
+        .is_some_and(|memory| memory <= MAX_MEMORY as u64);
+    if !fits {
+        return Err(invalid("the parameters need too much memory"));
+    }
+    Ok(block_len)
+}
+
+// scryptROMix from the RFC. `b` is a block of `128 * r` bytes, and `v` is
+// scratch space for `n` blocks.
+fn ro_mix(b: &mut [u8], r: usize, n: usize, v: &mut [u32]) {
+    let words = 32 * r;
+    let mut x = b
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect::<Vec<_>>();
+    let mut y = vec![0u32; words];
+    for i in 0..n {
+        v[i * words..(i + 1) * words].copy_from_slice(&x);
+        block_mix(&x, &mut y, r);
+        std::mem::swap(&mut x, &mut y);
+    }
+    for _ in 0..n {
+        let j = integerify(&x, r) & (n - 1);
+        for (xi, vi) in x.iter_mut().zip(&v[j * words..(j + 1) * words]) {
+            *xi ^= vi;
+        }
+        block_mix(&x, &mut y, r);
+        std::mem::swap(&mut x, &mut y);
+    }
+    for (w, x) in b.chunks_exact_mut(4).zip(&x) {
+        w.copy_from_slice(&x.to_le_bytes());
+    }
+}
+
+// The first 64 bits of the last 64-byte sub-block of `x`. As `n` fits in a
+// `usize`, the bits we discard would be masked off anyway.
+fn integerify(x: &[u32], r: usize) -> usize {
+    let last = (2 * r - 1) * 16;
+    (u64::from(x[last]) | u64::from(x[last + 1]) << 32) as usize
+}
+
+// scryptBlockMix from the RFC, which writes the even sub-blocks of the result
+// to the first half of `out`, and the odd ones to the second half.
+fn block_mix(input: &[u32], out: &mut [u32], r: usize) {
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&input[(2 * r - 1) * 16..]);
+    for (i, chunk) in input.chunks_exact(16).enumerate() {
+        for (xi, ci) in x.iter_mut().zip(chunk) {
+            *xi ^= ci;
+        }
+        salsa20_8(&mut x);
+        let offset = (i / 2 + (i % 2) * r) * 16;
+        out[offset..offset + 16].copy_from_slice(&x);
+    }
+}
+
+// The Salsa20/8 core function, applied to `block` in place.
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    let mut quarter_round = |a: usize, b: usize, c: usize, d: usize| {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    };
+    for _ in 0..4 {
+        // Columns.
+        quarter_round(0, 4, 8, 12);
+        quarter_round(5, 9, 13, 1);
+        quarter_round(10, 14, 2, 6);
+        quarter_round(15, 3, 7, 11);
+        // Rows.
+        quarter_round(0, 1, 2, 3);
+        quarter_round(5, 6, 7, 4);
+        quarter_round(10, 11, 8, 9);
+        quarter_round(15, 12, 13, 14);
+    }
+    for (bi, xi) in block.iter_mut().zip(x) {
+        *bi = bi.wrapping_add(xi);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The test vectors from section 12 of the RFC.
+    #[test]
+    fn test_rfc_vectors() {
+        let mut out = vec![0u8; 64];
+        derive(b"", b"", 16, 1, 1, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+             fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+
+        derive(b"password", b"NaCl", 1024, 8, 16, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+
+        derive(b"pleaseletmein", b"SodiumChloride", 16384, 8, 1, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out),
+            "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2\
+             d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887"
+        );
+    }
+
+    #[test]
+    fn test_salsa20_8() {
+        // The test vector from section 8 of the RFC.
+        let input = hex::decode(
+            "7e879a214f3ec9867ca940e641718f26baee555b8c61c1b50df846116dcd3b1d\
+             ee24f319df9b3d8514121e4b5ac5aa3276021d2909c74829edebc68db8b8c25e",
+        )
+        .unwrap();
+        let mut b = [0u32; 16];
+        for (b, w) in b.iter_mut().zip(input.chunks_exact(4)) {
+            *b = u32::from_le_bytes([w[0], w[1], w[2], w[3]]);
+        }
+        salsa20_8(&mut b);
+        let output = b.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<_>>();
+        assert_eq!(
+            hex::encode(output),
+            "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29\
+             b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81"
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_params() {
+        let mut out = vec![0u8; 32];
+        // n must be a power of 2 greater than 1.
+        assert!(derive(b"password", b"salt", 0, 1, 1, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 1, 1, 1, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 15, 1, 1, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 16, 0, 1, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 16, 1, 0, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 16, 1 << 15, 1 << 15, &mut out).is_err());
+        // Too much memory.
+        assert!(derive(b"password", b"salt", 1 << 40, 8, 1, &mut out).is_err());
+        assert!(derive(b"password", b"salt", 1 << 20, 8, 1, &mut out).is_err());
+    }
+}
@@ -29,6 +29,9 @@ pub fn set_max_level(level: Level) {
 
 uniffi::include_scaffolding!("rust_log_forwarder");
 
+pub const UNIFFI_NAMESPACE: &str = "rust_log_forwarder";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -30,7 +30,9 @@ pub(crate) use coll_update::{fetch_incoming, CollectionUpdate};
 pub(crate) use collection_keys::CollectionKeys;
 pub(crate) use request::InfoConfiguration;
 pub(crate) use state::GlobalState;
-pub use status::{ServiceStatus, SyncResult};
+pub use status::{
+    EngineProgress, EngineSkipReason, ServiceStatus, SyncProgressListener, SyncResult,
+};
 pub use storage_client::{
     SetupStorageClient, Sync15ClientResponse, Sync15StorageClient, Sync15StorageClientInit,
 };
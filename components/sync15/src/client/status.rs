@@ -60,6 +60,47 @@ impl ServiceStatus {
     }
 }
 
+/// Why an engine wasn't synced. Engines are synced after the things they
+/// depend on, and are skipped if one of those fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineSkipReason {
+    /// We couldn't get a token, or couldn't fetch or upload `meta/global` or
+    /// `crypto/keys`. Every engine depends on these.
+    KeysFailed,
+    /// The clients collection failed to sync. Every engine depends on it,
+    /// because commands from other clients (such as a wipe) must be applied
+    /// before the engine syncs.
+    ClientsFailed,
+    /// An error which affects every engine, such as a network or
+    /// authentication error, ended the sync before this engine.
+    Aborted,
+    /// The server asked us to back off, either during this sync or during an
+    /// earlier one whose backoff hasn't passed yet.
+    BackedOff,
+    /// The sync was interrupted before this engine.
+    Interrupted,
+}
+
+/// How far an engine has got, reported to the [SyncProgressListener] passed in
+/// [SyncRequestInfo](super::SyncRequestInfo) so apps can show progress for long
+/// syncs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineProgress {
+    /// We started syncing the engine.
+    Started,
+    /// We downloaded `count` records changed by other devices.
+    Downloaded { count: usize },
+    /// We're uploading `count` records changed on this device.
+    Uploading { count: usize },
+    /// We finished syncing the engine. `status` is `ServiceStatus::Ok` if it
+    /// synced successfully.
+    Finished { status: ServiceStatus },
+}
+
+pub trait SyncProgressListener {
+    fn on_engine_progress(&self, engine: &str, progress: EngineProgress);
+}
+
 /// The result of a sync request. This too is from the "sync manager", but only
 /// has a fraction of the things it will have when we actually build that.
 #[derive(Debug)]
@@ -77,6 +118,10 @@ pub struct SyncResult {
     /// Note that we expect the `String` to be replaced with an enum later.
     pub engine_results: HashMap<String, Result<(), Error>>,
 
+    /// The engines which weren't synced, and why. Together with
+    /// `engine_results` and `declined`, this accounts for every engine.
+    pub skipped_engines: HashMap<String, EngineSkipReason>,
+
     pub telemetry: SyncTelemetryPing,
 
     pub next_sync_after: Option<std::time::SystemTime>,
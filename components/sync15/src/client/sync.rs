@@ -2,7 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use super::{CollectionUpdate, GlobalState, LocalCollStateMachine, Sync15StorageClient};
+use super::{
+    CollectionUpdate, EngineProgress, GlobalState, LocalCollStateMachine, Sync15StorageClient,
+    SyncProgressListener,
+};
 use crate::clients_engine;
 use crate::engine::SyncEngine;
 use crate::error::Error;
@@ -20,6 +23,7 @@ pub fn synchronize_with_clients_engine(
     fully_atomic: bool,
     telem_engine: &mut telemetry::Engine,
     interruptee: &dyn Interruptee,
+    progress_listener: Option<&dyn SyncProgressListener>,
 ) -> Result<(), Error> {
     let collection = engine.collection_name();
     log::info!("Syncing collection {}", collection);
@@ -65,6 +69,14 @@ pub fn synchronize_with_clients_engine(
             // very end when we know we've staged them all.
             let incoming = super::fetch_incoming(client, &coll_state, collection_request)?;
             log::info!("Downloaded {} remote changes", incoming.len());
+            if let Some(listener) = progress_listener {
+                listener.on_engine_progress(
+                    &collection,
+                    EngineProgress::Downloaded {
+                        count: incoming.len(),
+                    },
+                );
+            }
             engine.stage_incoming(incoming, telem_engine)?;
             interruptee.err_if_interrupted()?;
         }
@@ -84,6 +96,14 @@ pub fn synchronize_with_clients_engine(
     // Most stuff below should be called per-batch rather than at the successful end of all
     // batches, but that's not trivial.
     log::info!("Uploading {} outgoing changes", outgoing.len());
+    if let Some(listener) = progress_listener {
+        listener.on_engine_progress(
+            &collection,
+            EngineProgress::Uploading {
+                count: outgoing.len(),
+            },
+        );
+    }
     let upload_info = CollectionUpdate::new_from_changeset(
         client,
         &coll_state,
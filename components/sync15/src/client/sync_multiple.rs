@@ -6,7 +6,9 @@
 // global and local state between syncs.
 
 use super::state::{EngineChangesNeeded, GlobalState, PersistedGlobalState, SetupStateMachine};
-use super::status::{ServiceStatus, SyncResult};
+use super::status::{
+    EngineProgress, EngineSkipReason, ServiceStatus, SyncProgressListener, SyncResult,
+};
 use super::storage_client::{BackoffListener, Sync15StorageClient, Sync15StorageClientInit};
 use crate::clients_engine::{self, CommandProcessor, CLIENTS_TTL_REFRESH};
 use crate::engine::{EngineSyncAssociation, SyncEngine};
@@ -86,10 +88,10 @@ impl MemoryCachedState {
 ///   configured.
 /// * `root_sync_key` - The KeyBundle used for encryption.
 ///
-/// Returns a map, keyed by name and holding an error value - if any engine
-/// fails, the sync will continue on to other engines, but the error will be
-/// places in this map. The absence of a name in the map implies the engine
-/// succeeded.
+/// Returns the result for each engine which was synced - if any engine fails,
+/// the sync will continue on to other engines, but the error will be placed
+/// in `engine_results`. Engines which weren't synced, because something they
+/// depend on failed or the sync ended early, are in `skipped_engines`.
 pub fn sync_multiple(
     engines: &[&dyn SyncEngine],
     persisted_global_state: &mut Option<String>,
@@ -132,15 +134,17 @@ pub fn sync_multiple_with_command_processor(
         declined: None,
         next_sync_after: None,
         engine_results: HashMap::with_capacity(engines.len()),
+        skipped_engines: HashMap::new(),
         telemetry: telemetry::SyncTelemetryPing::new(),
     };
     let backoff = super::storage_client::new_backoff_listener();
     let req_info = req_info.unwrap_or_default();
-    let driver = SyncMultipleDriver {
+    let mut driver = SyncMultipleDriver {
         command_processor,
         engines,
         storage_init,
         interruptee,
+        progress_listener: req_info.progress_listener,
         engines_to_state_change: req_info.engines_to_state_change,
         backoff: backoff.clone(),
         root_sync_key,
@@ -149,8 +153,11 @@ pub fn sync_multiple_with_command_processor(
         mem_cached_state,
         saw_auth_error: false,
         ignore_soft_backoff: req_info.is_user_action,
+        skip_reason: EngineSkipReason::KeysFailed,
     };
-    match driver.sync() {
+    let result = driver.sync();
+    driver.note_skipped_engines();
+    match result {
         Ok(()) => {
             log::debug!(
                 "sync was successful, final status={:?}",
@@ -177,10 +184,21 @@ pub fn sync_multiple_with_command_processor(
 /// This is essentially a bag of information that the sync manager knows, but
 /// otherwise we won't. It should probably be rethought if it gains many more
 /// fields.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct SyncRequestInfo<'a> {
     pub engines_to_state_change: Option<&'a HashMap<String, bool>>,
     pub is_user_action: bool,
+    pub progress_listener: Option<&'a dyn SyncProgressListener>,
+}
+
+// `SyncProgressListener` isn't `Debug`, so we leave the listener out.
+impl std::fmt::Debug for SyncRequestInfo<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncRequestInfo")
+            .field("engines_to_state_change", &self.engines_to_state_change)
+            .field("is_user_action", &self.is_user_action)
+            .finish_non_exhaustive()
+    }
 }
 
 // The sync multiple driver
@@ -190,6 +208,7 @@ struct SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
     storage_init: &'info Sync15StorageClientInit,
     root_sync_key: &'info KeyBundle,
     interruptee: &'info dyn Interruptee,
+    progress_listener: Option<&'info dyn SyncProgressListener>,
     backoff: BackoffListener,
     engines_to_state_change: Option<&'info HashMap<String, bool>>,
     result: &'res mut SyncResult,
@@ -197,11 +216,14 @@ struct SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
     mem_cached_state: &'mcs mut MemoryCachedState,
     ignore_soft_backoff: bool,
     saw_auth_error: bool,
+    // Why any engines we haven't synced yet will be skipped if the sync ends
+    // now. This tracks how far we've got through the things engines depend on.
+    skip_reason: EngineSkipReason,
 }
 
 impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
     /// The actual worker for sync_multiple.
-    fn sync(mut self) -> result::Result<(), Error> {
+    fn sync(&mut self) -> result::Result<(), Error> {
         log::info!("Loading/initializing persisted state");
         let mut pgs = self.prepare_persisted_state();
 
@@ -215,15 +237,26 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
         log::info!("Entering sync state machine");
         // Advance the state machine to the point where it can perform a full
         // sync. This may involve uploading meta/global, crypto/keys etc.
-        let mut global_state = self.run_state_machine(&client_info, &mut pgs)?;
+        let global_state = self.run_state_machine(&client_info, &mut pgs)?;
 
         if self.was_interrupted() {
             return Ok(());
         }
 
+        self.sync_with_global_state(client_info, global_state)
+    }
+
+    /// Syncs the clients engine, then the other engines, once the state
+    /// machine has set up the global state.
+    fn sync_with_global_state(
+        &mut self,
+        client_info: ClientInfo,
+        mut global_state: GlobalState,
+    ) -> result::Result<(), Error> {
         // Set the service status to OK here - we may adjust it based on an individual
         // engine failing.
         self.result.service_status = ServiceStatus::Ok;
+        self.skip_reason = EngineSkipReason::ClientsFailed;
 
         let clients_engine = if let Some(command_processor) = self.command_processor {
             log::info!("Synchronizing clients engine");
@@ -249,6 +282,10 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
             // syncs, since we only keep client records in memory, we
             // expect the counts to be the same most times, and a
             // failure aborts the entire sync.
+            // We do, however, record any commands we applied.
+            for event in engine.events.drain(..) {
+                self.result.telemetry.event(event);
+            }
             if self.was_interrupted() {
                 return Ok(());
             }
@@ -257,6 +294,7 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
         } else {
             None
         };
+        self.skip_reason = EngineSkipReason::Aborted;
 
         log::info!("Synchronizing engines");
 
@@ -279,12 +317,54 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
         if self.interruptee.was_interrupted() {
             log::info!("Interrupted, bailing out");
             self.result.service_status = ServiceStatus::Interrupted;
+            self.skip_reason = EngineSkipReason::Interrupted;
             true
         } else {
             false
         }
     }
 
+    fn note_progress(&self, engine: &str, progress: EngineProgress) {
+        if let Some(listener) = self.progress_listener {
+            listener.on_engine_progress(engine, progress);
+        }
+    }
+
+    /// Records every engine which wasn't synced, and isn't declined, as
+    /// skipped. Called once the sync has finished, successfully or not.
+    fn note_skipped_engines(&mut self) {
+        // An interruption can also surface as an error from a dependency.
+        if self.result.service_status == ServiceStatus::Interrupted {
+            self.skip_reason = EngineSkipReason::Interrupted;
+        }
+        // `result.declined` is only set once the state machine has run, so fall
+        // back to the declined engines we persisted after the last sync.
+        let declined = match &self.result.declined {
+            Some(declined) => declined.clone(),
+            None => self.persisted_declined(),
+        };
+        for engine in self.engines {
+            let name = engine.collection_name();
+            if self.result.engine_results.contains_key(&*name)
+                || declined.iter().any(|e| e == &*name)
+            {
+                continue;
+            }
+            log::info!("Skipped the {} engine: {:?}", name, self.skip_reason);
+            self.result
+                .skipped_engines
+                .insert(name.into(), self.skip_reason);
+        }
+    }
+
+    fn persisted_declined(&self) -> Vec<String> {
+        self.persisted_global_state
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<PersistedGlobalState>(s).ok())
+            .map(|pgs| pgs.get_declined().to_vec())
+            .unwrap_or_default()
+    }
+
     fn sync_engines(
         &mut self,
         client_info: &ClientInfo,
@@ -300,6 +380,7 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
                 .is_some()
             {
                 log::warn!("Got backoff, bailing out of sync early");
+                self.skip_reason = EngineSkipReason::BackedOff;
                 break;
             }
             if global_state.global.declined.iter().any(|e| e == &*name) {
@@ -307,6 +388,7 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
                 continue;
             }
             log::info!("Syncing {} engine!", name);
+            self.note_progress(&name, EngineProgress::Started);
 
             let mut telem_engine = telemetry::Engine::new(&*name);
             let result = super::sync::synchronize_with_clients_engine(
@@ -318,13 +400,28 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
                 true,
                 &mut telem_engine,
                 self.interruptee,
+                self.progress_listener,
             );
 
             match result {
-                Ok(()) => log::info!("Sync of {} was successful!", name),
+                Ok(()) => {
+                    log::info!("Sync of {} was successful!", name);
+                    self.note_progress(
+                        &name,
+                        EngineProgress::Finished {
+                            status: ServiceStatus::Ok,
+                        },
+                    );
+                }
                 Err(ref e) => {
                     log::warn!("Sync of {} failed! {:?}", name, e);
                     let this_status = ServiceStatus::from_err(e);
+                    self.note_progress(
+                        &name,
+                        EngineProgress::Finished {
+                            status: this_status.clone(),
+                        },
+                    );
                     // The only error which forces us to discard our state is an
                     // auth error.
                     self.saw_auth_error =
@@ -489,3 +586,288 @@ impl<'info, 'res, 'pgs, 'mcs> SyncMultipleDriver<'info, 'res, 'pgs, 'mcs> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::collection_keys::CollectionKeys;
+    use super::super::request::{InfoCollections, InfoConfiguration};
+    use super::*;
+    use crate::bso::{IncomingBso, OutgoingBso};
+    use crate::clients_engine::{Command, CommandStatus, Settings};
+    use crate::engine::CollectionRequest;
+    use crate::record_types::{MetaGlobalEngine, MetaGlobalRecord};
+    use crate::{CollectionName, DeviceType, Guid, ServerTimestamp};
+    use interrupt_support::NeverInterrupts;
+    use std::collections::HashSet;
+    use url::Url;
+
+    // No viaduct backend is set up in these tests, so every request fails
+    // with a network error.
+
+    struct TestEngine(&'static str);
+
+    impl SyncEngine for TestEngine {
+        fn collection_name(&self) -> CollectionName {
+            self.0.into()
+        }
+
+        fn stage_incoming(
+            &self,
+            _inbound: Vec<IncomingBso>,
+            _telem: &mut telemetry::Engine,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn apply(
+            &self,
+            _timestamp: ServerTimestamp,
+            _telem: &mut telemetry::Engine,
+        ) -> anyhow::Result<Vec<OutgoingBso>> {
+            Ok(vec![])
+        }
+
+        fn set_uploaded(
+            &self,
+            _new_timestamp: ServerTimestamp,
+            _ids: Vec<Guid>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn get_collection_request(
+            &self,
+            server_timestamp: ServerTimestamp,
+        ) -> anyhow::Result<Option<CollectionRequest>> {
+            Ok(Some(
+                CollectionRequest::new(self.collection_name())
+                    .full()
+                    .newer_than(server_timestamp),
+            ))
+        }
+
+        fn get_sync_assoc(&self) -> anyhow::Result<EngineSyncAssociation> {
+            Ok(EngineSyncAssociation::Disconnected)
+        }
+
+        fn reset(&self, _assoc: &EngineSyncAssociation) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct TestProcessor(Settings);
+
+    impl CommandProcessor for TestProcessor {
+        fn settings(&self) -> &Settings {
+            &self.0
+        }
+
+        fn fetch_outgoing_commands(&self) -> anyhow::Result<HashSet<Command>> {
+            Ok(HashSet::new())
+        }
+
+        fn apply_incoming_command(&self, _command: Command) -> anyhow::Result<CommandStatus> {
+            Ok(CommandStatus::Ignored)
+        }
+    }
+
+    struct AlwaysInterrupts;
+
+    impl Interruptee for AlwaysInterrupts {
+        fn was_interrupted(&self) -> bool {
+            true
+        }
+    }
+
+    fn storage_init() -> Sync15StorageClientInit {
+        Sync15StorageClientInit {
+            key_id: "key-id".into(),
+            access_token: "access-token".into(),
+            tokenserver_url: Url::parse("https://token.example.com").unwrap(),
+        }
+    }
+
+    fn persisted_declined(declined: &[&str]) -> Option<String> {
+        let pgs = PersistedGlobalState::V2 {
+            declined: Some(declined.iter().map(|s| s.to_string()).collect()),
+        };
+        Some(serde_json::to_string(&pgs).unwrap())
+    }
+
+    fn skipped(result: &SyncResult) -> Vec<(&str, EngineSkipReason)> {
+        let mut skipped = result
+            .skipped_engines
+            .iter()
+            .map(|(name, reason)| (name.as_str(), *reason))
+            .collect::<Vec<_>>();
+        skipped.sort_by(|a, b| a.0.cmp(b.0));
+        skipped
+    }
+
+    /// Syncs the engines with a global state which enables all of them, as if
+    /// the state machine had succeeded.
+    fn sync_with_global_state(
+        engines: &[&dyn SyncEngine],
+        command_processor: Option<&dyn CommandProcessor>,
+        backoff_secs: u32,
+    ) -> SyncResult {
+        let root_sync_key = KeyBundle::new_random().unwrap();
+        let global_state = GlobalState {
+            config: InfoConfiguration::default(),
+            collections: InfoCollections::default(),
+            global: MetaGlobalRecord {
+                sync_id: Guid::random(),
+                storage_version: 5,
+                engines: engines
+                    .iter()
+                    .map(|engine| {
+                        (
+                            engine.collection_name().into_owned(),
+                            MetaGlobalEngine {
+                                version: 1,
+                                sync_id: Guid::random(),
+                            },
+                        )
+                    })
+                    .collect(),
+                declined: vec![],
+            },
+            global_timestamp: ServerTimestamp(0),
+            keys: CollectionKeys::new_random()
+                .unwrap()
+                .to_encrypted_payload(&root_sync_key)
+                .unwrap(),
+            keys_timestamp: ServerTimestamp(0),
+        };
+        let storage_init = storage_init();
+        let client_info = ClientInfo::new(&storage_init).unwrap();
+        let mut result = SyncResult {
+            service_status: ServiceStatus::OtherError,
+            result: Ok(()),
+            declined: None,
+            next_sync_after: None,
+            engine_results: HashMap::new(),
+            skipped_engines: HashMap::new(),
+            telemetry: telemetry::SyncTelemetryPing::new(),
+        };
+        let mut persisted_global_state = None;
+        let mut mem_cached_state = MemoryCachedState::default();
+        let mut driver = SyncMultipleDriver {
+            command_processor,
+            engines,
+            storage_init: &storage_init,
+            root_sync_key: &root_sync_key,
+            interruptee: &NeverInterrupts,
+            progress_listener: None,
+            backoff: super::super::storage_client::new_backoff_listener(),
+            engines_to_state_change: None,
+            result: &mut result,
+            persisted_global_state: &mut persisted_global_state,
+            mem_cached_state: &mut mem_cached_state,
+            ignore_soft_backoff: false,
+            saw_auth_error: false,
+            skip_reason: EngineSkipReason::KeysFailed,
+        };
+        driver.backoff.note_backoff(backoff_secs);
+        let _ = driver.sync_with_global_state(client_info, global_state);
+        driver.note_skipped_engines();
+        result
+    }
+
+    #[test]
+    fn test_skipped_when_keys_failed() {
+        let engines: &[&dyn SyncEngine] = &[&TestEngine("bookmarks"), &TestEngine("history")];
+        let mut persisted_global_state = persisted_declined(&["bookmarks"]);
+        let result = sync_multiple(
+            engines,
+            &mut persisted_global_state,
+            &mut MemoryCachedState::default(),
+            &storage_init(),
+            &KeyBundle::new_random().unwrap(),
+            &NeverInterrupts,
+            None,
+        );
+        assert!(result.result.is_err());
+        assert!(result.engine_results.is_empty());
+        // Declined engines aren't skipped.
+        assert_eq!(
+            skipped(&result),
+            vec![("history", EngineSkipReason::KeysFailed)]
+        );
+    }
+
+    #[test]
+    fn test_skipped_when_interrupted() {
+        let engines: &[&dyn SyncEngine] = &[&TestEngine("bookmarks"), &TestEngine("history")];
+        let mut persisted_global_state = persisted_declined(&["bookmarks"]);
+        let result = sync_multiple(
+            engines,
+            &mut persisted_global_state,
+            &mut MemoryCachedState::default(),
+            &storage_init(),
+            &KeyBundle::new_random().unwrap(),
+            &AlwaysInterrupts,
+            None,
+        );
+        assert_eq!(result.service_status, ServiceStatus::Interrupted);
+        // We were interrupted before the state machine ran, so the declined
+        // engines come from the persisted state.
+        assert_eq!(result.declined, None);
+        assert_eq!(
+            skipped(&result),
+            vec![("history", EngineSkipReason::Interrupted)]
+        );
+    }
+
+    #[test]
+    fn test_skipped_when_clients_failed() {
+        let processor = TestProcessor(Settings {
+            fxa_device_id: "device-id".into(),
+            device_name: "Test Device".into(),
+            device_type: DeviceType::Desktop,
+        });
+        let result = sync_with_global_state(
+            &[&TestEngine("bookmarks"), &TestEngine("history")],
+            Some(&processor),
+            0,
+        );
+        assert!(result.engine_results.is_empty());
+        assert_eq!(
+            skipped(&result),
+            vec![
+                ("bookmarks", EngineSkipReason::ClientsFailed),
+                ("history", EngineSkipReason::ClientsFailed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skipped_when_aborted() {
+        let result =
+            sync_with_global_state(&[&TestEngine("bookmarks"), &TestEngine("history")], None, 0);
+        // The network error from the first engine ends the sync.
+        assert_eq!(result.service_status, ServiceStatus::NetworkError);
+        assert!(result.engine_results["bookmarks"].is_err());
+        assert_eq!(
+            skipped(&result),
+            vec![("history", EngineSkipReason::Aborted)]
+        );
+    }
+
+    #[test]
+    fn test_skipped_when_backed_off() {
+        let result = sync_with_global_state(
+            &[&TestEngine("bookmarks"), &TestEngine("history")],
+            None,
+            60,
+        );
+        assert!(result.engine_results.is_empty());
+        assert_eq!(
+            skipped(&result),
+            vec![
+                ("bookmarks", EngineSkipReason::BackedOff),
+                ("history", EngineSkipReason::BackedOff),
+            ]
+        );
+    }
+}
@@ -11,6 +11,7 @@ use crate::client::{
 };
 use crate::client_types::{ClientData, RemoteClient};
 use crate::engine::CollectionRequest;
+use crate::telemetry;
 use crate::{error::Result, Guid, KeyBundle};
 use interrupt_support::Interruptee;
 
@@ -29,6 +30,7 @@ struct Driver<'a> {
     interruptee: &'a dyn Interruptee,
     config: &'a InfoConfiguration,
     recent_clients: HashMap<String, RemoteClient>,
+    events: Vec<telemetry::Event>,
 }
 
 impl<'a> Driver<'a> {
@@ -42,6 +44,7 @@ impl<'a> Driver<'a> {
             interruptee,
             config,
             recent_clients: HashMap::new(),
+            events: Vec::new(),
         }
     }
 
@@ -87,16 +90,30 @@ impl<'a> Driver<'a> {
                 has_own_client_record = true;
                 let mut current_client_record = self.current_client_record();
                 for c in &client.commands {
-                    let status = match c.as_command() {
-                        Some(command) => self.command_processor.apply_incoming_command(command)?,
-                        None => CommandStatus::Unsupported,
+                    let (status, name) = match c.as_command() {
+                        Some(command) => {
+                            let name = command.name();
+                            let status = self.command_processor.apply_incoming_command(command)?;
+                            (status, Some(name))
+                        }
+                        None => (CommandStatus::Unsupported, None),
                     };
-                    match status {
-                        CommandStatus::Applied => {}
-                        CommandStatus::Ignored => {
+                    match (status, name) {
+                        (CommandStatus::Applied, Some(name)) => {
+                            // Record the command in the sync ping, so that
+                            // the sender's flow can be matched up with ours.
+                            let mut event = telemetry::Event::new("processcommand", name);
+                            if let Some(flow_id) = &c.flow_id {
+                                event = event.extra("flowID", flow_id.clone());
+                            }
+                            self.events.push(event);
+                        }
+                        (CommandStatus::Ignored, _) => {
                             log::debug!("Ignored command {:?}", c);
                         }
-                        CommandStatus::Unsupported => {
+                        // Commands we couldn't parse are never applied, but
+                        // if they were, we'd still not understand them.
+                        (CommandStatus::Unsupported, _) | (CommandStatus::Applied, None) => {
                             log::warn!("Don't know how to apply command {:?}", c);
                             current_client_record.commands.push(c.clone());
                         }
@@ -237,6 +254,8 @@ pub struct Engine<'a> {
     pub command_processor: &'a dyn CommandProcessor,
     pub interruptee: &'a dyn Interruptee,
     pub recent_clients: HashMap<String, RemoteClient>,
+    /// Telemetry events for the commands we applied during the last sync.
+    pub events: Vec<telemetry::Event>,
 }
 
 impl<'a> Engine<'a> {
@@ -250,6 +269,7 @@ impl<'a> Engine<'a> {
             command_processor,
             interruptee,
             recent_clients: HashMap::new(),
+            events: Vec::new(),
         }
     }
 
@@ -303,6 +323,7 @@ impl<'a> Engine<'a> {
 
         let outgoing = driver.sync(inbound, should_refresh_client)?;
         self.recent_clients = driver.recent_clients;
+        self.events = driver.events;
 
         self.interruptee.err_if_interrupted()?;
         let upload_info = CollectionUpdate::new_from_changeset(
@@ -501,10 +522,6 @@ mod tests {
             "name": "Laptop",
             "type": "desktop",
             "commands": [{
-                "command": "displayURI",
-                "args": ["http://example.com", "Fennec", "Example page"],
-                "flowID": "flooooooooow",
-            }, {
                 "command": "resetEngine",
                 "args": ["forms"],
             }, {
@@ -622,6 +639,54 @@ mod tests {
         assert_eq!(actual_remote_clients, expected_remote_clients);
     }
 
+    #[test]
+    fn test_applied_commands_recorded() {
+        let processor = TestProcessor {
+            settings: Settings {
+                fxa_device_id: "deviceAAAAAA".into(),
+                device_name: "Laptop".into(),
+                device_type: DeviceType::Desktop,
+            },
+            outgoing_commands: HashSet::new(),
+        };
+
+        let config = InfoConfiguration::default();
+
+        let mut driver = Driver::new(&processor, &NeverInterrupts, &config);
+
+        let inbound = inbound_from_clients(json!([{
+            "id": "deviceAAAAAA",
+            "name": "Laptop",
+            "type": "desktop",
+            "commands": [{
+                "command": "resetEngine",
+                "args": ["history"],
+                "flowID": "flooooooooow",
+            }, {
+                "command": "wipeEngine",
+                "args": ["logins"],
+            }, {
+                "command": "resetEngine",
+                "args": ["forms"],
+            }],
+            "fxaDeviceId": "deviceAAAAAA",
+        }]));
+
+        driver.sync(inbound, false).expect("Should sync clients");
+
+        // Only the command which was applied should be recorded.
+        assert_eq!(
+            serde_json::to_value(&driver.events).unwrap(),
+            json!([{
+                "object": "processcommand",
+                "method": "resetEngine",
+                "extra": {
+                    "flowID": "flooooooooow",
+                },
+            }])
+        );
+    }
+
     #[test]
     fn test_clients_sync_explicit_refresh() {
         let processor = TestProcessor {
@@ -13,6 +13,12 @@
 //! desktop will delete all local bookmarks then replace them with the backed
 //! up set, which without a "wipe" command would almost certainly cause other
 //! connected devices to "resurrect" the deleted bookmarks.
+//! Older desktop versions also use the legacy `displayURI` command to send
+//! tabs to other devices; these are handed to the command processor so the
+//! sync manager can surface them to the app.
+//! The `wipeExtensionStorage` command clears the synced storage of a single
+//! extension. As with all commands, the sender can tell it was handled once
+//! it's been removed from the target's client record.
 use std::collections::HashSet;
 
 mod engine;
@@ -90,4 +96,27 @@ pub enum Command {
     ResetAll,
     /// Resets local sync state for a specific engine.
     Reset(String),
+    /// Asks this client to display a URI. This is the legacy "send tab"
+    /// command which is still sent by some desktop versions.
+    DisplayUri {
+        uri: String,
+        sender_id: String,
+        title: String,
+    },
+    /// Clears the `storage.sync` data stored for a specific extension, used to
+    /// troubleshoot an extension remotely.
+    WipeExtension(String),
+}
+
+impl Command {
+    /// The name of this command, as used in client records and telemetry.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Wipe(_) => "wipeEngine",
+            Command::ResetAll => "resetAll",
+            Command::Reset(_) => "resetEngine",
+            Command::DisplayUri { .. } => "displayURI",
+            Command::WipeExtension(_) => "wipeExtensionStorage",
+        }
+    }
 }
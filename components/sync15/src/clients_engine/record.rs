@@ -112,12 +112,38 @@ impl CommandRecord {
         }
     }
 
+    // `displayURI` takes the URI, the sender's client ID and, optionally, the
+    // title of the page. Older clients have been known to send a null title.
+    fn get_display_uri_args(&self) -> Option<Command> {
+        let cmd_name = &self.name;
+        if self.args.len() != 2 && self.args.len() != 3 {
+            log::error!(
+                "Incoming '{cmd_name}' command has wrong number of arguments ({})",
+                self.args.len()
+            );
+            return None;
+        }
+        match (&self.args[0], &self.args[1]) {
+            (Some(uri), Some(sender_id)) => Some(Command::DisplayUri {
+                uri: uri.clone(),
+                sender_id: sender_id.clone(),
+                title: self.args.get(2).cloned().flatten().unwrap_or_default(),
+            }),
+            _ => {
+                log::error!("Incoming '{cmd_name}' command has null argument");
+                None
+            }
+        }
+    }
+
     /// Converts a serialized command into one that we can apply. Returns `None`
     /// if we don't support the command.
     pub fn as_command(&self) -> Option<Command> {
         match self.name.as_str() {
             "wipeEngine" => self.get_single_string_arg().map(Command::Wipe),
             "resetEngine" => self.get_single_string_arg().map(Command::Reset),
+            "displayURI" => self.get_display_uri_args(),
+            "wipeExtensionStorage" => self.get_single_string_arg().map(Command::WipeExtension),
             "resetAll" => {
                 if self.args.is_empty() {
                     Some(Command::ResetAll)
@@ -150,6 +176,20 @@ impl From<Command> for CommandRecord {
                 args: Vec::new(),
                 flow_id: None,
             },
+            Command::DisplayUri {
+                uri,
+                sender_id,
+                title,
+            } => CommandRecord {
+                name: "displayURI".into(),
+                args: vec![Some(uri), Some(sender_id), Some(title)],
+                flow_id: None,
+            },
+            Command::WipeExtension(ext_id) => CommandRecord {
+                name: "wipeExtensionStorage".into(),
+                args: vec![Some(ext_id)],
+                flow_id: None,
+            },
         }
     }
 }
@@ -171,6 +211,41 @@ mod test {
         let ser = serde_json::json!({"command": "resetAll"});
         let record: CommandRecord = serde_json::from_value(ser).unwrap();
         assert_eq!(record.as_command(), Some(Command::ResetAll));
+
+        let ser = serde_json::json!({"command": "wipeExtensionStorage", "args": ["{ext-id}"]});
+        let record: CommandRecord = serde_json::from_value(ser).unwrap();
+        assert_eq!(
+            record.as_command(),
+            Some(Command::WipeExtension("{ext-id}".to_string()))
+        );
+
+        let ser = serde_json::json!({
+            "command": "displayURI",
+            "args": ["https://example.com", "deviceAAAAAA", "Example"],
+        });
+        let record: CommandRecord = serde_json::from_value(ser).unwrap();
+        assert_eq!(
+            record.as_command(),
+            Some(Command::DisplayUri {
+                uri: "https://example.com".to_string(),
+                sender_id: "deviceAAAAAA".to_string(),
+                title: "Example".to_string(),
+            })
+        );
+
+        let ser = serde_json::json!({
+            "command": "displayURI",
+            "args": ["https://example.com", "deviceAAAAAA", null],
+        });
+        let record: CommandRecord = serde_json::from_value(ser).unwrap();
+        assert_eq!(
+            record.as_command(),
+            Some(Command::DisplayUri {
+                uri: "https://example.com".to_string(),
+                sender_id: "deviceAAAAAA".to_string(),
+                title: "".to_string(),
+            })
+        );
     }
 
     #[test]
@@ -193,6 +268,14 @@ mod test {
         let ser = serde_json::json!({"command": "resetAll", "args": ["foo"]});
         let record: CommandRecord = serde_json::from_value(ser).unwrap();
         assert_eq!(record.as_command(), None);
+
+        let ser = serde_json::json!({"command": "displayURI", "args": ["https://example.com"]});
+        let record: CommandRecord = serde_json::from_value(ser).unwrap();
+        assert_eq!(record.as_command(), None);
+
+        let ser = serde_json::json!({"command": "displayURI", "args": [null, "deviceAAAAAA"]});
+        let record: CommandRecord = serde_json::from_value(ser).unwrap();
+        assert_eq!(record.as_command(), None);
     }
 
     #[test]
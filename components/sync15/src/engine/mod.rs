@@ -26,10 +26,12 @@
 //! types and payload management used by these traits, then to combine the
 //! requirements into a single trait that captures both use-cases.
 mod bridged_engine;
+mod registry;
 mod request;
 mod sync_engine;
 
 pub use bridged_engine::{ApplyResults, BridgedEngine, BridgedEngineAdaptor};
+pub use registry::EngineRegistry;
 #[cfg(feature = "sync-client")]
 pub(crate) use request::CollectionPost;
 
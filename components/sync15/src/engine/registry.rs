@@ -0,0 +1,140 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Crates which implement sync engines don't link with the sync manager, so
+//! instead they "offer" their store to it by registering it in an
+//! [EngineRegistry]. When the sync manager asks the crate for an engine, the
+//! crate creates it from the registered store.
+//!
+//! The registry only keeps weak references, so registering a store doesn't
+//! keep it alive - once the app drops the store, it's no longer registered.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+/// The stores a crate has registered with the sync manager.
+pub struct EngineRegistry<T> {
+    // The store registered with `register()`.
+    store: Mutex<Weak<T>>,
+    // Stores registered for a specific account, for apps which have more than
+    // one account (eg, one per profile) in the same process.
+    stores_for_accounts: Mutex<HashMap<String, Weak<T>>>,
+}
+
+impl<T> EngineRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            store: Mutex::new(Weak::new()),
+            stores_for_accounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers the store used by sync managers that aren't for a specific
+    /// account, replacing any store registered before.
+    pub fn register(&self, store: &Arc<T>) {
+        *self.store.lock().unwrap() = Arc::downgrade(store);
+    }
+
+    /// Registers the store used by sync managers for `account_id`, so stores
+    /// belonging to different accounts don't replace each other.
+    pub fn register_for_account(&self, account_id: impl Into<String>, store: &Arc<T>) {
+        let mut stores = self.stores_for_accounts.lock().unwrap();
+        stores.retain(|_, weak| weak.strong_count() > 0);
+        stores.insert(account_id.into(), Arc::downgrade(store));
+    }
+
+    pub fn unregister(&self) {
+        *self.store.lock().unwrap() = Weak::new();
+    }
+
+    pub fn unregister_for_account(&self, account_id: &str) {
+        self.stores_for_accounts.lock().unwrap().remove(account_id);
+    }
+
+    /// Returns the store registered with `register()`, if it's still alive.
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.store.lock().unwrap().upgrade()
+    }
+
+    /// Returns the store registered for `account_id`, if it's still alive.
+    pub fn get_for_account(&self, account_id: &str) -> Option<Arc<T>> {
+        self.stores_for_accounts
+            .lock()
+            .unwrap()
+            .get(account_id)
+            .and_then(Weak::upgrade)
+    }
+}
+
+impl<T> Default for EngineRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_does_not_keep_the_store_alive() {
+        let registry = EngineRegistry::new();
+        assert!(registry.get().is_none());
+
+        let store = Arc::new("store");
+        registry.register(&store);
+        assert_eq!(Arc::strong_count(&store), 1);
+        assert_eq!(Arc::weak_count(&store), 1);
+        let registered = registry.get().expect("should be registered");
+        assert!(Arc::ptr_eq(&store, &registered));
+        drop(registered);
+        assert_eq!(Arc::strong_count(&store), 1);
+
+        // dropping the store should drop the registration.
+        drop(store);
+        assert!(registry.get().is_none());
+    }
+
+    #[test]
+    fn test_register_replaces_the_previous_store() {
+        let registry = EngineRegistry::new();
+        let store1 = Arc::new("store1");
+        let store2 = Arc::new("store2");
+        registry.register(&store1);
+        registry.register(&store2);
+        assert!(Arc::ptr_eq(&registry.get().unwrap(), &store2));
+        assert_eq!(Arc::weak_count(&store1), 0);
+
+        registry.unregister();
+        assert!(registry.get().is_none());
+    }
+
+    #[test]
+    fn test_registration_for_account() {
+        let registry = EngineRegistry::new();
+        let store1 = Arc::new("store1");
+        let store2 = Arc::new("store2");
+        registry.register_for_account("account-1", &store1);
+        registry.register_for_account("account-2", &store2);
+        assert!(Arc::ptr_eq(
+            &registry.get_for_account("account-1").unwrap(),
+            &store1
+        ));
+        assert!(registry.get_for_account("account-3").is_none());
+        // stores registered for an account aren't used for other sync managers.
+        assert!(registry.get().is_none());
+
+        // dropping one account's store shouldn't impact the other.
+        drop(store1);
+        assert!(registry.get_for_account("account-1").is_none());
+        assert!(Arc::ptr_eq(
+            &registry.get_for_account("account-2").unwrap(),
+            &store2
+        ));
+
+        registry.unregister_for_account("account-2");
+        assert!(registry.get_for_account("account-2").is_none());
+        assert_eq!(Arc::weak_count(&store2), 0);
+    }
+}
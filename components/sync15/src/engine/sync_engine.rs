@@ -7,7 +7,9 @@ use crate::bso::{IncomingBso, OutgoingBso};
 use crate::client_types::ClientData;
 use crate::{telemetry, CollectionName, Guid, ServerTimestamp};
 use anyhow::Result;
+use interrupt_support::SqlInterruptHandle;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CollSyncIds {
@@ -232,6 +234,15 @@ pub trait SyncEngine {
     fn wipe(&self) -> Result<()> {
         unimplemented!("The engine does not implement wipe, no wipe should be requested")
     }
+
+    /// Returns a handle which interrupts the engine's database work, such as
+    /// applying incoming records. The sync manager uses this to stop a long
+    /// `apply()` when a sync is interrupted from another thread.
+    ///
+    /// Engines which don't return a handle only stop between network requests.
+    fn interrupt_handle(&self) -> Option<Arc<SqlInterruptHandle>> {
+        None
+    }
 }
 
 #[cfg(test)]
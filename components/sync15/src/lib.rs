@@ -48,3 +48,6 @@ fn skip_if_default<T: PartialEq + Default>(v: &T) -> bool {
 }
 
 uniffi::include_scaffolding!("sync15");
+
+pub const UNIFFI_NAMESPACE: &str = "sync15";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -19,6 +19,14 @@ open class SyncManagerComponent {
         return try api.sync(params: params)
     }
 
+    public func syncWithProgress(params: SyncParams, listener: SyncProgressListener) throws -> SyncResult {
+        return try api.syncWithProgress(params: params, listener: listener)
+    }
+
+    public func interrupt() {
+        api.interrupt()
+    }
+
     public func getAvailableEngines() -> [String] {
         return api.getAvailableEngines()
     }
@@ -15,25 +15,8 @@ pub use error::{Result, SyncManagerError};
 pub use types::*;
 
 use manager::SyncManager;
-use parking_lot::Mutex;
-
-lazy_static::lazy_static! {
-    static ref MANAGER: Mutex<SyncManager> = Mutex::new(SyncManager::new());
-}
-
-pub(crate) fn wipe(engine: &str) -> Result<()> {
-    let manager = MANAGER.lock();
-    manager.wipe(engine)
-}
-
-pub(crate) fn reset(engine: &str) -> Result<()> {
-    let manager = MANAGER.lock();
-    manager.reset(engine)
-}
-
-pub(crate) fn reset_all() -> Result<()> {
-    let manager = MANAGER.lock();
-    manager.reset_all()
-}
 
 uniffi::include_scaffolding!("syncmanager");
+
+pub const UNIFFI_NAMESPACE: &str = "syncmanager";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
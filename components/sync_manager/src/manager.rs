@@ -3,12 +3,17 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use crate::error::*;
-use crate::types::{ServiceStatus, SyncEngineSelection, SyncParams, SyncReason, SyncResult};
-use crate::{reset, reset_all, wipe};
+use crate::types::{
+    EngineProgress, EngineSkipReason, ReceivedUri, ServiceStatus, SyncEngineSelection, SyncParams,
+    SyncProgressListener, SyncReason, SyncResult,
+};
 use error_support::breadcrumb;
+use interrupt_support::{in_shutdown, Interruptee, SqlInterruptHandle};
 use parking_lot::Mutex;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::SystemTime;
 use sync15::client::{
     sync_multiple_with_command_processor, MemoryCachedState, Sync15StorageClientInit,
@@ -20,6 +25,15 @@ use sync15::engine::{EngineSyncAssociation, SyncEngine, SyncEngineId};
 #[derive(Default)]
 pub struct SyncManager {
     mem_cached_state: Mutex<Option<MemoryCachedState>>,
+    // When set, we only sync the stores which were registered for this
+    // account rather than the ones registered with `register_with_sync_manager()`.
+    account_id: Option<String>,
+    // Incremented by `interrupt()`. A sync is interrupted if this changes
+    // while it's running.
+    interrupt_counter: AtomicUsize,
+    // The interrupt handles of the engines in the running sync, so that
+    // `interrupt()` can also stop their database work.
+    engine_interrupt_handles: Mutex<Vec<Arc<SqlInterruptHandle>>>,
 }
 
 impl SyncManager {
@@ -27,11 +41,54 @@ impl SyncManager {
         Self::default()
     }
 
+    /// Create a sync manager which uses the stores registered with
+    /// `register_with_sync_manager_for_account()` for this account.
+    pub fn new_for_account(account_id: String) -> Self {
+        Self {
+            account_id: Some(account_id),
+            ..Self::default()
+        }
+    }
+
+    /// Interrupt the sync which is currently running, if any. The sync stops
+    /// at the next network request or engine, and engines which are applying
+    /// or uploading records stop their database work. Engines which weren't
+    /// synced are reported as skipped with `EngineSkipReason::Interrupted`.
+    /// Syncs started after this call aren't affected.
+    pub fn interrupt(&self) {
+        self.interrupt_counter.fetch_add(1, Ordering::Relaxed);
+        for handle in self.engine_interrupt_handles.lock().iter() {
+            handle.interrupt();
+        }
+    }
+
+    // Remember the engines' interrupt handles for `interrupt()`, and interrupt
+    // them now if the sync was interrupted before we got here.
+    fn set_engine_interrupt_handles(
+        &self,
+        engines: &[&dyn SyncEngine],
+        interruptee: &SyncInterruptee<'_>,
+    ) {
+        let mut handles = self.engine_interrupt_handles.lock();
+        *handles = engines
+            .iter()
+            .filter_map(|e| e.interrupt_handle())
+            .collect();
+        if interruptee.was_interrupted() {
+            for handle in handles.iter() {
+                handle.interrupt();
+            }
+        }
+    }
+
     fn get_engine_id(engine_name: &str) -> Result<SyncEngineId> {
         SyncEngineId::try_from(engine_name).map_err(SyncManagerError::UnknownEngine)
     }
 
-    fn get_engine(engine_id: &SyncEngineId) -> Option<Box<dyn SyncEngine>> {
+    fn get_engine(&self, engine_id: &SyncEngineId) -> Option<Box<dyn SyncEngine>> {
+        if let Some(account_id) = &self.account_id {
+            return Self::get_engine_for_account(account_id, engine_id);
+        }
         match engine_id {
             SyncEngineId::History => places::get_registered_sync_engine(engine_id),
             SyncEngineId::Bookmarks => places::get_registered_sync_engine(engine_id),
@@ -42,15 +99,35 @@ impl SyncManager {
         }
     }
 
+    fn get_engine_for_account(
+        account_id: &str,
+        engine_id: &SyncEngineId,
+    ) -> Option<Box<dyn SyncEngine>> {
+        match engine_id {
+            SyncEngineId::History | SyncEngineId::Bookmarks => {
+                places::get_registered_sync_engine_for_account(account_id, engine_id)
+            }
+            SyncEngineId::Addresses | SyncEngineId::CreditCards => {
+                autofill::get_registered_sync_engine_for_account(account_id, engine_id)
+            }
+            SyncEngineId::Passwords => {
+                logins::get_registered_sync_engine_for_account(account_id, engine_id)
+            }
+            SyncEngineId::Tabs => {
+                tabs::get_registered_sync_engine_for_account(account_id, engine_id)
+            }
+        }
+    }
+
     pub fn wipe(&self, engine_name: &str) -> Result<()> {
-        if let Some(engine) = Self::get_engine(&Self::get_engine_id(engine_name)?) {
+        if let Some(engine) = self.get_engine(&Self::get_engine_id(engine_name)?) {
             engine.wipe()?;
         }
         Ok(())
     }
 
     pub fn reset(&self, engine_name: &str) -> Result<()> {
-        if let Some(engine) = Self::get_engine(&Self::get_engine_id(engine_name)?) {
+        if let Some(engine) = self.get_engine(&Self::get_engine_id(engine_name)?) {
             engine.reset(&EngineSyncAssociation::Disconnected)?;
         }
         Ok(())
@@ -67,7 +144,7 @@ impl SyncManager {
     pub fn disconnect(&self) {
         breadcrumb!("SyncManager disconnect()");
         for engine_id in SyncEngineId::iter() {
-            if let Some(engine) = Self::get_engine(&engine_id) {
+            if let Some(engine) = self.get_engine(&engine_id) {
                 if let Err(e) = engine.reset(&EngineSyncAssociation::Disconnected) {
                     error_support::report_error!(
                         "sync-manager-reset",
@@ -84,13 +161,30 @@ impl SyncManager {
 
     /// Perform a sync.  See [SyncParams] and [SyncResult] for details on how this works
     pub fn sync(&self, params: SyncParams) -> Result<SyncResult> {
+        self.sync_impl(params, None)
+    }
+
+    /// Perform a sync, reporting the progress of each engine to `listener`.
+    pub fn sync_with_progress(
+        &self,
+        params: SyncParams,
+        listener: Box<dyn SyncProgressListener>,
+    ) -> Result<SyncResult> {
+        self.sync_impl(params, Some(&*listener))
+    }
+
+    fn sync_impl(
+        &self,
+        params: SyncParams,
+        listener: Option<&dyn SyncProgressListener>,
+    ) -> Result<SyncResult> {
         breadcrumb!("SyncManager::sync started");
         let mut state = self.mem_cached_state.lock();
         let engines = self.calc_engines_to_sync(&params.engines)?;
         let next_sync_after = state.as_ref().and_then(|mcs| mcs.get_next_sync_after());
         let result = if !backoff_in_effect(next_sync_after, &params) {
             log::info!("No backoff in effect (or we decided to ignore it), starting sync");
-            self.do_sync(params, &mut state, engines)
+            self.do_sync(params, &mut state, engines, listener)
         } else {
             breadcrumb!(
                 "Backoff still in effect (until {:?}), bailing out early",
@@ -100,11 +194,16 @@ impl SyncManager {
                 status: ServiceStatus::BackedOff,
                 successful: Default::default(),
                 failures: Default::default(),
+                skipped: engines
+                    .iter()
+                    .map(|engine| (engine.collection_name().into(), EngineSkipReason::BackedOff))
+                    .collect(),
                 declined: None,
                 next_sync_allowed_at: next_sync_after,
                 persisted_state: params.persisted_state.unwrap_or_default(),
                 // It would be nice to record telemetry here.
                 telemetry_json: None,
+                received_uris: Vec::new(),
             })
         };
         breadcrumb!("SyncManager sync ended");
@@ -116,10 +215,11 @@ impl SyncManager {
         mut params: SyncParams,
         state: &mut Option<MemoryCachedState>,
         mut engines: Vec<Box<dyn SyncEngine>>,
+        listener: Option<&dyn SyncProgressListener>,
     ) -> Result<SyncResult> {
         let key_bundle = sync15::KeyBundle::from_ksync_base64(&params.auth_info.sync_key)?;
         let tokenserver_url = url::Url::parse(&params.auth_info.tokenserver_url)?;
-        let interruptee = interrupt_support::ShutdownInterruptee;
+        let interruptee = SyncInterruptee::new(&self.interrupt_counter);
         let mut mem_cached_state = state.take().unwrap_or_default();
         let mut disk_cached_state = params.persisted_state.take();
 
@@ -131,6 +231,7 @@ impl SyncManager {
         }
 
         let engine_refs: Vec<&dyn SyncEngine> = engines.iter().map(|s| &**s).collect();
+        self.set_engine_interrupt_handles(&engine_refs, &interruptee);
 
         let client_init = Sync15StorageClientInit {
             key_id: params.auth_info.kid.clone(),
@@ -148,7 +249,8 @@ impl SyncManager {
             device_name: params.device_settings.name,
             device_type: params.device_settings.kind,
         };
-        let c = SyncClient::new(settings);
+        let c = SyncClient::new(self, settings);
+        let progress_listener = listener.map(ProgressListenerAdapter);
         let result = sync_multiple_with_command_processor(
             Some(&c),
             &engine_refs,
@@ -160,8 +262,12 @@ impl SyncManager {
             Some(SyncRequestInfo {
                 engines_to_state_change: engines_to_change,
                 is_user_action: matches!(params.reason, SyncReason::User),
+                progress_listener: progress_listener
+                    .as_ref()
+                    .map(|l| l as &dyn sync15::client::SyncProgressListener),
             }),
         );
+        self.engine_interrupt_handles.lock().clear();
         *state = Some(mem_cached_state);
 
         log::info!("Sync finished with status {:?}", result.service_status);
@@ -181,21 +287,28 @@ impl SyncManager {
                 }
             }
         }
+        let skipped = result
+            .skipped_engines
+            .into_iter()
+            .map(|(engine, reason)| (engine, reason.into()))
+            .collect();
         let telemetry_json = serde_json::to_string(&result.telemetry).unwrap();
 
         Ok(SyncResult {
             status,
             successful,
             failures,
+            skipped,
             declined: result.declined,
             next_sync_allowed_at: result.next_sync_after,
             persisted_state: disk_cached_state.unwrap_or_default(),
             telemetry_json: Some(telemetry_json),
+            received_uris: c.take_received_uris(),
         })
     }
 
     fn iter_registered_engines(&self) -> impl Iterator<Item = (SyncEngineId, Box<dyn SyncEngine>)> {
-        SyncEngineId::iter().filter_map(|id| Self::get_engine(&id).map(|engine| (id, engine)))
+        SyncEngineId::iter().filter_map(|id| self.get_engine(&id).map(|engine| (id, engine)))
     }
 
     pub fn get_available_engines(&self) -> Vec<String> {
@@ -280,24 +393,124 @@ impl From<sync15::client::ServiceStatus> for ServiceStatus {
     }
 }
 
-struct SyncClient(Settings);
+impl From<sync15::client::EngineSkipReason> for EngineSkipReason {
+    fn from(reason: sync15::client::EngineSkipReason) -> Self {
+        use sync15::client::EngineSkipReason::*;
+        match reason {
+            KeysFailed => EngineSkipReason::KeysFailed,
+            ClientsFailed => EngineSkipReason::ClientsFailed,
+            Aborted => EngineSkipReason::Aborted,
+            BackedOff => EngineSkipReason::BackedOff,
+            Interrupted => EngineSkipReason::Interrupted,
+        }
+    }
+}
+
+impl From<sync15::client::EngineProgress> for EngineProgress {
+    fn from(progress: sync15::client::EngineProgress) -> Self {
+        use sync15::client::EngineProgress::*;
+        match progress {
+            Started => EngineProgress::Started,
+            Downloaded { count } => EngineProgress::Downloaded {
+                count: count.try_into().unwrap_or(u32::MAX),
+            },
+            Uploading { count } => EngineProgress::Uploading {
+                count: count.try_into().unwrap_or(u32::MAX),
+            },
+            Finished { status } => EngineProgress::Finished {
+                status: status.into(),
+            },
+        }
+    }
+}
+
+// Forwards the progress reported by sync15 to the app's listener.
+struct ProgressListenerAdapter<'a>(&'a dyn SyncProgressListener);
+
+impl<'a> sync15::client::SyncProgressListener for ProgressListenerAdapter<'a> {
+    fn on_engine_progress(&self, engine: &str, progress: sync15::client::EngineProgress) {
+        self.0
+            .on_engine_progress(engine.to_owned(), progress.into());
+    }
+}
 
-impl SyncClient {
-    pub fn new(settings: Settings) -> SyncClient {
-        SyncClient(settings)
+// Interrupts a sync when `SyncManager::interrupt()` is called while it's
+// running, or when we enter shutdown mode.
+struct SyncInterruptee<'a> {
+    interrupt_counter: &'a AtomicUsize,
+    start_value: usize,
+}
+
+impl<'a> SyncInterruptee<'a> {
+    fn new(interrupt_counter: &'a AtomicUsize) -> Self {
+        Self {
+            interrupt_counter,
+            start_value: interrupt_counter.load(Ordering::Relaxed),
+        }
     }
 }
 
-impl CommandProcessor for SyncClient {
+impl<'a> Interruptee for SyncInterruptee<'a> {
+    fn was_interrupted(&self) -> bool {
+        in_shutdown() || self.interrupt_counter.load(Ordering::Relaxed) != self.start_value
+    }
+}
+
+struct SyncClient<'a> {
+    // Commands are applied to the engines of the manager which is syncing, so
+    // a wipe for one account doesn't touch another account's stores.
+    manager: &'a SyncManager,
+    settings: Settings,
+    // Populated by `displayURI` commands as they are applied, and handed to
+    // the app via the `SyncResult`.
+    received_uris: Mutex<Vec<ReceivedUri>>,
+}
+
+impl<'a> SyncClient<'a> {
+    pub fn new(manager: &'a SyncManager, settings: Settings) -> SyncClient<'a> {
+        SyncClient {
+            manager,
+            settings,
+            received_uris: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn take_received_uris(&self) -> Vec<ReceivedUri> {
+        std::mem::take(&mut *self.received_uris.lock())
+    }
+}
+
+impl<'a> CommandProcessor for SyncClient<'a> {
     fn settings(&self) -> &Settings {
-        &self.0
+        &self.settings
     }
 
     fn apply_incoming_command(&self, command: Command) -> anyhow::Result<CommandStatus> {
         let result = match command {
-            Command::Wipe(engine) => wipe(&engine),
-            Command::Reset(engine) => reset(&engine),
-            Command::ResetAll => reset_all(),
+            Command::Wipe(engine) => self.manager.wipe(&engine),
+            Command::Reset(engine) => self.manager.reset(&engine),
+            Command::ResetAll => self.manager.reset_all(),
+            Command::DisplayUri {
+                uri,
+                sender_id,
+                title,
+            } => {
+                self.received_uris.lock().push(ReceivedUri {
+                    uri,
+                    sender_id,
+                    title,
+                });
+                Ok(())
+            }
+            Command::WipeExtension(ext_id) => match webext_storage::get_registered_store() {
+                Some(store) => store
+                    .clear(webext_storage::StorageArea::Sync, &ext_id)
+                    .map(|_| ())
+                    .map_err(|e| anyhow::Error::from(e).into()),
+                // Leave the command in our record until the app registers a
+                // store, like we do for engines we don't know about.
+                None => Err(SyncManagerError::UnknownEngine("extension-storage".into())),
+            },
         };
         match result {
             Ok(()) => Ok(CommandStatus::Applied),
@@ -316,6 +529,195 @@ impl CommandProcessor for SyncClient {
 #[cfg(test)]
 mod test {
     use super::*;
+    use sync15::engine::CollSyncIds;
+
+    fn test_settings() -> Settings {
+        Settings {
+            fxa_device_id: "deviceAAAAAA".into(),
+            device_name: "Phone".into(),
+            device_type: sync15::DeviceType::Mobile,
+        }
+    }
+
+    #[test]
+    fn test_progress_listener() {
+        struct Listener(Mutex<Vec<String>>);
+        impl SyncProgressListener for Listener {
+            fn on_engine_progress(&self, engine: String, progress: EngineProgress) {
+                self.0.lock().push(format!("{engine}: {progress:?}"));
+            }
+        }
+        let listener = Listener(Mutex::new(Vec::new()));
+        let adapter = ProgressListenerAdapter(&listener);
+        for progress in [
+            sync15::client::EngineProgress::Started,
+            sync15::client::EngineProgress::Downloaded { count: 3 },
+            sync15::client::EngineProgress::Uploading { count: 1 },
+            sync15::client::EngineProgress::Finished {
+                status: sync15::client::ServiceStatus::NetworkError,
+            },
+        ] {
+            sync15::client::SyncProgressListener::on_engine_progress(&adapter, "tabs", progress);
+        }
+        assert_eq!(
+            *listener.0.lock(),
+            vec![
+                "tabs: Started",
+                "tabs: Downloaded { count: 3 }",
+                "tabs: Uploading { count: 1 }",
+                "tabs: Finished { status: NetworkError }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let manager = SyncManager::new();
+        manager.interrupt();
+        // interrupting before a sync starts shouldn't affect it.
+        let interruptee = SyncInterruptee::new(&manager.interrupt_counter);
+        assert!(!interruptee.was_interrupted());
+        manager.interrupt();
+        assert!(interruptee.was_interrupted());
+        assert!(!SyncInterruptee::new(&manager.interrupt_counter).was_interrupted());
+    }
+
+    #[test]
+    fn test_interrupt_engines() {
+        let manager = SyncManager::new();
+        let store = Arc::new(logins::LoginStore::new_in_memory().unwrap());
+        let engine = logins::LoginsSyncEngine::new(store).unwrap();
+        let handle = engine.interrupt_handle().unwrap();
+        let engines: Vec<&dyn SyncEngine> = vec![&engine];
+
+        let interruptee = SyncInterruptee::new(&manager.interrupt_counter);
+        manager.set_engine_interrupt_handles(&engines, &interruptee);
+        let scope = handle.begin_interrupt_scope().unwrap();
+        manager.interrupt();
+        assert!(scope.was_interrupted());
+
+        // an engine registered after the sync was interrupted is interrupted
+        // straight away.
+        let scope = handle.begin_interrupt_scope().unwrap();
+        manager.set_engine_interrupt_handles(&engines, &interruptee);
+        assert!(scope.was_interrupted());
+    }
+
+    #[test]
+    fn test_display_uri_command() {
+        let manager = SyncManager::new();
+        let client = SyncClient::new(&manager, test_settings());
+        let status = client
+            .apply_incoming_command(Command::DisplayUri {
+                uri: "https://example.com".into(),
+                sender_id: "deviceBBBBBB".into(),
+                title: "Example".into(),
+            })
+            .unwrap();
+        assert_eq!(status, CommandStatus::Applied);
+        assert_eq!(
+            client.take_received_uris(),
+            vec![ReceivedUri {
+                uri: "https://example.com".into(),
+                sender_id: "deviceBBBBBB".into(),
+                title: "Example".into(),
+            }]
+        );
+        assert!(client.take_received_uris().is_empty());
+    }
+
+    #[test]
+    fn test_wipe_extension_command() {
+        let manager = SyncManager::new();
+        let client = SyncClient::new(&manager, test_settings());
+        let command = Command::WipeExtension("{ext-id}".into());
+        // Without a store, the command is kept so it can be retried.
+        assert_eq!(
+            client.apply_incoming_command(command.clone()).unwrap(),
+            CommandStatus::Unsupported
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(
+            webext_storage::WebExtStorageStore::new(dir.path().join("webext.sqlite")).unwrap(),
+        );
+        Arc::clone(&store).register_with_sync_manager();
+        store
+            .set(
+                webext_storage::StorageArea::Sync,
+                "{ext-id}",
+                serde_json::json!({"key": "value"}),
+            )
+            .unwrap();
+        assert_eq!(
+            client.apply_incoming_command(command).unwrap(),
+            CommandStatus::Applied
+        );
+        assert_eq!(
+            store
+                .get(
+                    webext_storage::StorageArea::Sync,
+                    "{ext-id}",
+                    serde_json::Value::Null
+                )
+                .unwrap(),
+            serde_json::json!({})
+        );
+    }
+
+    #[test]
+    fn test_engines_for_account() {
+        // The stores are registered globally and tests run in parallel, so
+        // each test uses its own account ids.
+        let store = Arc::new(tabs::TabsStore::new_with_mem_path(
+            "test-sync-manager-account",
+        ));
+        Arc::clone(&store).register_with_sync_manager_for_account("engines-account-1".into());
+        let manager = SyncManager::new_for_account("engines-account-1".into());
+        assert_eq!(manager.get_available_engines(), vec!["tabs".to_string()]);
+        let other = SyncManager::new_for_account("engines-account-2".into());
+        assert!(other.get_available_engines().is_empty());
+    }
+
+    #[test]
+    fn test_wipe_command_for_account() {
+        let store1 = Arc::new(tabs::TabsStore::new_with_mem_path(
+            "test-sync-manager-wipe-account-1",
+        ));
+        let store2 = Arc::new(tabs::TabsStore::new_with_mem_path(
+            "test-sync-manager-wipe-account-2",
+        ));
+        Arc::clone(&store1).register_with_sync_manager_for_account("wipe-account-1".into());
+        Arc::clone(&store2).register_with_sync_manager_for_account("wipe-account-2".into());
+        let connected = EngineSyncAssociation::Connected(CollSyncIds {
+            global: sync15::Guid::new("globalAAAAAA"),
+            coll: sync15::Guid::new("collAAAAAAAA"),
+        });
+        let engine_for = |account_id: &str| {
+            SyncManager::get_engine_for_account(account_id, &SyncEngineId::Tabs).unwrap()
+        };
+        for account_id in ["wipe-account-1", "wipe-account-2"] {
+            engine_for(account_id).reset(&connected).unwrap();
+        }
+
+        let manager = SyncManager::new_for_account("wipe-account-1".into());
+        let client = SyncClient::new(&manager, test_settings());
+        assert_eq!(
+            client
+                .apply_incoming_command(Command::Wipe("tabs".into()))
+                .unwrap(),
+            CommandStatus::Applied
+        );
+        // Only the syncing account's store should have been wiped.
+        assert_eq!(
+            engine_for("wipe-account-1").get_sync_assoc().unwrap(),
+            EngineSyncAssociation::Disconnected
+        );
+        assert_eq!(
+            engine_for("wipe-account-2").get_sync_assoc().unwrap(),
+            connected
+        );
+    }
 
     #[test]
     fn test_engine_id_sanity() {
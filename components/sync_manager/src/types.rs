@@ -67,6 +67,8 @@ pub struct SyncResult {
     pub successful: Vec<String>,
     // Maps the names of engines that failed to sync to the reason why
     pub failures: HashMap<String, String>,
+    // Maps the names of engines that weren't synced to the reason why
+    pub skipped: HashMap<String, EngineSkipReason>,
     // State that should be persisted to disk and supplied to the sync method
     // on the next sync (See SyncParams.persisted_state).
     pub persisted_state: String,
@@ -81,6 +83,37 @@ pub struct SyncResult {
     pub next_sync_allowed_at: Option<SystemTime>,
     // JSON string encoding a `SyncTelemetryPing` object
     pub telemetry_json: Option<String>,
+    // URIs sent to this device by other clients via the legacy "displayURI"
+    // command. Apps should display these in the same way as a received tab.
+    pub received_uris: Vec<ReceivedUri>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedUri {
+    // The URI to display.
+    pub uri: String,
+    // The client ID of the device which sent the URI.
+    pub sender_id: String,
+    // The title of the page, which may be empty.
+    pub title: String,
+}
+
+// How far an engine has got, reported to a `SyncProgressListener`.
+#[derive(Debug)]
+pub enum EngineProgress {
+    // We started syncing the engine.
+    Started,
+    // We downloaded `count` records changed by other devices.
+    Downloaded { count: u32 },
+    // We're uploading `count` records changed on this device.
+    Uploading { count: u32 },
+    // We finished syncing the engine. `status` is `Ok` if it synced
+    // successfully.
+    Finished { status: ServiceStatus },
+}
+
+pub trait SyncProgressListener: Send + Sync {
+    fn on_engine_progress(&self, engine: String, progress: EngineProgress);
 }
 
 #[derive(Debug)]
@@ -98,3 +131,20 @@ impl ServiceStatus {
         matches!(self, ServiceStatus::Ok)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineSkipReason {
+    // Something every engine depends on failed: getting a token, or fetching
+    // or uploading `meta/global` or `crypto/keys`.
+    KeysFailed,
+    // The clients collection failed to sync. Engines are only synced after
+    // commands from other clients, such as wipes, have been applied.
+    ClientsFailed,
+    // An error which affects every engine, such as a network or
+    // authentication error, ended the sync before this engine.
+    Aborted,
+    // The server asked us to back off, and the backoff hasn't passed yet.
+    BackedOff,
+    // The sync was interrupted before this engine.
+    Interrupted,
+}
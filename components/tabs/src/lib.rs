@@ -29,6 +29,9 @@ impl UniffiCustomTypeConverter for Timestamp {
 
 uniffi::include_scaffolding!("tabs");
 
+pub const UNIFFI_NAMESPACE: &str = "tabs";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 // Our UDL uses a `Guid` type.
 use sync_guid::Guid as TabsGuid;
 impl UniffiCustomTypeConverter for TabsGuid {
@@ -43,12 +46,12 @@ impl UniffiCustomTypeConverter for TabsGuid {
     }
 }
 
-pub use crate::storage::{ClientRemoteTabs, RemoteTabRecord, TabsDeviceType};
+pub use crate::storage::{ClientRemoteTabs, RemoteTabRecord, TabsDeviceType, TabsRedactionRules};
 pub use crate::store::{RemoteCommandStore, TabsStore};
 pub use error::{ApiResult, Error, Result, TabsApiError};
 use sync15::DeviceType;
 
-pub use crate::sync::engine::get_registered_sync_engine;
+pub use crate::sync::engine::{get_registered_sync_engine, get_registered_sync_engine_for_account};
 
 pub use crate::sync::bridge::TabsBridgedEngine;
 pub use crate::sync::engine::TabsEngine;
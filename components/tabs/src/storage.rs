@@ -36,6 +36,52 @@ const FAR_FUTURE: i64 = 4_102_405_200_000; // 2100/01/01
 const MAX_PAYLOAD_SIZE: usize = 512 * 1024; // Twice as big as desktop, still smaller than server max (2MB)
 const MAX_TITLE_CHAR_LENGTH: usize = 512; // We put an upper limit on title sizes for tabs to reduce memory
 
+/// Rules the app can configure to redact local tabs before they're uploaded,
+/// so tabs can be shared without leaking sensitive URLs. By default nothing is
+/// redacted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TabsRedactionRules {
+    /// Remove the query string and fragment from every uploaded URL.
+    pub strip_query_strings: bool,
+    /// URLs for these domains, and their subdomains, are never uploaded. A
+    /// leading "*." is allowed but not required.
+    pub private_domains: Vec<String>,
+}
+
+impl TabsRedactionRules {
+    fn is_private_url(&self, url: &str) -> bool {
+        if self.private_domains.is_empty() {
+            return false;
+        }
+        let Some(host) = url::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        else {
+            return false;
+        };
+        self.private_domains.iter().any(|domain| {
+            let domain = domain
+                .trim()
+                .trim_start_matches("*.")
+                .trim_start_matches('.')
+                .to_ascii_lowercase();
+            !domain.is_empty()
+                && host
+                    .strip_suffix(&domain)
+                    .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+        })
+    }
+
+    fn redact_url(&self, mut url: String) -> String {
+        if self.strip_query_strings {
+            if let Some(index) = url.find(['?', '#']) {
+                url.truncate(index);
+            }
+        }
+        url
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RemoteTab {
     pub title: String,
@@ -71,6 +117,7 @@ pub struct ClientRemoteTabs {
 // no remote tabs in an existing DB is also a normal situation)
 pub struct TabsStorage {
     local_tabs: RefCell<Option<Vec<RemoteTab>>>,
+    redaction_rules: TabsRedactionRules,
     db_path: PathBuf,
     db_connection: Option<Connection>,
 }
@@ -79,6 +126,7 @@ impl TabsStorage {
     pub fn new(db_path: impl AsRef<Path>) -> Self {
         Self {
             local_tabs: RefCell::default(),
+            redaction_rules: TabsRedactionRules::default(),
             db_path: db_path.as_ref().to_path_buf(),
             db_connection: None,
         }
@@ -139,6 +187,10 @@ impl TabsStorage {
         self.local_tabs.borrow_mut().replace(local_state);
     }
 
+    pub fn set_redaction_rules(&mut self, rules: TabsRedactionRules) {
+        self.redaction_rules = rules;
+    }
+
     // We try our best to fit as many tabs in a payload as possible, this includes
     // limiting the url history entries, title character count and finally drop enough tabs
     // until we have small enough payload that the server will accept
@@ -151,13 +203,18 @@ impl TabsStorage {
                     if tab.url_history.is_empty() || !is_url_syncable(&tab.url_history[0]) {
                         return None;
                     }
+                    // Don't upload the tab at all if its current page is private,
+                    // because the title would give it away.
+                    if self.redaction_rules.is_private_url(&tab.url_history[0]) {
+                        return None;
+                    }
                     let mut sanitized_history = Vec::with_capacity(TAB_ENTRIES_LIMIT);
                     for url in tab.url_history {
                         if sanitized_history.len() == TAB_ENTRIES_LIMIT {
                             break;
                         }
-                        if is_url_syncable(&url) {
-                            sanitized_history.push(url);
+                        if is_url_syncable(&url) && !self.redaction_rules.is_private_url(&url) {
+                            sanitized_history.push(self.redaction_rules.redact_url(url));
                         }
                     }
 
@@ -855,6 +912,52 @@ mod tests {
             ])
         );
     }
+    #[test]
+    fn test_redaction_rules() {
+        env_logger::try_init().ok();
+        let mut storage = TabsStorage::new_with_mem_path("test_redaction_rules");
+        storage.update_local_state(vec![
+            RemoteTab {
+                title: "Payroll".to_owned(),
+                url_history: vec!["https://hr.intranet.example/payroll".to_owned()],
+                last_used: 2,
+                ..Default::default()
+            },
+            RemoteTab {
+                title: "Search".to_owned(),
+                url_history: vec![
+                    "https://www.example.com/search?q=secret#results".to_owned(),
+                    "https://INTRANET.example/".to_owned(),
+                    "https://notintranet.example/?token=abc".to_owned(),
+                ],
+                last_used: 1,
+                ..Default::default()
+            },
+        ]);
+        // Nothing is redacted by default.
+        assert_eq!(storage.prepare_local_tabs_for_upload().unwrap().len(), 2);
+
+        storage.set_redaction_rules(TabsRedactionRules {
+            strip_query_strings: true,
+            private_domains: vec![
+                "*.intranet.example".to_owned(),
+                "intranet.example".to_owned(),
+            ],
+        });
+        assert_eq!(
+            storage.prepare_local_tabs_for_upload(),
+            Some(vec![RemoteTab {
+                title: "Search".to_owned(),
+                url_history: vec![
+                    "https://www.example.com/search".to_owned(),
+                    "https://notintranet.example/".to_owned(),
+                ],
+                last_used: 1,
+                ..Default::default()
+            }])
+        );
+    }
+
     #[test]
     fn test_trimming_tab_title() {
         env_logger::try_init().ok();
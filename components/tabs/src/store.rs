@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use crate::storage::{ClientRemoteTabs, RemoteTab, TabsStorage};
+use crate::storage::{ClientRemoteTabs, RemoteTab, TabsRedactionRules, TabsStorage};
 use crate::{ApiResult, PendingCommand, RemoteCommand};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -28,6 +28,11 @@ impl TabsStore {
         self.storage.lock().unwrap().update_local_state(local_state);
     }
 
+    /// Sets the rules used to redact local tabs before they're uploaded.
+    pub fn set_redaction_rules(&self, rules: TabsRedactionRules) {
+        self.storage.lock().unwrap().set_redaction_rules(rules);
+    }
+
     // like remote_tabs, but serves the uniffi layer
     pub fn get_all(&self) -> Vec<ClientRemoteTabs> {
         match self.remote_tabs() {
@@ -8,18 +8,17 @@ use crate::store::TabsStore;
 use crate::sync::record::{TabsRecord, TabsRecordTab};
 use anyhow::Result;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::sync::{Arc, RwLock};
 use sync15::bso::{IncomingBso, OutgoingBso, OutgoingEnvelope};
 use sync15::engine::{
-    CollSyncIds, CollectionRequest, EngineSyncAssociation, SyncEngine, SyncEngineId,
+    CollSyncIds, CollectionRequest, EngineRegistry, EngineSyncAssociation, SyncEngine, SyncEngineId,
 };
 use sync15::{telemetry, ClientData, CollectionName, DeviceType, RemoteClient, ServerTimestamp};
 use sync_guid::Guid;
 
-// Our "sync manager" will use whatever is stashed here.
+// Our "sync manager" will use whatever is registered here.
 lazy_static::lazy_static! {
-    // Mutex: just taken long enough to update the inner stuff
-    static ref STORE_FOR_MANAGER: Mutex<Weak<TabsStore>> = Mutex::new(Weak::new());
+    static ref STORES_FOR_MANAGER: EngineRegistry<TabsStore> = EngineRegistry::new();
 }
 
 /// Called by the sync manager to get a sync engine via the store previously
@@ -27,15 +26,29 @@ lazy_static::lazy_static! {
 pub fn get_registered_sync_engine(
     engine_id: &SyncEngineId,
 ) -> Option<Box<dyn sync15::engine::SyncEngine>> {
-    let weak = STORE_FOR_MANAGER.lock().unwrap();
-    match weak.upgrade() {
-        None => None,
-        Some(store) => match engine_id {
-            SyncEngineId::Tabs => Some(Box::new(TabsEngine::new(Arc::clone(&store)))),
-            // panicking here seems reasonable - it's a static error if this
-            // it hit, not something that runtime conditions can influence.
-            _ => unreachable!("can't provide unknown engine: {}", engine_id),
-        },
+    let store = STORES_FOR_MANAGER.get()?;
+    Some(create_sync_engine(store, engine_id))
+}
+
+/// Called by the sync manager to get a sync engine via the store previously
+/// registered with the sync manager for the specified account.
+pub fn get_registered_sync_engine_for_account(
+    account_id: &str,
+    engine_id: &SyncEngineId,
+) -> Option<Box<dyn sync15::engine::SyncEngine>> {
+    let store = STORES_FOR_MANAGER.get_for_account(account_id)?;
+    Some(create_sync_engine(store, engine_id))
+}
+
+fn create_sync_engine(
+    store: Arc<TabsStore>,
+    engine_id: &SyncEngineId,
+) -> Box<dyn sync15::engine::SyncEngine> {
+    match engine_id {
+        SyncEngineId::Tabs => Box::new(TabsEngine::new(store)),
+        // panicking here seems reasonable - it's a static error if this
+        // it hit, not something that runtime conditions can influence.
+        _ => unreachable!("can't provide unknown engine: {}", engine_id),
     }
 }
 
@@ -317,8 +330,14 @@ impl crate::TabsStore {
     // `register_with_sync_manager()` is logically what's happening so that's
     // the name it gets.
     pub fn register_with_sync_manager(self: Arc<Self>) {
-        let mut state = STORE_FOR_MANAGER.lock().unwrap();
-        *state = Arc::downgrade(&self);
+        STORES_FOR_MANAGER.register(&self);
+    }
+
+    // Like `register_with_sync_manager()`, but only makes this instance
+    // available to a sync manager created for the same account, so stores
+    // belonging to different accounts don't replace each other.
+    pub fn register_with_sync_manager_for_account(self: Arc<Self>, account_id: String) {
+        STORES_FOR_MANAGER.register_for_account(account_id, &self);
     }
 }
 
@@ -470,11 +489,7 @@ pub mod test {
         Arc::clone(&store).register_with_sync_manager();
         assert_eq!(Arc::strong_count(&store), 1);
         assert_eq!(Arc::weak_count(&store), 1);
-        let registered = STORE_FOR_MANAGER
-            .lock()
-            .unwrap()
-            .upgrade()
-            .expect("should upgrade");
+        let registered = STORES_FOR_MANAGER.get().expect("should upgrade");
         assert!(Arc::ptr_eq(&store, &registered));
         drop(registered);
         // should be no new references
@@ -482,7 +497,21 @@ pub mod test {
         assert_eq!(Arc::weak_count(&store), 1);
         // dropping the registered object should drop the registration.
         drop(store);
-        assert!(STORE_FOR_MANAGER.lock().unwrap().upgrade().is_none());
+        assert!(STORES_FOR_MANAGER.get().is_none());
+    }
+
+    #[test]
+    fn test_sync_manager_registration_for_account() {
+        let store1 = Arc::new(TabsStore::new_with_mem_path("test-registration-account-1"));
+        let store2 = Arc::new(TabsStore::new_with_mem_path("test-registration-account-2"));
+        Arc::clone(&store1).register_with_sync_manager_for_account("account-1".to_string());
+        Arc::clone(&store2).register_with_sync_manager_for_account("account-2".to_string());
+        assert!(get_registered_sync_engine_for_account("account-1", &SyncEngineId::Tabs).is_some());
+        assert!(get_registered_sync_engine_for_account("account-3", &SyncEngineId::Tabs).is_none());
+        // dropping one account's store shouldn't impact the other.
+        drop(store1);
+        assert!(get_registered_sync_engine_for_account("account-1", &SyncEngineId::Tabs).is_none());
+        assert!(get_registered_sync_engine_for_account("account-2", &SyncEngineId::Tabs).is_some());
     }
 
     #[test]
@@ -31,8 +31,8 @@ class WebExtStorageTest {
         val extId = "ab"
         val jsonString = """{"a":"a"}"""
 
-        store.set(extId, jsonString)
-        val data = store.get(extId, "null")
+        store.set(StorageArea.SYNC, extId, jsonString)
+        val data = store.get(StorageArea.SYNC, extId, "null")
         Assert.assertEquals(jsonString, data)
         store.close()
     }
@@ -43,8 +43,8 @@ class WebExtStorageTest {
         val extId = "ab"
         val jsonString = """{"a":"a","b":"b"}"""
 
-        store.set(extId, jsonString)
-        val change = store.remove("ab", """["b"]""").changes[0]
+        store.set(StorageArea.SYNC, extId, jsonString)
+        val change = store.remove(StorageArea.SYNC, "ab", """["b"]""").changes[0]
 
         Assert.assertEquals(change.key, "b")
         Assert.assertEquals(change.oldValue, """"b"""")
@@ -58,8 +58,8 @@ class WebExtStorageTest {
         val extId = "ab"
         val jsonString = """{"a":"a","b":"b"}"""
 
-        store.set(extId, jsonString)
-        val result = store.clear(extId)
+        store.set(StorageArea.SYNC, extId, jsonString)
+        val result = store.clear(StorageArea.SYNC, extId)
 
         val firstChange = result.changes[0]
         Assert.assertEquals(firstChange.key, "a")
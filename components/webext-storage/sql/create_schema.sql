@@ -2,10 +2,9 @@
 -- License, v. 2.0. If a copy of the MPL was not distributed with this
 -- file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
--- This is a very simple schema for a chrome.storage.* implementation. At time
--- of writing, only chrome.storage.sync is supported, but this can be trivially
--- enhanced to support chrome.storage.local (the api is identical, it's just a
--- different "bucket" and doesn't sync).
+-- This is a very simple schema for a chrome.storage.* implementation. Both
+-- chrome.storage.sync and chrome.storage.local are supported - the api is
+-- identical, local data is just in a different "bucket" and doesn't sync.
 --
 -- Even though the spec allows for a single extension to have any number of
 -- "keys", we've made the decision to store all keys for a given extension in a
@@ -47,6 +46,15 @@ CREATE TABLE IF NOT EXISTS storage_sync_mirror (
     CHECK((ext_id IS NULL AND data IS NULL) OR (ext_id IS NOT NULL AND data IS NOT NULL))
 );
 
+-- The same shape as storage_sync_data, but for chrome.storage.local. This data
+-- never syncs, so there are no tombstones or change counters.
+CREATE TABLE IF NOT EXISTS storage_local_data (
+    ext_id TEXT NOT NULL PRIMARY KEY,
+
+    /* The JSON payload. */
+    data TEXT NOT NULL
+);
+
 -- This table holds key-value metadata - primarily for sync.
 CREATE TABLE IF NOT EXISTS meta (
     key TEXT PRIMARY KEY,
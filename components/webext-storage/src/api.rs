@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use crate::error::*;
+use interrupt_support::Interruptee;
 use rusqlite::{Connection, Transaction};
 use serde::{ser::SerializeMap, Serialize, Serializer};
 
@@ -23,20 +24,47 @@ pub const SYNC_MAX_ITEMS: usize = 512;
 
 type JsonMap = Map<String, JsonValue>;
 
+/// The `chrome.storage` area an operation applies to. Both areas live in the
+/// same database, but only `Sync` data is synced or subject to quotas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageArea {
+    /// `storage.sync`
+    Sync,
+    /// `storage.local`
+    Local,
+}
+
 enum StorageChangeOp {
     Clear,
     Set(JsonValue),
     SetWithoutQuota(JsonValue),
 }
 
-fn get_from_db(conn: &Connection, ext_id: &str) -> Result<Option<JsonMap>> {
-    Ok(
-        match conn.try_query_one::<String, _>(
+impl StorageChangeOp {
+    // Either an empty map or explicit null is a removal.
+    fn is_delete(&self) -> bool {
+        match self {
+            StorageChangeOp::Clear => true,
+            StorageChangeOp::Set(JsonValue::Object(v)) => v.is_empty(),
+            StorageChangeOp::SetWithoutQuota(JsonValue::Object(v)) => v.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+fn get_from_db(conn: &Connection, area: StorageArea, ext_id: &str) -> Result<Option<JsonMap>> {
+    let sql = match area {
+        StorageArea::Sync => {
             "SELECT data FROM storage_sync_data
-             WHERE ext_id = :ext_id",
-            &[(":ext_id", &ext_id)],
-            true,
-        )? {
+             WHERE ext_id = :ext_id"
+        }
+        StorageArea::Local => {
+            "SELECT data FROM storage_local_data
+             WHERE ext_id = :ext_id"
+        }
+    };
+    Ok(
+        match conn.try_query_one::<String, _>(sql, &[(":ext_id", &ext_id)], true)? {
             Some(s) => match serde_json::from_str(&s)? {
                 JsonValue::Object(m) => Some(m),
                 // we could panic here as it's theoretically impossible, but we
@@ -48,17 +76,22 @@ fn get_from_db(conn: &Connection, ext_id: &str) -> Result<Option<JsonMap>> {
     )
 }
 
-fn save_to_db(tx: &Transaction<'_>, ext_id: &str, val: &StorageChangeOp) -> Result<()> {
-    // This function also handles removals. Either an empty map or explicit null
-    // is a removal. If there's a mirror record for this extension ID, then we
-    // must leave a tombstone behind for syncing.
-    let is_delete = match val {
-        StorageChangeOp::Clear => true,
-        StorageChangeOp::Set(JsonValue::Object(v)) => v.is_empty(),
-        StorageChangeOp::SetWithoutQuota(JsonValue::Object(v)) => v.is_empty(),
-        _ => false,
-    };
-    if is_delete {
+fn save_to_db(
+    tx: &Transaction<'_>,
+    area: StorageArea,
+    ext_id: &str,
+    val: &StorageChangeOp,
+) -> Result<()> {
+    match area {
+        StorageArea::Sync => save_to_sync_db(tx, ext_id, val),
+        StorageArea::Local => save_to_local_db(tx, ext_id, val),
+    }
+}
+
+fn save_to_sync_db(tx: &Transaction<'_>, ext_id: &str, val: &StorageChangeOp) -> Result<()> {
+    // This function also handles removals. If there's a mirror record for this
+    // extension ID, then we must leave a tombstone behind for syncing.
+    if val.is_delete() {
         let in_mirror = tx
             .try_query_one(
                 "SELECT EXISTS(SELECT 1 FROM storage_sync_mirror WHERE ext_id = :ext_id);",
@@ -119,8 +152,39 @@ fn save_to_db(tx: &Transaction<'_>, ext_id: &str, val: &StorageChangeOp) -> Resu
     Ok(())
 }
 
-fn remove_from_db(tx: &Transaction<'_>, ext_id: &str) -> Result<()> {
-    save_to_db(tx, ext_id, &StorageChangeOp::Clear)
+// `storage.local` data never syncs, so there are no tombstones, change
+// counters or quotas to worry about.
+fn save_to_local_db(tx: &Transaction<'_>, ext_id: &str, val: &StorageChangeOp) -> Result<()> {
+    if val.is_delete() {
+        log::trace!("saving local data for '{}': removing the row", ext_id);
+        tx.execute_cached(
+            "DELETE FROM storage_local_data WHERE ext_id = :ext_id",
+            rusqlite::named_params! {
+                ":ext_id": ext_id,
+            },
+        )?;
+    } else {
+        let sval = match val {
+            StorageChangeOp::Set(v) | StorageChangeOp::SetWithoutQuota(v) => v.to_string(),
+            StorageChangeOp::Clear => unreachable!(),
+        };
+        log::trace!("saving local data for '{}': writing", ext_id);
+        tx.execute_cached(
+            "INSERT INTO storage_local_data(ext_id, data)
+                VALUES (:ext_id, :data)
+                ON CONFLICT (ext_id) DO UPDATE
+                SET data = :data",
+            rusqlite::named_params! {
+                ":ext_id": ext_id,
+                ":data": &sval,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn remove_from_db(tx: &Transaction<'_>, area: StorageArea, ext_id: &str) -> Result<()> {
+    save_to_db(tx, area, ext_id, &StorageChangeOp::Clear)
 }
 
 // This is a "helper struct" for the callback part of the chrome.storage spec,
@@ -190,27 +254,39 @@ pub fn get_quota_size_of(key: &str, v: &JsonValue) -> usize {
 /// The implementation of `storage[.sync].set()`. On success this returns the
 /// StorageChanges defined by the chrome API - it's assumed the caller will
 /// arrange to deliver this to observers as defined in that API.
-pub fn set(tx: &Transaction<'_>, ext_id: &str, val: JsonValue) -> Result<StorageChanges> {
+///
+/// Extensions can store a lot of data, so this checks `signal` as it goes.
+pub fn set(
+    tx: &Transaction<'_>,
+    area: StorageArea,
+    ext_id: &str,
+    val: JsonValue,
+    signal: &dyn Interruptee,
+) -> Result<StorageChanges> {
     let val_map = match val {
         JsonValue::Object(m) => m,
         // Not clear what the error semantics should be yet. For now, pretend an empty map.
         _ => Map::new(),
     };
 
-    let mut current = get_from_db(tx, ext_id)?.unwrap_or_default();
+    let mut current = get_from_db(tx, area, ext_id)?.unwrap_or_default();
 
     let mut changes = StorageChanges::with_capacity(val_map.len());
 
     // iterate over the value we are adding/updating.
     for (k, v) in val_map.into_iter() {
+        signal.err_if_interrupted()?;
         let old_value = current.remove(&k);
-        if current.len() >= SYNC_MAX_ITEMS {
-            return Err(Error::QuotaError(QuotaReason::MaxItems));
-        }
-        // Reading the chrome docs literally re the quota, the length of the key
-        // is just the string len, but the value is the json val, as bytes
-        if get_quota_size_of(&k, &v) > SYNC_QUOTA_BYTES_PER_ITEM {
-            return Err(Error::QuotaError(QuotaReason::ItemBytes));
+        // `storage.local` has no quotas.
+        if area == StorageArea::Sync {
+            if current.len() >= SYNC_MAX_ITEMS {
+                return Err(Error::QuotaError(QuotaReason::MaxItems));
+            }
+            // Reading the chrome docs literally re the quota, the length of the key
+            // is just the string len, but the value is the json val, as bytes
+            if get_quota_size_of(&k, &v) > SYNC_QUOTA_BYTES_PER_ITEM {
+                return Err(Error::QuotaError(QuotaReason::ItemBytes));
+            }
         }
         let change = StorageValueChange {
             key: k.clone(),
@@ -221,8 +297,10 @@ pub fn set(tx: &Transaction<'_>, ext_id: &str, val: JsonValue) -> Result<Storage
         current.insert(k, v);
     }
 
+    signal.err_if_interrupted()?;
     save_to_db(
         tx,
+        area,
         ext_id,
         &StorageChangeOp::Set(JsonValue::Object(current)),
     )?;
@@ -249,9 +327,16 @@ fn get_keys(keys: JsonValue) -> Vec<(String, Option<JsonValue>)> {
 
 /// The implementation of `storage[.sync].get()` - on success this always
 /// returns a Json object.
-pub fn get(conn: &Connection, ext_id: &str, keys: JsonValue) -> Result<JsonValue> {
+pub fn get(
+    conn: &Connection,
+    area: StorageArea,
+    ext_id: &str,
+    keys: JsonValue,
+    signal: &dyn Interruptee,
+) -> Result<JsonValue> {
     // key is optional, or string or array of string or object keys
-    let maybe_existing = get_from_db(conn, ext_id)?;
+    let maybe_existing = get_from_db(conn, area, ext_id)?;
+    signal.err_if_interrupted()?;
     let mut existing = match (maybe_existing, keys.is_object()) {
         (None, true) => return Ok(keys),
         (None, false) => return Ok(JsonValue::Object(Map::new())),
@@ -265,6 +350,7 @@ pub fn get(conn: &Connection, ext_id: &str, keys: JsonValue) -> Result<JsonValue
     let keys_and_defaults = get_keys(keys);
     let mut result = Map::with_capacity(keys_and_defaults.len());
     for (key, maybe_default) in keys_and_defaults {
+        signal.err_if_interrupted()?;
         if let Some(v) = existing.remove(&key) {
             result.insert(key, v);
         } else if let Some(def) = maybe_default {
@@ -279,8 +365,13 @@ pub fn get(conn: &Connection, ext_id: &str, keys: JsonValue) -> Result<JsonValue
 /// The implementation of `storage[.sync].remove()`. On success this returns the
 /// StorageChanges defined by the chrome API - it's assumed the caller will
 /// arrange to deliver this to observers as defined in that API.
-pub fn remove(tx: &Transaction<'_>, ext_id: &str, keys: JsonValue) -> Result<StorageChanges> {
-    let mut existing = match get_from_db(tx, ext_id)? {
+pub fn remove(
+    tx: &Transaction<'_>,
+    area: StorageArea,
+    ext_id: &str,
+    keys: JsonValue,
+) -> Result<StorageChanges> {
+    let mut existing = match get_from_db(tx, area, ext_id)? {
         None => return Ok(StorageChanges::new()),
         Some(v) => v,
     };
@@ -302,6 +393,7 @@ pub fn remove(tx: &Transaction<'_>, ext_id: &str, keys: JsonValue) -> Result<Sto
     if !result.is_empty() {
         save_to_db(
             tx,
+            area,
             ext_id,
             &StorageChangeOp::SetWithoutQuota(JsonValue::Object(existing)),
         )?;
@@ -312,8 +404,8 @@ pub fn remove(tx: &Transaction<'_>, ext_id: &str, keys: JsonValue) -> Result<Sto
 /// The implementation of `storage[.sync].clear()`. On success this returns the
 /// StorageChanges defined by the chrome API - it's assumed the caller will
 /// arrange to deliver this to observers as defined in that API.
-pub fn clear(tx: &Transaction<'_>, ext_id: &str) -> Result<StorageChanges> {
-    let existing = match get_from_db(tx, ext_id)? {
+pub fn clear(tx: &Transaction<'_>, area: StorageArea, ext_id: &str) -> Result<StorageChanges> {
+    let existing = match get_from_db(tx, area, ext_id)? {
         None => return Ok(StorageChanges::new()),
         Some(v) => v,
     };
@@ -325,13 +417,18 @@ pub fn clear(tx: &Transaction<'_>, ext_id: &str) -> Result<StorageChanges> {
             old_value: Some(val),
         });
     }
-    remove_from_db(tx, ext_id)?;
+    remove_from_db(tx, area, ext_id)?;
     Ok(result)
 }
 
 /// The implementation of `storage[.sync].getBytesInUse()`.
-pub fn get_bytes_in_use(conn: &Connection, ext_id: &str, keys: JsonValue) -> Result<usize> {
-    let maybe_existing = get_from_db(conn, ext_id)?;
+pub fn get_bytes_in_use(
+    conn: &Connection,
+    area: StorageArea,
+    ext_id: &str,
+    keys: JsonValue,
+) -> Result<usize> {
+    let maybe_existing = get_from_db(conn, area, ext_id)?;
     let existing = match maybe_existing {
         None => return Ok(0),
         Some(v) => v,
@@ -360,13 +457,13 @@ pub struct UsageInfo {
     /// The extension id.
     pub ext_id: String,
     /// The number of keys the extension uses.
-    pub num_keys: usize,
+    pub num_keys: u64,
     /// The number of bytes used by the extension. This result is somewhat rough
     /// -- it doesn't bother counting the size of the extension ID, or data in
     /// the mirror, and favors returning the exact number of bytes used by the
     /// column (that is, the size of the JSON object) rather than replicating
     /// the `get_bytes_in_use` return value for all keys.
-    pub num_bytes: usize,
+    pub num_bytes: u64,
 }
 
 /// Exposes information about per-collection usage for the purpose of telemetry.
@@ -383,8 +480,8 @@ pub fn usage(db: &Connection) -> Result<Vec<UsageInfo>> {
     db.query_rows_into(sql, [], |row| {
         let ext_id: String = row.get("ext_id")?;
         let data: String = row.get("data")?;
-        let num_bytes = data.len();
-        let num_keys = serde_json::from_str::<JsonObject>(&data)?.len();
+        let num_bytes = data.len() as u64;
+        let num_keys = serde_json::from_str::<JsonObject>(&data)?.len() as u64;
         Ok(UsageInfo {
             ext_id,
             num_keys,
@@ -397,6 +494,7 @@ pub fn usage(db: &Connection) -> Result<Vec<UsageInfo>> {
 mod tests {
     use super::*;
     use crate::db::test::new_mem_db;
+    use interrupt_support::NeverInterrupts;
     use serde_json::json;
 
     #[test]
@@ -443,16 +541,28 @@ mod tests {
 
         // an empty store.
         for q in vec![JsonValue::Null, json!("foo"), json!(["foo"])].into_iter() {
-            assert_eq!(get(&tx, ext_id, q)?, json!({}));
+            assert_eq!(
+                get(&tx, StorageArea::Sync, ext_id, q, &NeverInterrupts)?,
+                json!({})
+            );
         }
 
         // Default values in an empty store.
         for q in vec![json!({ "foo": null }), json!({"foo": "default"})].into_iter() {
-            assert_eq!(get(&tx, ext_id, q.clone())?, q.clone());
+            assert_eq!(
+                get(&tx, StorageArea::Sync, ext_id, q.clone(), &NeverInterrupts)?,
+                q.clone()
+            );
         }
 
         // Single item in the store.
-        set(&tx, ext_id, json!({"foo": "bar" }))?;
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({"foo": "bar" }),
+            &NeverInterrupts,
+        )?;
         for q in vec![
             JsonValue::Null,
             json!("foo"),
@@ -462,7 +572,10 @@ mod tests {
         ]
         .into_iter()
         {
-            assert_eq!(get(&tx, ext_id, q)?, json!({"foo": "bar" }));
+            assert_eq!(
+                get(&tx, StorageArea::Sync, ext_id, q, &NeverInterrupts)?,
+                json!({"foo": "bar" })
+            );
         }
 
         // Default values in a non-empty store.
@@ -476,51 +589,102 @@ mod tests {
         ]
         .into_iter()
         {
-            assert_eq!(get(&tx, ext_id, q.clone())?, q.clone());
+            assert_eq!(
+                get(&tx, StorageArea::Sync, ext_id, q.clone(), &NeverInterrupts)?,
+                q.clone()
+            );
         }
 
         // more complex stuff, including changes checking.
         assert_eq!(
-            set(&tx, ext_id, json!({"foo": "new", "other": "also new" }))?,
+            set(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!({"foo": "new", "other": "also new" }),
+                &NeverInterrupts
+            )?,
             make_changes(&[
                 ("foo", Some(json!("bar")), Some(json!("new"))),
                 ("other", None, Some(json!("also new")))
             ])
         );
         assert_eq!(
-            get(&tx, ext_id, JsonValue::Null)?,
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                JsonValue::Null,
+                &NeverInterrupts
+            )?,
             json!({"foo": "new", "other": "also new"})
         );
-        assert_eq!(get(&tx, ext_id, json!("foo"))?, json!({"foo": "new"}));
         assert_eq!(
-            get(&tx, ext_id, json!(["foo", "other"]))?,
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!("foo"),
+                &NeverInterrupts
+            )?,
+            json!({"foo": "new"})
+        );
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!(["foo", "other"]),
+                &NeverInterrupts
+            )?,
             json!({"foo": "new", "other": "also new"})
         );
         assert_eq!(
-            get(&tx, ext_id, json!({"foo": null, "default": "yo"}))?,
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!({"foo": null, "default": "yo"}),
+                &NeverInterrupts
+            )?,
             json!({"foo": "new", "default": "yo"})
         );
 
         assert_eq!(
-            remove(&tx, ext_id, json!("foo"))?,
+            remove(&tx, StorageArea::Sync, ext_id, json!("foo"))?,
             make_changes(&[("foo", Some(json!("new")), None)]),
         );
 
         assert_eq!(
-            set(&tx, ext_id, json!({"foo": {"sub-object": "sub-value"}}))?,
+            set(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!({"foo": {"sub-object": "sub-value"}}),
+                &NeverInterrupts
+            )?,
             make_changes(&[("foo", None, Some(json!({"sub-object": "sub-value"}))),])
         );
 
         // XXX - other variants.
 
         assert_eq!(
-            clear(&tx, ext_id)?,
+            clear(&tx, StorageArea::Sync, ext_id)?,
             make_changes(&[
                 ("other", Some(json!("also new")), None),
                 ("foo", Some(json!({"sub-object": "sub-value"})), None),
             ]),
         );
-        assert_eq!(get(&tx, ext_id, JsonValue::Null)?, json!({}));
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                JsonValue::Null,
+                &NeverInterrupts
+            )?,
+            json!({})
+        );
 
         Ok(())
     }
@@ -535,13 +699,31 @@ mod tests {
         let prop = "test-prop";
         let value = "test-value";
 
-        set(&tx, ext_id, json!({ prop: value }))?;
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ prop: value }),
+            &NeverInterrupts,
+        )?;
 
         // this is the checkGetImpl part!
-        let mut data = get(&tx, ext_id, json!(null))?;
+        let mut data = get(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!(null),
+            &NeverInterrupts,
+        )?;
         assert_eq!(value, json!(data[prop]), "null getter worked for {}", prop);
 
-        data = get(&tx, ext_id, json!(prop))?;
+        data = get(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!(prop),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
             value,
             json!(data[prop]),
@@ -554,7 +736,13 @@ mod tests {
             "string getter should return an object with a single property"
         );
 
-        data = get(&tx, ext_id, json!([prop]))?;
+        data = get(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!([prop]),
+            &NeverInterrupts,
+        )?;
         assert_eq!(value, json!(data[prop]), "array getter worked for {}", prop);
         assert_eq!(
             data.as_object().unwrap().len(),
@@ -564,7 +752,13 @@ mod tests {
 
         // checkGetImpl() uses `{ [prop]: undefined }` - but json!() can't do that :(
         // Hopefully it's just testing a simple object, so we use `{ prop: null }`
-        data = get(&tx, ext_id, json!({ prop: null }))?;
+        data = get(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ prop: null }),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
             value,
             json!(data[prop]),
@@ -588,10 +782,22 @@ mod tests {
         let tx = db.transaction()?;
         let ext_id = "xyz";
 
-        set(&tx, ext_id, json!({"foo": "bar" }))?;
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({"foo": "bar" }),
+            &NeverInterrupts,
+        )?;
 
         assert_eq!(
-            set(&tx, ext_id, json!({"foo": "bar" }))?,
+            set(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!({"foo": "bar" }),
+                &NeverInterrupts
+            )?,
             make_changes(&[("foo", Some(json!("bar")), Some(json!("bar")))]),
         );
         Ok(())
@@ -605,11 +811,20 @@ mod tests {
         for i in 1..SYNC_MAX_ITEMS + 1 {
             set(
                 &tx,
+                StorageArea::Sync,
                 ext_id,
                 json!({ format!("key-{}", i): format!("value-{}", i) }),
+                &NeverInterrupts,
             )?;
         }
-        let e = set(&tx, ext_id, json!({"another": "another"})).unwrap_err();
+        let e = set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({"another": "another"}),
+            &NeverInterrupts,
+        )
+        .unwrap_err();
         match e {
             Error::QuotaError(QuotaReason::MaxItems) => {}
             _ => panic!("unexpected error type"),
@@ -628,14 +843,27 @@ mod tests {
         let val = "x".repeat(SYNC_QUOTA_BYTES_PER_ITEM - 5);
 
         // Key length doesn't push it over.
-        set(&tx, ext_id, json!({ "x": val }))?;
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "x": val }),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
-            get_bytes_in_use(&tx, ext_id, json!("x"))?,
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("x"))?,
             SYNC_QUOTA_BYTES_PER_ITEM - 2
         );
 
         // Key length does push it over.
-        let e = set(&tx, ext_id, json!({ "xxxx": val })).unwrap_err();
+        let e = set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "xxxx": val }),
+            &NeverInterrupts,
+        )
+        .unwrap_err();
         match e {
             Error::QuotaError(QuotaReason::ItemBytes) => {}
             _ => panic!("unexpected error type"),
@@ -653,29 +881,44 @@ mod tests {
         // Init an over quota db with a single key.
         save_to_db(
             &tx,
+            StorageArea::Sync,
             ext_id,
             &StorageChangeOp::SetWithoutQuota(json!({ "x": val })),
         )?;
 
         // Adding more data fails.
-        let e = set(&tx, ext_id, json!({ "y": "newvalue" })).unwrap_err();
+        let e = set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "y": "newvalue" }),
+            &NeverInterrupts,
+        )
+        .unwrap_err();
         match e {
             Error::QuotaError(QuotaReason::TotalBytes) => {}
             _ => panic!("unexpected error type"),
         };
 
         // Remove data does not fails.
-        remove(&tx, ext_id, json!["x"])?;
+        remove(&tx, StorageArea::Sync, ext_id, json!["x"])?;
 
         // Restore the over quota data.
         save_to_db(
             &tx,
+            StorageArea::Sync,
             ext_id,
             &StorageChangeOp::SetWithoutQuota(json!({ "y": val })),
         )?;
 
         // Overwrite with less data does not fail.
-        set(&tx, ext_id, json!({ "y": "lessdata" }))?;
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "y": "lessdata" }),
+            &NeverInterrupts,
+        )?;
 
         Ok(())
     }
@@ -686,29 +929,182 @@ mod tests {
         let tx = db.transaction()?;
         let ext_id = "xyz";
 
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(null))?, 0);
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(null))?,
+            0
+        );
 
-        set(&tx, ext_id, json!({ "a": "a" }))?; // should be 4
-        set(&tx, ext_id, json!({ "b": "bb" }))?; // should be 5
-        set(&tx, ext_id, json!({ "c": "ccc" }))?; // should be 6
-        set(&tx, ext_id, json!({ "n": 999_999 }))?; // should be 7
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "a": "a" }),
+            &NeverInterrupts,
+        )?; // should be 4
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "b": "bb" }),
+            &NeverInterrupts,
+        )?; // should be 5
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "c": "ccc" }),
+            &NeverInterrupts,
+        )?; // should be 6
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({ "n": 999_999 }),
+            &NeverInterrupts,
+        )?; // should be 7
 
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!("x"))?, 0);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!("a"))?, 4);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!("b"))?, 5);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!("c"))?, 6);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!("n"))?, 7);
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("x"))?,
+            0
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("a"))?,
+            4
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("b"))?,
+            5
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("c"))?,
+            6
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!("n"))?,
+            7
+        );
 
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(["a"]))?, 4);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(["a", "x"]))?, 4);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(["a", "b"]))?, 9);
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(["a", "c"]))?, 10);
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(["a"]))?,
+            4
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(["a", "x"]))?,
+            4
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(["a", "b"]))?,
+            9
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(["a", "c"]))?,
+            10
+        );
 
         assert_eq!(
-            get_bytes_in_use(&tx, ext_id, json!(["a", "b", "c", "n"]))?,
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(["a", "b", "c", "n"]))?,
+            22
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Sync, ext_id, json!(null))?,
             22
         );
-        assert_eq!(get_bytes_in_use(&tx, ext_id, json!(null))?, 22);
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_area() -> Result<()> {
+        let mut db = new_mem_db();
+        let tx = db.transaction()?;
+        let ext_id = "xyz";
+        set(
+            &tx,
+            StorageArea::Sync,
+            ext_id,
+            json!({"foo": "sync"}),
+            &NeverInterrupts,
+        )?;
+        // The sync quotas don't apply to local data.
+        let big = "x".repeat(SYNC_QUOTA_BYTES + 1);
+        set(
+            &tx,
+            StorageArea::Local,
+            ext_id,
+            json!({"foo": "local", "big": big}),
+            &NeverInterrupts,
+        )?;
+        for i in 0..SYNC_MAX_ITEMS {
+            set(
+                &tx,
+                StorageArea::Local,
+                ext_id,
+                json!({ format!("key-{}", i): i }),
+                &NeverInterrupts,
+            )?;
+        }
+
+        // Each area only sees its own data.
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!(null),
+                &NeverInterrupts
+            )?,
+            json!({"foo": "sync"})
+        );
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Local,
+                ext_id,
+                json!("foo"),
+                &NeverInterrupts
+            )?,
+            json!({"foo": "local"})
+        );
+        assert_eq!(
+            get_bytes_in_use(&tx, StorageArea::Local, ext_id, json!("big"))?,
+            SYNC_QUOTA_BYTES + 6
+        );
+
+        // Clearing local data leaves the synced data alone.
+        clear(&tx, StorageArea::Local, ext_id)?;
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Local,
+                ext_id,
+                json!(null),
+                &NeverInterrupts
+            )?,
+            json!({})
+        );
+        assert_eq!(
+            get(
+                &tx,
+                StorageArea::Sync,
+                ext_id,
+                json!(null),
+                &NeverInterrupts
+            )?,
+            json!({"foo": "sync"})
+        );
+        let num_local: u32 = tx.query_one("SELECT COUNT(*) FROM storage_local_data")?;
+        assert_eq!(num_local, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interrupted() -> Result<()> {
+        let mut db = new_mem_db();
+        let handle = db.interrupt_handle();
+        let tx = db.transaction()?;
+        let signal = handle.begin_interrupt_scope()?;
+        handle.interrupt();
+        assert!(set(&tx, StorageArea::Sync, "x", json!({"foo": "bar"}), &signal).is_err());
+        assert!(get(&tx, StorageArea::Sync, "x", JsonValue::Null, &signal).is_err());
         Ok(())
     }
 
@@ -717,13 +1113,48 @@ mod tests {
         let mut db = new_mem_db();
         let tx = db.transaction().unwrap();
         // '{"a":"a","b":"bb","c":"ccc","n":999999}': 39 bytes
-        set(&tx, "xyz", json!({ "a": "a" })).unwrap();
-        set(&tx, "xyz", json!({ "b": "bb" })).unwrap();
-        set(&tx, "xyz", json!({ "c": "ccc" })).unwrap();
-        set(&tx, "xyz", json!({ "n": 999_999 })).unwrap();
+        set(
+            &tx,
+            StorageArea::Sync,
+            "xyz",
+            json!({ "a": "a" }),
+            &NeverInterrupts,
+        )
+        .unwrap();
+        set(
+            &tx,
+            StorageArea::Sync,
+            "xyz",
+            json!({ "b": "bb" }),
+            &NeverInterrupts,
+        )
+        .unwrap();
+        set(
+            &tx,
+            StorageArea::Sync,
+            "xyz",
+            json!({ "c": "ccc" }),
+            &NeverInterrupts,
+        )
+        .unwrap();
+        set(
+            &tx,
+            StorageArea::Sync,
+            "xyz",
+            json!({ "n": 999_999 }),
+            &NeverInterrupts,
+        )
+        .unwrap();
 
         // '{"a":"a"}': 9 bytes
-        set(&tx, "abc", json!({ "a": "a" })).unwrap();
+        set(
+            &tx,
+            StorageArea::Sync,
+            "abc",
+            json!({ "a": "a" }),
+            &NeverInterrupts,
+        )
+        .unwrap();
 
         tx.commit().unwrap();
 
@@ -7,8 +7,8 @@ use crate::schema;
 use interrupt_support::{SqlInterruptHandle, SqlInterruptScope};
 use parking_lot::Mutex;
 use rusqlite::types::{FromSql, ToSql};
-use rusqlite::Connection;
 use rusqlite::OpenFlags;
+use rusqlite::{Connection, DatabaseName};
 use sql_support::open_database::open_database_with_flags;
 use sql_support::ConnExt;
 use std::ops::{Deref, DerefMut};
@@ -67,6 +67,23 @@ impl StorageDb {
         Ok(self.interrupt_handle.begin_interrupt_scope()?)
     }
 
+    /// Copies the database to a new file at `path`, using SQLite's online
+    /// backup API. The copy is consistent even if the database is written to
+    /// while the backup is in progress.
+    pub fn backup_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = normalize_path(path)?;
+        self.writer.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// Checkpoints the write-ahead log and truncates it, so a large write
+    /// doesn't leave a large WAL file behind.
+    pub fn checkpoint(&self) -> Result<()> {
+        self.writer
+            .pragma_update(None, "wal_checkpoint", "TRUNCATE")?;
+        Ok(())
+    }
+
     /// Closes the database connection. If there are any unfinalized prepared
     /// statements on the connection, `close` will fail and the `StorageDb` will
     /// remain open and the connection will be leaked - we used to return the
@@ -288,6 +305,13 @@ mod tests {
         // nothing.
     }
 
+    #[test]
+    fn test_busy_timeout() -> Result<()> {
+        let db = new_mem_db();
+        assert_eq!(db.query_one::<u32>("PRAGMA busy_timeout")?, 5000);
+        Ok(())
+    }
+
     #[test]
     fn test_meta() -> Result<()> {
         let writer = new_mem_db();
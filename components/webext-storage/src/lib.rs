@@ -24,12 +24,15 @@ pub use api::SYNC_QUOTA_BYTES;
 pub use api::SYNC_QUOTA_BYTES_PER_ITEM;
 
 pub use crate::error::{QuotaReason, WebExtStorageApiError};
-pub use crate::store::WebExtStorageStore;
+pub use crate::store::{get_registered_store, WebExtStorageStore};
 pub use api::UsageInfo;
-pub use api::{StorageChanges, StorageValueChange};
+pub use api::{StorageArea, StorageChanges, StorageValueChange};
 
 uniffi::include_scaffolding!("webext-storage");
 
+pub const UNIFFI_NAMESPACE: &str = "webextstorage";
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 use serde_json::Value as JsonValue;
 impl UniffiCustomTypeConverter for JsonValue {
     type Builtin = String;
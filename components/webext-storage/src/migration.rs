@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use crate::db::{get_meta, put_meta};
 use crate::error::*;
 use rusqlite::{named_params, Connection, OpenFlags, Transaction};
 use serde_json::{Map, Value};
@@ -16,6 +17,9 @@ use std::path::Path;
 // * We don't want to enforce the same quotas when migrating.
 // * We'd rather do the entire migration in a single transaction for perf
 //   reasons.
+// We only migrate once, and extensions which already have data (or a
+// tombstone) in our database are skipped, so the migration never overwrites
+// newer data.
 
 // The sqlite database we migrate from has a very simple structure:
 // * table collection_data with columns collection_name, record_id and record
@@ -108,7 +112,14 @@ struct Parsed<'a> {
     data: serde_json::Value,
 }
 
+// Set once we've migrated, so we don't do it again.
+pub(crate) const MIGRATED_META_KEY: &str = "kinto_migrated";
+
 pub fn migrate(tx: &Transaction<'_>, filename: &Path) -> Result<MigrationInfo> {
+    if get_meta::<bool>(tx, MIGRATED_META_KEY)?.unwrap_or_default() {
+        log::info!("already migrated, skipping");
+        return Ok(MigrationInfo::default());
+    }
     // We do the grouping manually, collecting string values as we go.
     let mut last_ext_id = "".to_string();
     let mut curr_values: Vec<(String, serde_json::Value)> = Vec::new();
@@ -124,8 +135,10 @@ pub fn migrate(tx: &Transaction<'_>, filename: &Path) -> Result<MigrationInfo> {
             if !last_ext_id.is_empty() && !curr_values.is_empty() {
                 // a different extension id - write what we have to the DB.
                 let entries = do_insert(tx, &last_ext_id, curr_values)?;
-                mi.extensions_successful += 1;
-                mi.entries_successful += entries;
+                if entries > 0 {
+                    mi.extensions_successful += 1;
+                    mi.entries_successful += entries;
+                }
             }
             last_ext_id = parsed.ext_id.to_string();
             curr_values = Vec::new();
@@ -144,10 +157,16 @@ pub fn migrate(tx: &Transaction<'_>, filename: &Path) -> Result<MigrationInfo> {
     if !last_ext_id.is_empty() && !curr_values.is_empty() {
         // a different extension id - write what we have to the DB.
         let entries = do_insert(tx, &last_ext_id, curr_values)?;
-        mi.extensions_successful += 1;
-        mi.entries_successful += entries;
+        if entries > 0 {
+            mi.extensions_successful += 1;
+            mi.entries_successful += entries;
+        }
     }
     log::info!("migrated {} extensions: {:?}", mi.extensions_successful, mi);
+    // If we couldn't read the source DB, we might have more luck next time.
+    if !mi.open_failure {
+        put_meta(tx, MIGRATED_META_KEY, &true)?;
+    }
     Ok(mi)
 }
 
@@ -206,21 +225,26 @@ fn read_rows(filename: &Path) -> (Vec<LegacyRow>, MigrationInfo) {
 /// key (which shouldn't be possible but who knows, database corruption causes
 /// strange things), chooses an arbitrary one. Returns the number of entries
 /// inserted, which could be different from `vals.len()` if multiple entries in
-/// `vals` have the same key.
+/// `vals` have the same key, and is zero if the extension already has data.
 fn do_insert(tx: &Transaction<'_>, ext_id: &str, vals: Vec<(String, Value)>) -> Result<usize> {
     let mut map = Map::with_capacity(vals.len());
     for (key, val) in vals {
         map.insert(key, val);
     }
     let num_entries = map.len();
-    tx.execute_cached(
-        "INSERT OR REPLACE INTO storage_sync_data(ext_id, data, sync_change_counter)
-         VALUES (:ext_id, :data, 1)",
+    let inserted = tx.execute_cached(
+        "INSERT INTO storage_sync_data(ext_id, data, sync_change_counter)
+         VALUES (:ext_id, :data, 1)
+         ON CONFLICT (ext_id) DO NOTHING",
         rusqlite::named_params! {
             ":ext_id": &ext_id,
             ":data": &Value::Object(map),
         },
     )?;
+    if inserted == 0 {
+        log::info!("skipping migration of '{}': it already has data", ext_id);
+        return Ok(0);
+    }
     Ok(num_entries)
 }
 
@@ -311,8 +335,9 @@ impl MigrationInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api;
+    use crate::api::{self, StorageArea};
     use crate::db::{test::new_mem_db, StorageDb};
+    use interrupt_support::NeverInterrupts;
     use serde_json::json;
     use tempfile::tempdir;
 
@@ -357,7 +382,8 @@ mod tests {
 
     fn assert_has(c: &Connection, ext_id: &str, expect: Value) {
         assert_eq!(
-            api::get(c, ext_id, json!(null)).expect("should get"),
+            api::get(c, StorageArea::Sync, ext_id, json!(null), &NeverInterrupts)
+                .expect("should get"),
             expect
         );
     }
@@ -400,6 +426,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_migrate_twice() {
+        let tmpdir = tempdir().unwrap();
+        let path = tmpdir.path().join("source.db");
+        init_source_db(&path, |c| {
+            c.execute_batch(HAPPY_PATH_SQL).expect("should populate")
+        });
+        let mut db = new_mem_db();
+        let tx = db.transaction().expect("tx should work");
+
+        // An extension which already has data isn't migrated.
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "https-everywhere@eff.org",
+            json!({"userRules": ["new"]}),
+            &NeverInterrupts,
+        )
+        .expect("should set");
+        let mi = migrate(&tx, &path).expect("migrate should work");
+        assert_eq!(
+            mi,
+            MigrationInfo {
+                entries_successful: 2,
+                extensions_successful: 1,
+                ..HAPPY_PATH_MIGRATION_INFO
+            }
+        );
+        assert_has(
+            &tx,
+            "https-everywhere@eff.org",
+            json!({"userRules": ["new"]}),
+        );
+
+        // Migrating again does nothing, so data removed since isn't restored.
+        api::clear(
+            &tx,
+            StorageArea::Sync,
+            "{e7fefcf3-b39c-4f17-5215-ebfe120a7031}",
+        )
+        .expect("should clear");
+        let mi = migrate(&tx, &path).expect("migrate should work");
+        assert_eq!(mi, MigrationInfo::default());
+        assert_has(&tx, "{e7fefcf3-b39c-4f17-5215-ebfe120a7031}", json!({}));
+    }
+
     #[test]
     fn test_sad_paths() {
         do_migrate(
@@ -16,7 +16,7 @@ pub struct WebExtMigrationLogin;
 
 impl MigrationLogic for WebExtMigrationLogin {
     const NAME: &'static str = "webext storage db";
-    const END_VERSION: u32 = 2;
+    const END_VERSION: u32 = 3;
 
     fn prepare(&self, conn: &Connection, _db_empty: bool) -> MigrationResult<()> {
         let initial_pragmas = "
@@ -26,6 +26,8 @@ impl MigrationLogic for WebExtMigrationLogin {
             PRAGMA journal_mode=WAL;
             -- foreign keys seem worth enforcing!
             PRAGMA foreign_keys = ON;
+            -- How long to wait for a lock before returning SQLITE_BUSY (in ms)
+            PRAGMA busy_timeout = 5000;
         ";
         conn.execute_batch(initial_pragmas)?;
         define_functions(conn)?;
@@ -42,6 +44,7 @@ impl MigrationLogic for WebExtMigrationLogin {
     fn upgrade_from(&self, db: &Transaction<'_>, version: u32) -> MigrationResult<()> {
         match version {
             1 => upgrade_from_1(db),
+            2 => upgrade_from_2(db),
             _ => Err(MigrationError::IncompatibleVersion(version)),
         }
     }
@@ -72,6 +75,17 @@ fn upgrade_from_1(db: &Connection) -> MigrationResult<()> {
     Ok(())
 }
 
+fn upgrade_from_2(db: &Connection) -> MigrationResult<()> {
+    // Added the storage.local table.
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS storage_local_data (
+            ext_id TEXT NOT NULL PRIMARY KEY,
+            data TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
 // Note that we expect this to be called before and after a sync - before to
 // ensure we are syncing with a clean state, after to be good memory citizens
 // given the temp tables are in memory.
@@ -174,4 +188,19 @@ mod tests {
         )?;
         Ok(())
     }
+
+    #[test]
+    fn test_upgrade_3() -> Result<()> {
+        let _ = env_logger::try_init();
+
+        let db_file = MigratedDatabaseFile::new(WebExtMigrationLogin, CREATE_SCHEMA_V1_SQL);
+        db_file.upgrade_to(3);
+        let db = db_file.open();
+
+        db.execute_batch(
+            "INSERT INTO storage_local_data(ext_id, data)
+             VALUES ('ext-id-1', '{\"foo\":\"bar\"}');",
+        )?;
+        Ok(())
+    }
 }
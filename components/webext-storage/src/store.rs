@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use crate::api::{self, StorageChanges};
+use crate::api::{self, StorageArea, StorageChanges};
 use crate::db::{StorageDb, ThreadSafeStorageDb};
 use crate::error::*;
 use crate::migration::{migrate, MigrationInfo};
@@ -10,13 +10,27 @@ use crate::sync;
 use std::path::Path;
 use std::sync::Arc;
 
-use interrupt_support::SqlInterruptHandle;
+use interrupt_support::{register_interrupt, SqlInterruptHandle};
 use serde_json::Value as JsonValue;
+use sync15::engine::EngineRegistry;
 
-/// A store is used to access `storage.sync` data. It manages an underlying
-/// database connection, and exposes methods for reading and writing storage
-/// items scoped to an extension ID. Each item is a JSON object, with one or
-/// more string keys, and values of any type that can serialize to JSON.
+// The sync manager applies remote commands, like wiping the storage of an
+// extension, to whatever store is stashed here.
+lazy_static::lazy_static! {
+    static ref STORES_FOR_MANAGER: EngineRegistry<WebExtStorageStore> = EngineRegistry::new();
+}
+
+/// Called by the sync manager to get the store previously registered with
+/// the sync manager.
+pub fn get_registered_store() -> Option<Arc<WebExtStorageStore>> {
+    STORES_FOR_MANAGER.get()
+}
+
+/// A store is used to access `storage.sync` and `storage.local` data. It
+/// manages an underlying database connection, and exposes methods for reading
+/// and writing storage items scoped to an extension ID and storage area. Each
+/// item is a JSON object, with one or more string keys, and values of any type
+/// that can serialize to JSON.
 ///
 /// An application should create only one store, and manage the instance as a
 /// singleton. While this isn't enforced, if you make multiple stores pointing
@@ -36,10 +50,10 @@ impl WebExtStorageStore {
     /// Creates a store backed by a database at `db_path`. The path can be a
     /// file path or `file:` URI.
     pub fn new(db_path: impl AsRef<Path>) -> Result<Self> {
-        let db = StorageDb::new(db_path)?;
-        Ok(Self {
-            db: Arc::new(ThreadSafeStorageDb::new(db)),
-        })
+        let db = Arc::new(ThreadSafeStorageDb::new(StorageDb::new(db_path)?));
+        // Interrupt any operations in progress when the app shuts down.
+        register_interrupt(Arc::<ThreadSafeStorageDb>::downgrade(&db));
+        Ok(Self { db })
     }
 
     /// Creates a store backed by an in-memory database.
@@ -58,10 +72,11 @@ impl WebExtStorageStore {
 
     /// Sets one or more JSON key-value pairs for an extension ID. Returns a
     /// list of changes, with existing and new values for each key in `val`.
-    pub fn set(&self, ext_id: &str, val: JsonValue) -> Result<StorageChanges> {
+    pub fn set(&self, area: StorageArea, ext_id: &str, val: JsonValue) -> Result<StorageChanges> {
+        let signal = self.db.begin_interrupt_scope()?;
         let db = self.db.lock();
         let tx = db.unchecked_transaction()?;
-        let result = api::set(&tx, ext_id, val)?;
+        let result = api::set(&tx, area, ext_id, val, &signal)?;
         tx.commit()?;
         Ok(result)
     }
@@ -88,20 +103,26 @@ impl WebExtStorageStore {
     ///
     /// This method always returns an object (that is, a
     /// `serde_json::Value::Object`).
-    pub fn get(&self, ext_id: &str, keys: JsonValue) -> Result<JsonValue> {
+    pub fn get(&self, area: StorageArea, ext_id: &str, keys: JsonValue) -> Result<JsonValue> {
         // Don't care about transactions here.
+        let signal = self.db.begin_interrupt_scope()?;
         let db = self.db.lock();
-        api::get(&db, ext_id, keys)
+        api::get(&db, area, ext_id, keys, &signal)
     }
 
     /// Deletes the values for one or more keys. As with `get`, `keys` can be
     /// either a single string key, or an array of string keys. Returns a list
     /// of changes, where each change contains the old value for each deleted
     /// key.
-    pub fn remove(&self, ext_id: &str, keys: JsonValue) -> Result<StorageChanges> {
+    pub fn remove(
+        &self,
+        area: StorageArea,
+        ext_id: &str,
+        keys: JsonValue,
+    ) -> Result<StorageChanges> {
         let db = self.db.lock();
         let tx = db.unchecked_transaction()?;
-        let result = api::remove(&tx, ext_id, keys)?;
+        let result = api::remove(&tx, area, ext_id, keys)?;
         tx.commit()?;
         Ok(result)
     }
@@ -109,19 +130,47 @@ impl WebExtStorageStore {
     /// Deletes all key-value pairs for the extension. As with `remove`, returns
     /// a list of changes, where each change contains the old value for each
     /// deleted key.
-    pub fn clear(&self, ext_id: &str) -> Result<StorageChanges> {
+    pub fn clear(&self, area: StorageArea, ext_id: &str) -> Result<StorageChanges> {
         let db = self.db.lock();
         let tx = db.unchecked_transaction()?;
-        let result = api::clear(&tx, ext_id)?;
+        let result = api::clear(&tx, area, ext_id)?;
         tx.commit()?;
         Ok(result)
     }
 
     /// Returns the bytes in use for the specified items (which can be null,
     /// a string, or an array)
-    pub fn get_bytes_in_use(&self, ext_id: &str, keys: JsonValue) -> Result<usize> {
+    pub fn get_bytes_in_use(
+        &self,
+        area: StorageArea,
+        ext_id: &str,
+        keys: JsonValue,
+    ) -> Result<u64> {
+        let db = self.db.lock();
+        Ok(api::get_bytes_in_use(&db, area, ext_id, keys)? as u64)
+    }
+
+    /// Writes a snapshot of the database to a new file at `path`. This can be
+    /// called while the store is in use, for example to move the database to
+    /// a new profile directory.
+    pub fn backup(&self, path: impl AsRef<Path>) -> Result<()> {
+        let db = self.db.lock();
+        db.backup_to(path)
+    }
+
+    /// Checkpoints and truncates the database's write-ahead log. Syncing does
+    /// this automatically, but apps on storage-constrained devices may also
+    /// want to call it after writing lots of data.
+    pub fn checkpoint(&self) -> Result<()> {
         let db = self.db.lock();
-        api::get_bytes_in_use(&db, ext_id, keys)
+        db.checkpoint()
+    }
+
+    /// Makes this store available to the sync manager, so it can apply the
+    /// "wipe extension storage" command sent by other devices. We only keep a
+    /// weak reference, so this doesn't keep the store alive.
+    pub fn register_with_sync_manager(self: Arc<Self>) {
+        STORES_FOR_MANAGER.register(&self);
     }
 
     /// Returns a bridged sync engine for Desktop for this store.
@@ -210,6 +259,67 @@ pub mod test {
         ensure_send::<WebExtStorageStore>();
     }
 
+    #[test]
+    fn test_register_with_sync_manager() -> Result<()> {
+        let store = Arc::new(new_mem_store());
+        Arc::clone(&store).register_with_sync_manager();
+        store.set(
+            StorageArea::Sync,
+            "ext-id",
+            serde_json::json!({"key": "value"}),
+        )?;
+
+        let registered = get_registered_store().expect("should be registered");
+        registered.clear(StorageArea::Sync, "ext-id")?;
+        assert_eq!(
+            store.get(StorageArea::Sync, "ext-id", JsonValue::Null)?,
+            serde_json::json!({})
+        );
+
+        drop(registered);
+        drop(store);
+        assert!(get_registered_store().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup() -> Result<()> {
+        let store = new_mem_store();
+        store.set(
+            StorageArea::Sync,
+            "ext-id",
+            serde_json::json!({"key": "value"}),
+        )?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("backup.sqlite");
+        store.backup(&path)?;
+
+        let backup = WebExtStorageStore::new(&path)?;
+        assert_eq!(
+            backup.get(StorageArea::Sync, "ext-id", JsonValue::Null)?,
+            serde_json::json!({"key": "value"})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkpoint() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("webext.sqlite");
+        let store = WebExtStorageStore::new(&path)?;
+        store.set(
+            StorageArea::Local,
+            "ext-id",
+            serde_json::json!({"key": "x".repeat(100_000)}),
+        )?;
+        let wal_path = dir.path().join("webext.sqlite-wal");
+        assert!(std::fs::metadata(&wal_path)?.len() > 0);
+
+        store.checkpoint()?;
+        assert_eq!(std::fs::metadata(&wal_path)?.len(), 0);
+        Ok(())
+    }
+
     pub fn new_mem_store() -> WebExtStorageStore {
         WebExtStorageStore {
             db: Arc::new(ThreadSafeStorageDb::new(crate::db::test::new_mem_db())),
@@ -10,6 +10,7 @@ use sync15::engine::ApplyResults;
 use sync_guid::Guid as SyncGuid;
 
 use crate::db::{delete_meta, get_meta, put_meta, ThreadSafeStorageDb};
+use crate::migration::MIGRATED_META_KEY;
 use crate::schema;
 use crate::sync::incoming::{apply_actions, get_incoming, plan_incoming, stage_incoming};
 use crate::sync::outgoing::{get_outgoing, record_uploaded, stage_outgoing};
@@ -156,6 +157,9 @@ impl sync15::engine::BridgedEngine for BridgedEngine {
         let shared_db = self.thread_safe_storage_db()?;
         let db = shared_db.lock();
         schema::create_empty_sync_temp_tables(&db)?;
+        // Applying incoming records can write a lot, so don't leave a large
+        // WAL behind.
+        db.checkpoint()?;
         Ok(())
     }
 
@@ -173,9 +177,12 @@ impl sync15::engine::BridgedEngine for BridgedEngine {
         let shared_db = self.thread_safe_storage_db()?;
         let db = shared_db.lock();
         let tx = db.unchecked_transaction()?;
-        // We assume the meta table is only used by sync.
-        tx.execute_batch(
-            "DELETE FROM storage_sync_data; DELETE FROM storage_sync_mirror; DELETE FROM meta;",
+        tx.execute_batch("DELETE FROM storage_sync_data; DELETE FROM storage_sync_mirror;")?;
+        // We assume the meta table is only used by sync, except for the flag
+        // which stops us migrating the old data again.
+        tx.execute(
+            "DELETE FROM meta WHERE key != :key",
+            rusqlite::named_params! { ":key": MIGRATED_META_KEY },
         )?;
         tx.commit()?;
         Ok(())
@@ -273,6 +280,11 @@ mod tests {
         let engine = super::BridgedEngine::new(&strong);
 
         setup_mock_data(&engine)?;
+        put_meta(
+            &engine.thread_safe_storage_db()?.lock(),
+            MIGRATED_META_KEY,
+            &true,
+        )?;
 
         engine.wipe()?;
 
@@ -281,7 +293,9 @@ mod tests {
 
         assert_eq!(query_count(&db, "storage_sync_data"), 0);
         assert_eq!(query_count(&db, "storage_sync_mirror"), 0);
-        assert_eq!(query_count(&db, "meta"), 0);
+        // Only the migration flag survives.
+        assert_eq!(query_count(&db, "meta"), 1);
+        assert_eq!(get_meta::<bool>(&db, MIGRATED_META_KEY)?, Some(true));
         Ok(())
     }
 
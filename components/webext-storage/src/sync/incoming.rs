@@ -477,7 +477,7 @@ pub fn apply_actions(
 mod tests {
     use super::super::test::new_syncable_mem_db;
     use super::*;
-    use crate::api;
+    use crate::api::{self, StorageArea};
     use interrupt_support::NeverInterrupts;
     use serde_json::{json, Value};
     use sync15::bso::IncomingBso;
@@ -613,7 +613,13 @@ mod tests {
         );
 
         // and finally the data itself - might as use the API here!
-        api::set(&tx, "ext_id", json!({"foo": "local"}))?;
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "ext_id",
+            json!({"foo": "local"}),
+            &NeverInterrupts,
+        )?;
         let incoming = get_incoming(&tx)?;
         assert_eq!(incoming.len(), 1);
         assert_eq!(
@@ -739,9 +745,21 @@ mod tests {
 
         // DeleteLocally - row should be entirely removed.
         let tx = db.transaction().expect("transaction should work");
-        api::set(&tx, "ext_id", json!({"foo": "local"}))?;
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "ext_id",
+            json!({"foo": "local"}),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
-            api::get(&tx, "ext_id", json!(null))?,
+            api::get(
+                &tx,
+                StorageArea::Sync,
+                "ext_id",
+                json!(null),
+                &NeverInterrupts
+            )?,
             json!({"foo": "local"})
         );
         let changes = changes![change!("foo", "local", None)];
@@ -752,7 +770,16 @@ mod tests {
                 changes: changes.clone(),
             },
         );
-        assert_eq!(api::get(&tx, "ext_id", json!(null))?, json!({}));
+        assert_eq!(
+            api::get(
+                &tx,
+                StorageArea::Sync,
+                "ext_id",
+                json!(null),
+                &NeverInterrupts
+            )?,
+            json!({})
+        );
         // and there should not be a local record at all.
         assert!(get_local_item(&tx).is_none());
         assert_eq!(get_applied_item_changes(&tx), Some(changes));
@@ -760,9 +787,21 @@ mod tests {
 
         // TakeRemote - replace local data with remote and marked as not dirty.
         let tx = db.transaction().expect("transaction should work");
-        api::set(&tx, "ext_id", json!({"foo": "local"}))?;
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "ext_id",
+            json!({"foo": "local"}),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
-            api::get(&tx, "ext_id", json!(null))?,
+            api::get(
+                &tx,
+                StorageArea::Sync,
+                "ext_id",
+                json!(null),
+                &NeverInterrupts
+            )?,
             json!({"foo": "local"})
         );
         // data should exist locally with a change recorded.
@@ -795,9 +834,21 @@ mod tests {
 
         // Merge - like ::TakeRemote, but data remains dirty.
         let tx = db.transaction().expect("transaction should work");
-        api::set(&tx, "ext_id", json!({"foo": "local"}))?;
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "ext_id",
+            json!({"foo": "local"}),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
-            api::get(&tx, "ext_id", json!(null))?,
+            api::get(
+                &tx,
+                StorageArea::Sync,
+                "ext_id",
+                json!(null),
+                &NeverInterrupts
+            )?,
             json!({"foo": "local"})
         );
         // data should exist locally with a change recorded.
@@ -829,9 +880,21 @@ mod tests {
 
         // Same - data stays the same but is marked not dirty.
         let tx = db.transaction().expect("transaction should work");
-        api::set(&tx, "ext_id", json!({"foo": "local"}))?;
+        api::set(
+            &tx,
+            StorageArea::Sync,
+            "ext_id",
+            json!({"foo": "local"}),
+            &NeverInterrupts,
+        )?;
         assert_eq!(
-            api::get(&tx, "ext_id", json!(null))?,
+            api::get(
+                &tx,
+                StorageArea::Sync,
+                "ext_id",
+                json!(null),
+                &NeverInterrupts
+            )?,
             json!({"foo": "local"})
         );
         // data should exist locally with a change recorded.
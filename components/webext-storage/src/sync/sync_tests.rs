@@ -6,7 +6,7 @@
 // fetching incoming items, generating items to upload, then updating the local
 // state (including the mirror) as a result.
 
-use crate::api::{clear, get, set};
+use crate::api::{clear, get, set, StorageArea};
 use crate::error::*;
 use crate::schema::create_empty_sync_temp_tables;
 use crate::sync::incoming::{apply_actions, get_incoming, plan_incoming, stage_incoming};
@@ -57,7 +57,13 @@ fn do_sync(
 
 // Check *both* the mirror and local API have ended up with the specified data.
 fn check_finished_with(conn: &Connection, ext_id: &str, val: serde_json::Value) -> Result<()> {
-    let local = get(conn, ext_id, serde_json::Value::Null)?;
+    let local = get(
+        conn,
+        StorageArea::Sync,
+        ext_id,
+        serde_json::Value::Null,
+        &NeverInterrupts,
+    )?;
     assert_eq!(local, val);
     let guid = get_mirror_guid(conn, ext_id)?;
     let mirror = get_mirror_data(conn, &guid);
@@ -143,7 +149,13 @@ fn test_simple_outgoing_sync() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        data.clone(),
+        &NeverInterrupts,
+    )?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     check_finished_with(&tx, "ext-id", data)?;
     Ok(())
@@ -156,7 +168,13 @@ fn test_simple_incoming_sync() -> Result<()> {
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
     let bridge_record = make_incoming(&Guid::new("guid"), "ext-id", &data);
     assert_eq!(do_sync(&tx, &[bridge_record])?.len(), 0);
-    let key1_from_api = get(&tx, "ext-id", json!("key1"))?;
+    let key1_from_api = get(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        json!("key1"),
+        &NeverInterrupts,
+    )?;
     assert_eq!(key1_from_api, json!({"key1": "key1-value"}));
     check_finished_with(&tx, "ext-id", data)?;
     Ok(())
@@ -169,21 +187,27 @@ fn test_outgoing_tombstone() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        data.clone(),
+        &NeverInterrupts,
+    )?;
     assert_eq!(
         get_local_data(&tx, "ext-id"),
         DbData::Data(data.to_string())
     );
     // hasn't synced yet, so clearing shouldn't write a tombstone.
-    clear(&tx, "ext-id")?;
+    clear(&tx, StorageArea::Sync, "ext-id")?;
     assert_eq!(get_local_data(&tx, "ext-id"), DbData::NoRow);
     // now set data again and sync and *then* remove.
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     assert!(get_local_data(&tx, "ext-id").has_data());
     let guid = get_mirror_guid(&tx, "ext-id")?;
     assert!(get_mirror_data(&tx, &guid).has_data());
-    clear(&tx, "ext-id")?;
+    clear(&tx, StorageArea::Sync, "ext-id")?;
     assert_eq!(get_local_data(&tx, "ext-id"), DbData::NullRow);
     // then after syncing, the tombstone will be in the mirror but the local row
     // has been removed.
@@ -200,7 +224,13 @@ fn test_incoming_tombstone_exists() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        data.clone(),
+        &NeverInterrupts,
+    )?;
     assert_eq!(
         get_local_data(&tx, "ext-id"),
         DbData::Data(data.to_string())
@@ -245,7 +275,7 @@ fn test_reconciled() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     // Incoming payload with the same data
     let record = make_incoming(&Guid::new("guid"), "ext-id", &json!({"key1": "key1-value"}));
     // Should be no outgoing records as we reconciled.
@@ -261,7 +291,13 @@ fn test_reconcile_with_null_payload() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        data.clone(),
+        &NeverInterrupts,
+    )?;
     // We try to push this change on the next sync.
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
@@ -283,10 +319,10 @@ fn test_accept_incoming_when_local_is_deleted() -> Result<()> {
     // We only record an extension as deleted locally if it has been
     // uploaded before being deleted.
     let data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
-    clear(&tx, "ext-id")?;
+    clear(&tx, StorageArea::Sync, "ext-id")?;
     // Incoming payload without 'key1'. Because we previously uploaded
     // key1, this means another client deleted it.
     let record = make_incoming(&guid, "ext-id", &json!({"key2": "key2-value"}));
@@ -302,9 +338,9 @@ fn test_accept_incoming_when_local_is_deleted_no_mirror() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
-    clear(&tx, "ext-id")?;
+    clear(&tx, StorageArea::Sync, "ext-id")?;
 
     // Use a random guid so that we don't find the mirrored data.
     // This test is somewhat bad because deduping might obviate
@@ -322,7 +358,7 @@ fn test_accept_deleted_key_mirrored() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
     // Incoming payload without 'key1'. Because we previously uploaded
@@ -339,7 +375,7 @@ fn test_merged_no_mirror() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     // Incoming payload without 'key1' and some data for 'key2'.
     // Because we never uploaded 'key1', we merge our local values
     // with the remote.
@@ -358,12 +394,18 @@ fn test_merged_incoming() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let old_data = json!({"key1": "key1-value", "key2": "key2-value", "doomed_key": "deletable"});
-    set(&tx, "ext-id", old_data)?;
+    set(&tx, StorageArea::Sync, "ext-id", old_data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
     // We update 'key1' locally.
     let local_data = json!({"key1": "key1-new", "key2": "key2-value", "doomed_key": "deletable"});
-    set(&tx, "ext-id", local_data)?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        local_data,
+        &NeverInterrupts,
+    )?;
     // Incoming payload where another client set 'key2' and removed
     // the 'doomed_key'.
     // Because we never uploaded our data, we'll merge our
@@ -388,7 +430,13 @@ fn test_merged_with_null_payload() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let old_data = json!({"key1": "key1-value"});
-    set(&tx, "ext-id", old_data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        old_data.clone(),
+        &NeverInterrupts,
+    )?;
     // Push this change remotely.
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
@@ -397,7 +445,13 @@ fn test_merged_with_null_payload() -> Result<()> {
         DbData::Data(old_data.to_string())
     );
     let local_data = json!({"key1": "key1-new", "key2": "key2-value"});
-    set(&tx, "ext-id", local_data.clone())?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        local_data.clone(),
+        &NeverInterrupts,
+    )?;
     // Incoming payload with the same old data.
     let record = make_incoming(&guid, "ext-id", &old_data);
     // Three-way-merge will not detect any change in key1, so we
@@ -412,7 +466,7 @@ fn test_deleted_mirrored_object_accept() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
     // Incoming payload with data deleted.
@@ -429,13 +483,21 @@ fn test_deleted_mirrored_object_accept() -> Result<()> {
 fn test_deleted_mirrored_object_merged() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
-    set(&tx, "ext-id", json!({"key1": "key1-value"}))?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        json!({"key1": "key1-value"}),
+        &NeverInterrupts,
+    )?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
     set(
         &tx,
+        StorageArea::Sync,
         "ext-id",
         json!({"key1": "key1-new", "key2": "key2-value"}),
+        &NeverInterrupts,
     )?;
     // Incoming payload with data deleted.
     // We synchronize this deletion by deleting the keys we think
@@ -453,7 +515,13 @@ fn test_deleted_mirrored_tombstone_merged() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     // Sync some data so we can get the guid for this extension.
-    set(&tx, "ext-id", json!({"key1": "key1-value"}))?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        json!({"key1": "key1-value"}),
+        &NeverInterrupts,
+    )?;
     assert_eq!(do_sync(&tx, &[])?.len(), 1);
     let guid = get_mirror_guid(&tx, "ext-id")?;
     // Sync a delete for this data so we have a tombstone in the mirror.
@@ -462,7 +530,13 @@ fn test_deleted_mirrored_tombstone_merged() -> Result<()> {
     assert_eq!(get_mirror_data(&tx, &guid), DbData::NullRow);
 
     // Set some data and sync it simultaneously with another incoming delete.
-    set(&tx, "ext-id", json!({"key2": "key2-value"}))?;
+    set(
+        &tx,
+        StorageArea::Sync,
+        "ext-id",
+        json!({"key2": "key2-value"}),
+        &NeverInterrupts,
+    )?;
     let record = make_incoming_tombstone(&guid);
     // We cannot delete any matching keys because there are no
     // matching keys. Instead we push our data.
@@ -476,7 +550,7 @@ fn test_deleted_not_mirrored_object_merged() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     // Incoming payload with data deleted.
     let record = make_incoming_tombstone(&Guid::new("guid"));
     // We normally delete the keys we think were on the server, but
@@ -496,7 +570,7 @@ fn test_conflicting_incoming() -> Result<()> {
     let mut db = new_syncable_mem_db();
     let tx = db.transaction()?;
     let data = json!({"key1": "key1-value", "key2": "key2-value"});
-    set(&tx, "ext-id", data)?;
+    set(&tx, StorageArea::Sync, "ext-id", data, &NeverInterrupts)?;
     // Incoming payload without 'key1' and conflicting for 'key2'.
     // Because we never uploaded either of our keys, we'll merge our
     // key1 in, but the server key2 wins.
@@ -187,6 +187,7 @@ fn run_add_address(store: &Store) -> Result<()> {
         country: prompt_string("country").unwrap_or_default(),
         tel: prompt_string("tel").unwrap_or_default(),
         email: prompt_string("email").unwrap_or_default(),
+        label: prompt_string("label").unwrap_or_default(),
     };
 
     println!("Making `add_address` api call");
@@ -229,6 +230,7 @@ fn run_update_address(store: &Store, guid: String) -> Result<()> {
         country: update_string("country", address.country),
         tel: update_string("tel", address.tel),
         email: update_string("email", address.email),
+        label: update_string("label", address.label),
     };
 
     println!("Making `update_address` api call for guid {}", guid);
@@ -8,22 +8,28 @@
 use std::ffi::CString;
 use std::os::raw::c_char;
 
-pub use autofill;
-pub use crashtest;
-pub use error_support;
-pub use fxa_client;
-pub use logins;
-pub use nimbus;
-pub use places;
-pub use push;
-pub use remote_settings;
-pub use rust_log_forwarder;
-pub use suggest;
-pub use sync_manager;
-pub use tabs;
+megazord_registry::megazord_components!(
+    autofill,
+    crashtest,
+    error_support,
+    fxa_client,
+    interrupt_support,
+    logins,
+    megazord_registry,
+    nimbus,
+    places,
+    push,
+    remote_settings,
+    rust_log_forwarder,
+    suggest,
+    sync15,
+    sync_manager,
+    tabs,
+    // TODO: Uncomment this code when webext-storage component is integrated in android
+    // webext_storage,
+);
+// viaduct has no UniFFI namespace.
 pub use viaduct;
-// TODO: Uncomment this code when webext-storage component is integrated in android
-// pub use webext_storage;
 
 /// In order to support the use case of consumers who don't know about megazords
 /// and don't need our e.g. networking or logging, we consider initialization
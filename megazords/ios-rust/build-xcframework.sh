@@ -147,6 +147,7 @@ $CARGO uniffi-bindgen generate "$REPO_ROOT/components/remote_settings/src/remote
 $CARGO uniffi-bindgen generate "$REPO_ROOT/components/nimbus/src/nimbus.udl" -l swift -o "$COMMON/Headers"
 $CARGO uniffi-bindgen generate "$REPO_ROOT/components/support/error/src/errorsupport.udl" -l swift -o "$COMMON/Headers"
 $CARGO uniffi-bindgen generate "$REPO_ROOT/components/support/rust-log-forwarder/src/rust_log_forwarder.udl" -l swift -o "$COMMON/Headers"
+$CARGO uniffi-bindgen generate "$REPO_ROOT/components/support/megazord-registry/src/megazord_registry.udl" -l swift -o "$COMMON/Headers"
 
 
 
@@ -10,3 +10,4 @@
 #import "errorFFI.h"
 #import "remote_settingsFFI.h"
 #import "rustlogforwarderFFI.h"
+#import "megazordregistryFFI.h"
@@ -5,8 +5,12 @@
 #![allow(unknown_lints)]
 #![warn(rust_2018_idioms)]
 
-pub use error_support;
-pub use nimbus;
-pub use remote_settings;
-pub use rust_log_forwarder;
+megazord_registry::megazord_components!(
+    error_support,
+    megazord_registry,
+    nimbus,
+    remote_settings,
+    rust_log_forwarder,
+);
+// viaduct_reqwest has no UniFFI namespace.
 pub use viaduct_reqwest;
@@ -5,19 +5,23 @@
 #![allow(unknown_lints)]
 #![warn(rust_2018_idioms)]
 
-pub use as_ohttp_client;
-pub use autofill;
-pub use crashtest;
-pub use error_support;
-pub use fxa_client;
-pub use logins;
-pub use nimbus;
-pub use places;
-pub use push;
-pub use remote_settings;
-pub use rust_log_forwarder;
-pub use suggest;
-pub use sync15;
-pub use sync_manager;
-pub use tabs;
+megazord_registry::megazord_components!(
+    as_ohttp_client,
+    autofill,
+    crashtest,
+    error_support,
+    fxa_client,
+    logins,
+    megazord_registry,
+    nimbus,
+    places,
+    push,
+    remote_settings,
+    rust_log_forwarder,
+    suggest,
+    sync15,
+    sync_manager,
+    tabs,
+);
+// viaduct_reqwest has no UniFFI namespace.
 pub use viaduct_reqwest;
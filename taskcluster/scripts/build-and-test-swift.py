@@ -24,6 +24,7 @@ BINDINGS_UDL_PATHS = [
     "components/sync_manager/src/syncmanager.udl",
     "components/tabs/src/tabs.udl",
     "components/support/rust-log-forwarder/src/rust_log_forwarder.udl",
+    "components/support/megazord-registry/src/megazord_registry.udl",
 ]
 
 # List of udl_paths to generate bindings for
@@ -32,6 +33,7 @@ FOCUS_UDL_PATHS = [
     "components/remote_settings/src/remote_settings.udl",
     "components/support/error/src/errorsupport.udl",
     "components/support/rust-log-forwarder/src/rust_log_forwarder.udl",
+    "components/support/megazord-registry/src/megazord_registry.udl",
 ]
 
 # List of globs to copy the sources from